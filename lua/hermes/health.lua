@@ -0,0 +1,6 @@
+-- Found by :checkhealth, the checks themselves are implemented in Rust
+return {
+    check = function()
+        require("hermes").health()
+    end,
+}
@@ -0,0 +1,36 @@
+-- Drives prompt turns while Neovim is idle, every tick lets Rust run them for a moment
+local M = {}
+
+local timer = nil
+local scheduled = false
+
+local function stop()
+    timer:stop()
+    timer:close()
+    timer = nil
+end
+
+--- Ticks every `interval` milliseconds until no turn is left running
+function M.start(interval)
+    if timer then
+        return
+    end
+    timer = assert(vim.uv.new_timer())
+    timer:start(0, interval or 20, function()
+        if scheduled then
+            return
+        end
+        scheduled = true
+        -- The API can't be used from libuv callbacks
+        vim.schedule(function()
+            scheduled = false
+            -- Fails while a request is waited on, which drives the turns meanwhile
+            local ok, running = pcall(require("hermes")._drive)
+            if ok and not running and timer then
+                stop()
+            end
+        end)
+    end)
+end
+
+return M
@@ -0,0 +1,33 @@
+-- Lets Neovim answer the editor MCP server while idle, requests are read and answered in Rust
+local M = {}
+
+local pipe = nil
+
+--- Answers pending requests whenever a byte arrives on the file descriptor `fd`, and removes
+--- `socket` when Neovim exits
+function M.watch(fd, socket)
+    if pipe then
+        return
+    end
+    pipe = assert(vim.uv.new_pipe(false))
+    assert(pipe:open(fd))
+    pipe:read_start(function(err, chunk)
+        if err or not chunk then
+            pipe:close()
+            pipe = nil
+            return
+        end
+        -- The API can't be used from libuv callbacks
+        vim.schedule(function()
+            pcall(require("hermes")._mcp_answer)
+        end)
+    end)
+    vim.api.nvim_create_autocmd("VimLeavePre", {
+        group = "hermes",
+        callback = function()
+            vim.fn.delete(socket)
+        end,
+    })
+end
+
+return M
@@ -0,0 +1,41 @@
+-- Run by agents as a stdio MCP server with `nvim --clean --headless -l mcp_bridge.lua <socket>`,
+-- copies stdin to the socket of the editor running Hermes and the answers back to stdout
+local uv = vim.uv
+local socket = assert(arg[1], "usage: nvim -l mcp_bridge.lua <socket>")
+
+local stdin = assert(uv.new_pipe(false))
+local stdout = assert(uv.new_pipe(false))
+local editor = assert(uv.new_pipe(false))
+stdin:open(0)
+stdout:open(1)
+
+local done = false
+local function finish()
+    done = true
+end
+
+editor:connect(socket, function(err)
+    if err then
+        io.stderr:write("Unable to reach Neovim at " .. socket .. ": " .. err .. "\n")
+        finish()
+        return
+    end
+    editor:read_start(function(read_err, chunk)
+        if read_err or not chunk then
+            finish()
+            return
+        end
+        stdout:write(chunk)
+    end)
+    stdin:read_start(function(read_err, chunk)
+        if read_err or not chunk then
+            finish()
+            return
+        end
+        editor:write(chunk)
+    end)
+end)
+
+while not done do
+    uv.run("once")
+end
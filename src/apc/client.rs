@@ -1,10 +1,19 @@
+use crate::apc::permissions::PermissionPolicy;
 use agent_client_protocol::{
-    Client, CreateTerminalRequest, CreateTerminalResponse, Error as AcpError, ReadTextFileRequest,
-    ReadTextFileResponse, ReleaseTerminalRequest, ReleaseTerminalResponse,
+    Client, CreateTerminalRequest, CreateTerminalResponse, Error as AcpError, ExtNotification,
+    ReadTextFileRequest, ReadTextFileResponse, ReleaseTerminalRequest, ReleaseTerminalResponse,
     RequestPermissionRequest, RequestPermissionResponse, Result, SessionNotification,
     TerminalOutputRequest, TerminalOutputResponse, WaitForTerminalExitRequest,
     WaitForTerminalExitResponse, WriteTextFileRequest, WriteTextFileResponse,
 };
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+/// Told how each permission request was answered, whether by the policy or the handler
+pub type PermissionObserver =
+    Arc<dyn Fn(&RequestPermissionRequest, &RequestPermissionResponse) -> Result<()> + Send + Sync>;
 
 #[derive(Debug, Clone)]
 pub struct ClientConfig {
@@ -30,17 +39,72 @@ impl Default for ClientConfig {
 #[derive(Clone)]
 pub struct ApcClient<H: Client> {
     config: ClientConfig,
+    permissions: PermissionPolicy,
+    fs_roots: Vec<PathBuf>,
+    permission_observer: Option<PermissionObserver>,
     handler: H,
 }
 
 impl<H: Client> ApcClient<H> {
     pub fn new(config: ClientConfig, handler: H) -> Self {
-        Self { config, handler }
+        Self {
+            config,
+            permissions: PermissionPolicy::default(),
+            fs_roots: Vec::new(),
+            permission_observer: None,
+            handler,
+        }
+    }
+
+    /// Answers permission requests covered by the policy instead of forwarding them to the handler
+    pub fn with_permissions(mut self, permissions: PermissionPolicy) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Reports every answer to a permission request once it is known
+    pub fn with_permission_observer(mut self, observer: PermissionObserver) -> Self {
+        self.permission_observer = Some(observer);
+        self
+    }
+
+    /// Restricts file reads and writes to paths below one of the roots, no roots means no restriction
+    pub fn with_fs_roots(mut self, fs_roots: Vec<PathBuf>) -> Self {
+        self.fs_roots = fs_roots;
+        self
     }
 
     pub fn config(&self) -> &ClientConfig {
         &self.config
     }
+
+    pub fn permissions(&self) -> &PermissionPolicy {
+        &self.permissions
+    }
+
+    pub fn fs_roots(&self) -> &[PathBuf] {
+        &self.fs_roots
+    }
+
+    /// Whether the agent may touch the path, relative paths and parent components are never allowed
+    /// once roots are configured
+    pub fn allows_path(&self, path: &Path) -> bool {
+        self.fs_roots.is_empty()
+            || (path.is_absolute()
+                && !path.components().any(|c| c == Component::ParentDir)
+                && self.fs_roots.iter().any(|root| path.starts_with(root)))
+    }
+
+    fn check_path(&self, path: &Path) -> Result<()> {
+        if self.allows_path(path) {
+            Ok(())
+        } else {
+            Err(AcpError::invalid_params().data(format!(
+                "{} is outside the allowed filesystem roots",
+                path.display()
+            )))
+        }
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -49,15 +113,27 @@ impl<H: Client> Client for ApcClient<H> {
         &self,
         args: RequestPermissionRequest,
     ) -> Result<RequestPermissionResponse> {
-        self.handler.request_permission(args).await
+        let response = match self.permissions.respond(&args) {
+            Some(response) => response,
+            None => self.handler.request_permission(args.clone()).await?,
+        };
+        if let Some(observer) = &self.permission_observer {
+            observer(&args, &response)?;
+        }
+        Ok(response)
     }
 
     async fn session_notification(&self, args: SessionNotification) -> Result<()> {
         self.handler.session_notification(args).await
     }
 
+    async fn ext_notification(&self, args: ExtNotification) -> Result<()> {
+        self.handler.ext_notification(args).await
+    }
+
     async fn write_text_file(&self, args: WriteTextFileRequest) -> Result<WriteTextFileResponse> {
         if self.config.fs_write_access {
+            self.check_path(&args.path)?;
             self.handler.write_text_file(args).await?;
             Ok(WriteTextFileResponse::new())
         } else {
@@ -67,6 +143,7 @@ impl<H: Client> Client for ApcClient<H> {
 
     async fn read_text_file(&self, _args: ReadTextFileRequest) -> Result<ReadTextFileResponse> {
         if self.config.fs_read_access {
+            self.check_path(&_args.path)?;
             self.handler.read_text_file(_args).await
         } else {
             Err(AcpError::method_not_found())
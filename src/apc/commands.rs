@@ -0,0 +1,44 @@
+use agent_client_protocol::{AvailableCommand, SessionId};
+use std::collections::HashMap;
+
+/// Keeps the commands most recently advertised by the agent for each session
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    sessions: HashMap<SessionId, Vec<AvailableCommand>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the commands of a session, agents always advertise the complete list
+    pub fn update(&mut self, session_id: &SessionId, commands: Vec<AvailableCommand>) {
+        self.sessions.insert(session_id.clone(), commands);
+    }
+
+    /// Finds a command by name, with or without its leading slash
+    pub fn get(&self, session_id: &SessionId, name: &str) -> Option<&AvailableCommand> {
+        let name = name.strip_prefix('/').unwrap_or(name);
+        self.sessions
+            .get(session_id)?
+            .iter()
+            .find(|command| command.name == name)
+    }
+
+    pub fn commands(&self, session_id: &SessionId) -> Vec<AvailableCommand> {
+        self.sessions.get(session_id).cloned().unwrap_or_default()
+    }
+
+    pub fn clear(&mut self, session_id: &SessionId) {
+        self.sessions.remove(session_id);
+    }
+}
+
+/// The prompt text that invokes a command, agents expect "/name" followed by any input
+pub fn invocation(command: &AvailableCommand, input: Option<&str>) -> String {
+    match input.map(str::trim).filter(|input| !input.is_empty()) {
+        Some(input) => format!("/{} {}", command.name, input),
+        None => format!("/{}", command.name),
+    }
+}
@@ -0,0 +1,58 @@
+use agent_client_protocol::{
+    SessionConfigId, SessionConfigKind, SessionConfigOption, SessionConfigSelectOptions,
+    SessionConfigValueId, SessionId,
+};
+use std::collections::HashMap;
+
+/// Keeps the latest configuration options (model, reasoning level, etc) advertised for each session
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOptionTracker {
+    sessions: HashMap<SessionId, Vec<SessionConfigOption>>,
+}
+
+impl ConfigOptionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the options of a session, agents always report the complete list
+    pub fn update(&mut self, session_id: &SessionId, options: Vec<SessionConfigOption>) {
+        self.sessions.insert(session_id.clone(), options);
+    }
+
+    pub fn get(
+        &self,
+        session_id: &SessionId,
+        id: &SessionConfigId,
+    ) -> Option<&SessionConfigOption> {
+        self.sessions
+            .get(session_id)?
+            .iter()
+            .find(|option| option.id == *id)
+    }
+
+    pub fn options(&self, session_id: &SessionId) -> Vec<SessionConfigOption> {
+        self.sessions.get(session_id).cloned().unwrap_or_default()
+    }
+
+    pub fn clear(&mut self, session_id: &SessionId) {
+        self.sessions.remove(session_id);
+    }
+}
+
+/// Every value an option can be set to, including the values of grouped selects
+pub fn values(option: &SessionConfigOption) -> Vec<&SessionConfigValueId> {
+    match &option.kind {
+        SessionConfigKind::Select(select) => match &select.options {
+            SessionConfigSelectOptions::Ungrouped(options) => {
+                options.iter().map(|option| &option.value).collect()
+            }
+            SessionConfigSelectOptions::Grouped(groups) => groups
+                .iter()
+                .flat_map(|group| group.options.iter().map(|option| &option.value))
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
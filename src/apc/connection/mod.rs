@@ -1,18 +1,39 @@
 pub mod stdio;
+pub mod unknown;
 
-use crate::{ApcClient, apc::error::Error};
-use agent_client_protocol::{Client, ClientSideConnection};
+use crate::{
+    ApcClient,
+    apc::{error::Error, mcp, status::StatusTracker},
+};
+use agent_client_protocol::{
+    Agent, AuthMethod, AuthMethodId, AuthenticateRequest, AuthenticateResponse, CancelNotification,
+    Client, ClientCapabilities, ClientSideConnection, ContentBlock, EmbeddedResourceResource,
+    FileSystemCapability, Implementation, InitializeRequest, InitializeResponse,
+    LoadSessionRequest, LoadSessionResponse, McpServer, NewSessionRequest, NewSessionResponse,
+    PromptRequest, PromptResponse, ProtocolVersion, ResourceLink, SessionConfigId,
+    SessionConfigValueId, SessionId, SessionModeId, SetSessionConfigOptionRequest,
+    SetSessionConfigOptionResponse, SetSessionModeRequest, SetSessionModeResponse,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+use tokio::process::Child;
 use tokio::runtime::Runtime;
 use tokio::task::LocalSet;
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 
-#[derive(PartialEq, Eq, Clone, std::hash::Hash, Serialize, Deserialize, Debug)]
+#[derive(PartialEq, Eq, Clone, std::hash::Hash, Serialize, Deserialize, Debug, Default)]
 pub enum Protocol {
     Socket,
     Http,
+    #[default]
     Stdio,
 }
 
@@ -37,22 +58,24 @@ impl From<&str> for Protocol {
     }
 }
 
-impl Default for Protocol {
-    fn default() -> Self {
-        Protocol::Stdio
-    }
-}
-
 impl From<String> for Protocol {
     fn from(s: String) -> Self {
         Protocol::from(s.as_str())
     }
 }
 
-#[derive(PartialEq, Eq, Clone, std::hash::Hash, Serialize, Deserialize, Debug)]
+#[derive(PartialEq, Eq, Clone, std::hash::Hash, Serialize, Deserialize, Debug, Default)]
 pub enum Assistant {
+    #[default]
     Copilot,
     Opencode,
+    /// An agent defined by the user, launched with the command from its [`AgentDefinition`]
+    Custom(String),
+}
+
+impl Assistant {
+    /// The agents Hermes knows how to launch without any configuration
+    pub const BUILTIN: [Assistant; 2] = [Assistant::Copilot, Assistant::Opencode];
 }
 
 impl std::fmt::Display for Assistant {
@@ -60,22 +83,17 @@ impl std::fmt::Display for Assistant {
         match self {
             Assistant::Copilot => write!(f, "copilot"),
             Assistant::Opencode => write!(f, "opencode"),
+            Assistant::Custom(name) => write!(f, "{}", name),
         }
     }
 }
 
-impl Default for Assistant {
-    fn default() -> Self {
-        Assistant::Copilot
-    }
-}
-
 impl From<&str> for Assistant {
     fn from(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "copilot" => Assistant::Copilot,
             "opencode" => Assistant::Opencode,
-            _ => Assistant::default(),
+            _ => Assistant::Custom(s.to_string()),
         }
     }
 }
@@ -86,15 +104,344 @@ impl From<String> for Assistant {
     }
 }
 
+/// How to launch an agent over stdio
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentDefinition {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+impl AgentDefinition {
+    pub fn new<I, S>(command: impl Into<String>, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            command: command.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            env: HashMap::new(),
+        }
+    }
+
+    /// The command Hermes uses for the agents it knows about
+    pub fn builtin(agent: &Assistant) -> Option<Self> {
+        match agent {
+            Assistant::Copilot => Some(Self::new("node", ["copilot-language-server", "--acp"])),
+            Assistant::Opencode => Some(Self::new("opencode", ["apc"])),
+            Assistant::Custom(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ConnectionDetails {
     pub agent: Assistant,
     pub protocol: Protocol,
+    /// A name to find the connection by, e.g. the worktree the agent works in
+    pub label: Option<String>,
+}
+
+/// Identifies one connection, several can be open to the same agent at once
+#[derive(PartialEq, Eq, Clone, std::hash::Hash, Debug)]
+pub struct ConnectionId(pub String);
+
+static NEXT_CONNECTION: AtomicUsize = AtomicUsize::new(1);
+
+impl ConnectionId {
+    /// A new id made of the agent name and a number unique to this process, e.g. "copilot-1"
+    pub fn generate(agent: &Assistant) -> Self {
+        Self(format!(
+            "{}-{}",
+            agent,
+            NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+}
+
+impl std::fmt::Display for ConnectionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for ConnectionId {
+    fn from(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+impl From<String> for ConnectionId {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+/// Which agent a connection is to and how it was labelled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    pub id: ConnectionId,
+    pub agent: Assistant,
+    pub label: Option<String>,
+}
+
+impl ConnectionInfo {
+    /// Whether `target` names this connection by id, label or agent
+    pub fn matches(&self, target: &str) -> bool {
+        self.id.0 == target
+            || self.label.as_deref() == Some(target)
+            || self.agent == Assistant::from(target)
+    }
+}
+
+struct Connection {
+    info: ConnectionInfo,
+    client: Rc<ClientSideConnection>,
+    initialized: InitializeResponse,
+    /// Kept so the agent is killed when the connection is dropped, agents connected over streams
+    /// have none
+    process: Option<Child>,
+}
+
+/// Links to an embedded resource instead of sending its contents, named by its URI
+fn resource_link(resource: EmbeddedResourceResource) -> Option<ContentBlock> {
+    let (uri, mime_type) = match resource {
+        EmbeddedResourceResource::TextResourceContents(contents) => {
+            (contents.uri, contents.mime_type)
+        }
+        EmbeddedResourceResource::BlobResourceContents(contents) => {
+            (contents.uri, contents.mime_type)
+        }
+        _ => return None,
+    };
+    Some(ContentBlock::ResourceLink(
+        ResourceLink::new(uri.clone(), uri).mime_type(mime_type),
+    ))
+}
+
+fn update_status(status: &Mutex<StatusTracker>, update: impl FnOnce(&mut StatusTracker)) {
+    // A poisoned tracker only loses status information, the connection itself is fine
+    if let Ok(mut status) = status.lock() {
+        update(&mut status);
+    }
+}
+
+/// Fails when called while a request is waited on
+///
+/// The handler is called while waiting, so a request made from it or from a callback it runs
+/// would block on the runtime from within itself. Tokio doesn't allow that.
+fn ensure_idle() -> Result<(), Error> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => Err(Error::Connection(
+            "Can't wait for an agent while already waiting for one, e.g. during a prompt"
+                .to_string(),
+        )),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Runs a request until the agent answers
+fn wait<F: Future>(runtime: &Runtime, local: &LocalSet, request: F) -> Result<F::Output, Error> {
+    ensure_idle()?;
+    Ok(runtime.block_on(local.run_until(request)))
+}
+
+/// One connection's client along with the runtime it runs on
+///
+/// Requests block until the agent answers and the handler runs meanwhile, so a caller keeping
+/// the [`ConnectionManager`] behind a lock takes a handle and releases the lock before the request.
+#[derive(Clone)]
+pub struct ConnectionHandle {
+    id: ConnectionId,
+    client: Rc<ClientSideConnection>,
+    status: Arc<Mutex<StatusTracker>>,
+    runtime: Arc<Runtime>,
+    local: Rc<LocalSet>,
+}
+
+impl ConnectionHandle {
+    pub fn id(&self) -> &ConnectionId {
+        &self.id
+    }
+
+    /// Records a failed request as the connection's last error
+    fn track<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(error) = &result {
+            update_status(&self.status, |status| status.failed(&self.id, error));
+        }
+        result
+    }
+
+    fn request<T>(
+        &self,
+        request: impl Future<Output = agent_client_protocol::Result<T>>,
+    ) -> Result<T, Error> {
+        let response = wait(&self.runtime, &self.local, request)
+            .and_then(|response| response.map_err(Error::from));
+        self.track(response)
+    }
+
+    pub fn authenticate(&self, method_id: AuthMethodId) -> Result<AuthenticateResponse, Error> {
+        let response = self.request(
+            self.client
+                .authenticate(AuthenticateRequest::new(method_id)),
+        )?;
+        update_status(&self.status, |status| status.authenticated(&self.id));
+        Ok(response)
+    }
+
+    /// Creates a session with exactly `mcp_servers`, see [`ConnectionManager::session_mcp_servers`]
+    ///
+    /// The manager learns about the session through [`ConnectionManager::add_session`].
+    pub fn new_session(
+        &self,
+        cwd: PathBuf,
+        mcp_servers: Vec<McpServer>,
+    ) -> Result<NewSessionResponse, Error> {
+        self.request(
+            self.client
+                .new_session(NewSessionRequest::new(cwd).mcp_servers(mcp_servers)),
+        )
+    }
+
+    /// Loads a session with exactly `mcp_servers`, the agent replays its history meanwhile
+    ///
+    /// The manager learns about the session through [`ConnectionManager::add_session`].
+    pub fn load_session(
+        &self,
+        session_id: SessionId,
+        cwd: PathBuf,
+        mcp_servers: Vec<McpServer>,
+    ) -> Result<LoadSessionResponse, Error> {
+        self.request(
+            self.client
+                .load_session(LoadSessionRequest::new(session_id, cwd).mcp_servers(mcp_servers)),
+        )
+    }
+
+    pub fn prompt(
+        &self,
+        session_id: SessionId,
+        content: Vec<ContentBlock>,
+    ) -> Result<PromptResponse, Error> {
+        update_status(&self.status, |status| {
+            status.turn_started(&self.id, &session_id)
+        });
+        let response = self.request(
+            self.client
+                .prompt(PromptRequest::new(session_id.clone(), content)),
+        );
+        update_status(&self.status, |status| {
+            status.turn_finished(&self.id, &session_id)
+        });
+        response
+    }
+
+    /// Starts a prompt turn next to the connections and returns right away
+    ///
+    /// The turn makes progress while a [`Driver`] runs or a request is waited on, `done` gets the
+    /// agent's answer once it stopped.
+    pub fn spawn_prompt(
+        &self,
+        session_id: SessionId,
+        content: Vec<ContentBlock>,
+        done: impl FnOnce(Result<PromptResponse, Error>) + 'static,
+    ) {
+        update_status(&self.status, |status| {
+            status.turn_started(&self.id, &session_id)
+        });
+        // Takes no handle along, the local set would end up holding itself
+        let (id, client, status) = (self.id.clone(), self.client.clone(), self.status.clone());
+        self.local.spawn_local(async move {
+            let response = client
+                .prompt(PromptRequest::new(session_id.clone(), content))
+                .await
+                .map_err(Error::from);
+            update_status(&status, |status| {
+                status.turn_finished(&id, &session_id);
+                if let Err(error) = &response {
+                    status.failed(&id, error);
+                }
+            });
+            done(response);
+        });
+    }
+
+    /// Asks the agent to stop the prompt turn running in the session
+    ///
+    /// Called while the turn is waited on, i.e. from the handler or a callback it runs, the
+    /// notification is sent alongside the turn and the prompt returns once the agent stopped.
+    pub fn cancel(&self, session_id: SessionId) -> Result<(), Error> {
+        let notification = CancelNotification::new(session_id);
+        if ensure_idle().is_ok() {
+            return self.request(self.client.cancel(notification));
+        }
+        let handle = self.clone();
+        self.local.spawn_local(async move {
+            let sent = handle.client.cancel(notification).await;
+            let _ = handle.track(sent.map_err(Error::from));
+        });
+        Ok(())
+    }
+
+    pub fn set_mode(
+        &self,
+        session_id: SessionId,
+        mode_id: SessionModeId,
+    ) -> Result<SetSessionModeResponse, Error> {
+        self.request(
+            self.client
+                .set_session_mode(SetSessionModeRequest::new(session_id, mode_id)),
+        )
+    }
+
+    pub fn set_config_option(
+        &self,
+        session_id: SessionId,
+        config_id: SessionConfigId,
+        value: SessionConfigValueId,
+    ) -> Result<SetSessionConfigOptionResponse, Error> {
+        self.request(
+            self.client
+                .set_session_config_option(SetSessionConfigOptionRequest::new(
+                    session_id, config_id, value,
+                )),
+        )
+    }
+}
+
+/// Runs the tasks spawned next to the connections, e.g. turns from
+/// [`ConnectionHandle::spawn_prompt`]
+///
+/// Taken from the manager like a handle, so the handler is free to lock the manager meanwhile.
+#[derive(Clone)]
+pub struct Driver {
+    runtime: Arc<Runtime>,
+    local: Rc<LocalSet>,
+}
+
+impl Driver {
+    /// Lets the tasks make progress for `duration`, fails when a request is waited on already
+    pub fn run_for(&self, duration: Duration) -> Result<(), Error> {
+        // The timer is made once running, it needs the runtime
+        wait(&self.runtime, &self.local, async move {
+            tokio::time::sleep(duration).await
+        })
+    }
 }
 
 #[derive(Clone)]
 pub struct ConnectionManager<H: Client> {
-    connection: HashMap<Assistant, Rc<ClientSideConnection>>,
+    /// Open connections in the order they were made
+    connections: Vec<Rc<Connection>>,
+    agents: HashMap<Assistant, AgentDefinition>,
+    sessions: HashMap<SessionId, ConnectionId>,
+    /// The directory each session was created or loaded in
+    cwds: HashMap<SessionId, PathBuf>,
+    /// Passed to every new or loaded session, along with the session's own servers
+    mcp_servers: Vec<McpServer>,
+    status: Arc<Mutex<StatusTracker>>,
     handler: Arc<ApcClient<H>>,
     runtime: Arc<Runtime>,
     local: Rc<LocalSet>,
@@ -111,38 +458,500 @@ impl<H: Client + 'static> ConnectionManager<H> {
 
         Ok(Self {
             handler: client,
-            connection: HashMap::new(),
+            connections: Vec::new(),
+            agents: HashMap::new(),
+            sessions: HashMap::new(),
+            cwds: HashMap::new(),
+            mcp_servers: Vec::new(),
+            status: Arc::new(Mutex::new(StatusTracker::new())),
             runtime: Arc::new(runtime),
             local: Rc::new(local_set),
         })
     }
 
-    fn add_connection(&mut self, agent: Assistant, connection: ClientSideConnection) {
-        self.connection.insert(agent, Rc::new(connection));
+    /// Shares a status tracker, so the status outlives the manager when it is replaced
+    pub fn with_status(mut self, status: Arc<Mutex<StatusTracker>>) -> Self {
+        self.status = status;
+        self
     }
 
-    pub fn get_connection(&self, agent: &Assistant) -> Option<Rc<ClientSideConnection>> {
-        self.connection.get(agent).cloned()
+    /// Sets the MCP servers every session gets
+    pub fn with_mcp_servers(mut self, servers: Vec<McpServer>) -> Self {
+        self.mcp_servers = servers;
+        self
     }
 
-    pub fn connect(
+    pub fn mcp_servers(&self) -> &[McpServer] {
+        &self.mcp_servers
+    }
+
+    pub fn status(&self) -> Arc<Mutex<StatusTracker>> {
+        self.status.clone()
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut StatusTracker)) {
+        update_status(&self.status, update);
+    }
+
+    /// Records a failed request as the connection's last error
+    fn track<T>(&self, id: &ConnectionId, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(error) = &result {
+            self.update_status(|status| status.failed(id, error));
+        }
+        result
+    }
+
+    /// Overrides how an agent is launched, or defines a new one
+    pub fn define_agent(&mut self, agent: Assistant, definition: AgentDefinition) {
+        self.agents.insert(agent, definition);
+    }
+
+    /// Whether the user defined how to launch the agent, rather than relying on the built-in command
+    pub fn is_configured(&self, agent: &Assistant) -> bool {
+        self.agents.contains_key(agent)
+    }
+
+    pub fn agent_definition(&self, agent: &Assistant) -> Option<AgentDefinition> {
+        self.agents
+            .get(agent)
+            .cloned()
+            .or_else(|| AgentDefinition::builtin(agent))
+    }
+
+    /// Every agent that can be connected to, built-in or defined, sorted by name
+    pub fn known_agents(&self) -> Vec<Assistant> {
+        let mut agents: Vec<Assistant> = Assistant::BUILTIN
+            .into_iter()
+            .chain(
+                self.agents
+                    .keys()
+                    .filter(|agent| !Assistant::BUILTIN.contains(agent))
+                    .cloned(),
+            )
+            .collect();
+        agents.sort_by_key(ToString::to_string);
+        agents
+    }
+
+    /// The open connections in the order they were made
+    pub fn connections(&self) -> Vec<ConnectionInfo> {
+        self.connections
+            .iter()
+            .map(|connection| connection.info.clone())
+            .collect()
+    }
+
+    fn find(&self, id: &ConnectionId) -> Option<&Rc<Connection>> {
+        self.connections
+            .iter()
+            .find(|connection| connection.info.id == *id)
+    }
+
+    pub fn connection_info(&self, id: &ConnectionId) -> Option<ConnectionInfo> {
+        self.find(id).map(|connection| connection.info.clone())
+    }
+
+    /// Finds a connection by id or label, or the oldest connection to an agent by its name
+    pub fn resolve(&self, target: &str) -> Option<ConnectionId> {
+        let infos = self.connections();
+        infos
+            .iter()
+            .find(|info| info.id.0 == target || info.label.as_deref() == Some(target))
+            .or_else(|| infos.iter().find(|info| info.matches(target)))
+            .map(|info| info.id.clone())
+    }
+
+    pub fn get_connection(&self, id: &ConnectionId) -> Option<Rc<ClientSideConnection>> {
+        self.find(id).map(|connection| connection.client.clone())
+    }
+
+    /// A handle to make requests on the connection without borrowing the manager
+    pub fn handle(&self, id: &ConnectionId) -> Result<ConnectionHandle, Error> {
+        Ok(ConnectionHandle {
+            id: id.clone(),
+            client: self.require_connection(id)?,
+            status: self.status.clone(),
+            runtime: self.runtime.clone(),
+            local: self.local.clone(),
+        })
+    }
+
+    pub fn driver(&self) -> Driver {
+        Driver {
+            runtime: self.runtime.clone(),
+            local: self.local.clone(),
+        }
+    }
+
+    /// Runs a task next to the connections, it makes progress while a request is waited on
+    pub fn spawn_local(&self, task: impl Future<Output = ()> + 'static) {
+        self.local.spawn_local(task);
+    }
+
+    /// Launches an agent and initializes a new connection to it
+    pub fn connect(&mut self, details: ConnectionDetails) -> Result<ConnectionId, Error> {
+        let id = ConnectionId::generate(&details.agent);
+        self.connect_as(id.clone(), details)?;
+        Ok(id)
+    }
+
+    /// Like [`Self::connect`] with an id picked by the caller, so it can be announced beforehand
+    pub fn connect_as(
+        &mut self,
+        id: ConnectionId,
+        ConnectionDetails {
+            agent,
+            protocol,
+            label,
+        }: ConnectionDetails,
+    ) -> Result<(), Error> {
+        if self.find(&id).is_some() {
+            return Err(Error::Connection(format!(
+                "Connection {} already exists",
+                id
+            )));
+        }
+        let info = ConnectionInfo {
+            id: id.clone(),
+            agent,
+            label,
+        };
+        self.update_status(|status| status.connecting(&info, protocol.clone()));
+        let (client, process) = self.track(&id, self.start(&info.agent, protocol))?;
+        self.add_connection(info, client, Some(process))
+    }
+
+    /// Connects to an agent over streams that are already open, e.g. to one running in-process
+    ///
+    /// There is no process to launch or stop, otherwise the connection is like a launched agent's.
+    pub fn connect_streams(
         &mut self,
-        ConnectionDetails { agent, protocol }: ConnectionDetails,
-    ) -> Result<Rc<ClientSideConnection>, Error> {
-        let connection = match protocol {
-            Protocol::Stdio => stdio::connect(
-                &self.runtime,
-                &self.local,
+        id: ConnectionId,
+        ConnectionDetails {
+            agent,
+            protocol,
+            label,
+        }: ConnectionDetails,
+        outgoing: impl tokio::io::AsyncWrite + Unpin + 'static,
+        incoming: impl tokio::io::AsyncRead + Unpin + 'static,
+    ) -> Result<(), Error> {
+        if self.find(&id).is_some() {
+            return Err(Error::Connection(format!(
+                "Connection {} already exists",
+                id
+            )));
+        }
+        let info = ConnectionInfo {
+            id: id.clone(),
+            agent,
+            label,
+        };
+        self.update_status(|status| status.connecting(&info, protocol));
+        let (client, handle_io) = {
+            let _local = self.local.enter();
+            ClientSideConnection::new(
                 self.handler.clone(),
-                agent.clone(),
-            ),
-            Protocol::Http => unimplemented!(),
-            Protocol::Socket => unimplemented!(),
-        }
-        .map_err(|e| Error::Connection(e.to_string()))?;
-        self.add_connection(agent.clone(), connection);
-        self.get_connection(&agent).ok_or_else(|| {
-            Error::Connection("Failed to retrieve connection after creation".to_string())
-        })
+                outgoing.compat_write(),
+                unknown::tolerant(incoming, &self.local).compat(),
+                |task| {
+                    tokio::task::spawn_local(task);
+                },
+            )
+        };
+        self.local.spawn_local(async move {
+            let _ = handle_io.await;
+        });
+        self.add_connection(info, client, None)
+    }
+
+    /// Initializes a connection and keeps it
+    fn add_connection(
+        &mut self,
+        info: ConnectionInfo,
+        client: ClientSideConnection,
+        process: Option<Child>,
+    ) -> Result<(), Error> {
+        let initialized = self.track(&info.id, self.initialize(&client))?;
+        let pid = process.as_ref().and_then(Child::id);
+        self.update_status(|status| status.initialized(&info.id, pid, &initialized));
+        self.connections.push(Rc::new(Connection {
+            info,
+            client: Rc::new(client),
+            initialized,
+            process,
+        }));
+        Ok(())
+    }
+
+    /// Launches the agent
+    fn start(
+        &self,
+        agent: &Assistant,
+        protocol: Protocol,
+    ) -> Result<(ClientSideConnection, Child), Error> {
+        ensure_idle()?;
+        match protocol {
+            Protocol::Stdio => {
+                let definition = self.agent_definition(agent).ok_or_else(|| {
+                    Error::Connection(format!("No command configured for agent {}", agent))
+                })?;
+                stdio::connect(
+                    &self.runtime,
+                    &self.local,
+                    self.handler.clone(),
+                    &definition,
+                )
+            }
+            Protocol::Http | Protocol::Socket => {
+                return Err(Error::Connection(format!(
+                    "{} connections are not supported",
+                    protocol
+                )));
+            }
+        }
+        .map_err(|e| Error::Connection(e.to_string()))
+    }
+
+    /// Closes a connection and stops the agent's process
+    ///
+    /// Returns the sessions that belonged to the connection, they can't be used anymore.
+    pub fn disconnect(&mut self, id: &ConnectionId) -> Result<Vec<SessionId>, Error> {
+        self.find(id)
+            .ok_or_else(|| Error::Connection(format!("Not connected to {}", id)))?;
+        self.connections
+            .retain(|connection| connection.info.id != *id);
+        self.update_status(|status| status.disconnected(id));
+        let sessions: Vec<SessionId> = self
+            .sessions
+            .iter()
+            .filter(|(_, owner)| *owner == id)
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in &sessions {
+            self.sessions.remove(session_id);
+            self.cwds.remove(session_id);
+        }
+        Ok(sessions)
+    }
+
+    fn initialize(&self, connection: &ClientSideConnection) -> Result<InitializeResponse, Error> {
+        let config = self.handler.config();
+        let request = InitializeRequest::new(ProtocolVersion::LATEST)
+            .client_capabilities(
+                ClientCapabilities::new()
+                    .fs(FileSystemCapability::new()
+                        .read_text_file(config.fs_read_access)
+                        .write_text_file(config.fs_write_access))
+                    .terminal(config.terminal_access),
+            )
+            .client_info(Implementation::new(
+                config.name.clone(),
+                config.version.clone(),
+            ));
+
+        wait(&self.runtime, &self.local, connection.initialize(request))?
+            .map_err(|e| Error::Connection(e.to_string()))
+    }
+
+    fn require_connection(&self, id: &ConnectionId) -> Result<Rc<ClientSideConnection>, Error> {
+        self.get_connection(id)
+            .ok_or_else(|| Error::Connection(format!("Not connected to {}", id)))
+    }
+
+    /// The response the agent gave when the connection was initialized
+    pub fn initialize_response(&self, id: &ConnectionId) -> Option<&InitializeResponse> {
+        self.find(id).map(|connection| &connection.initialized)
+    }
+
+    /// The process id of the agent behind a connection
+    pub fn pid(&self, id: &ConnectionId) -> Option<u32> {
+        self.find(id)
+            .and_then(|connection| connection.process.as_ref())
+            .and_then(Child::id)
+    }
+
+    /// The authentication methods the agent advertised when the connection was initialized
+    pub fn auth_methods(&self, id: &ConnectionId) -> Vec<AuthMethod> {
+        self.initialize_response(id)
+            .map(|response| response.auth_methods.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn authenticate(
+        &self,
+        id: &ConnectionId,
+        method_id: AuthMethodId,
+    ) -> Result<AuthenticateResponse, Error> {
+        self.handle(id)?.authenticate(method_id)
+    }
+
+    /// The connection a session was created on
+    pub fn session_connection(&self, session_id: &SessionId) -> Option<ConnectionId> {
+        self.sessions.get(session_id).cloned()
+    }
+
+    /// The agent a session was created with
+    pub fn session_agent(&self, session_id: &SessionId) -> Option<Assistant> {
+        self.sessions
+            .get(session_id)
+            .and_then(|id| self.find(id))
+            .map(|connection| connection.info.agent.clone())
+    }
+
+    /// Every open session and the connection it belongs to, sorted by session id
+    pub fn sessions(&self) -> Vec<(SessionId, ConnectionId)> {
+        let mut sessions: Vec<(SessionId, ConnectionId)> = self
+            .sessions
+            .iter()
+            .map(|(session_id, id)| (session_id.clone(), id.clone()))
+            .collect();
+        sessions.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+        sessions
+    }
+
+    /// The configured MCP servers with the session's own layered over them, checked against the
+    /// transports the agent supports
+    pub fn session_mcp_servers(
+        &self,
+        id: &ConnectionId,
+        servers: Vec<McpServer>,
+    ) -> Result<Vec<McpServer>, Error> {
+        let servers = mcp::merge(&self.mcp_servers, servers);
+        let capabilities = self
+            .initialize_response(id)
+            .map(|response| response.agent_capabilities.mcp_capabilities.clone())
+            .unwrap_or_default();
+        mcp::check(&servers, &capabilities, &id.0)?;
+        Ok(servers)
+    }
+
+    /// Fails unless the agent said it can load sessions when the connection was initialized
+    pub fn check_load_session(&self, id: &ConnectionId) -> Result<(), Error> {
+        if self
+            .initialize_response(id)
+            .is_some_and(|response| response.agent_capabilities.load_session)
+        {
+            Ok(())
+        } else {
+            Err(Error::Internal(format!(
+                "{} does not support loading sessions",
+                id
+            )))
+        }
+    }
+
+    /// Checks prompt content against the content types the agent said it accepts when the
+    /// connection was initialized
+    ///
+    /// Embedded resources are sent as links to agents that don't accept them.
+    pub fn prompt_content(
+        &self,
+        id: &ConnectionId,
+        content: Vec<ContentBlock>,
+    ) -> Result<Vec<ContentBlock>, Error> {
+        let capabilities = self
+            .initialize_response(id)
+            .map(|response| response.agent_capabilities.prompt_capabilities.clone())
+            .unwrap_or_default();
+        if !capabilities.audio
+            && content
+                .iter()
+                .any(|block| matches!(block, ContentBlock::Audio(_)))
+        {
+            return Err(Error::Internal(format!(
+                "{} does not accept audio in prompts",
+                id
+            )));
+        }
+        if capabilities.embedded_context {
+            return Ok(content);
+        }
+        content
+            .into_iter()
+            .map(|block| match block {
+                ContentBlock::Resource(resource) => {
+                    resource_link(resource.resource).ok_or_else(|| {
+                        Error::Internal(format!("{} does not accept embedded resources", id))
+                    })
+                }
+                block => Ok(block),
+            })
+            .collect()
+    }
+
+    /// Records that a session was created or loaded on the connection in `cwd`
+    pub fn add_session(&mut self, id: &ConnectionId, session_id: SessionId, cwd: PathBuf) {
+        self.update_status(|status| status.session_created(id, &session_id));
+        self.cwds.insert(session_id.clone(), cwd);
+        self.sessions.insert(session_id, id.clone());
+    }
+
+    /// The directory a session was created or loaded in
+    pub fn session_cwd(&self, session_id: &SessionId) -> Option<&Path> {
+        self.cwds.get(session_id).map(PathBuf::as_path)
+    }
+
+    /// Creates a session with the configured MCP servers and `mcp_servers`
+    pub fn new_session(
+        &mut self,
+        id: &ConnectionId,
+        cwd: PathBuf,
+        mcp_servers: Vec<McpServer>,
+    ) -> Result<NewSessionResponse, Error> {
+        let handle = self.handle(id)?;
+        let response =
+            handle.new_session(cwd.clone(), self.session_mcp_servers(id, mcp_servers)?)?;
+        self.add_session(id, response.session_id.clone(), cwd);
+        Ok(response)
+    }
+
+    /// Resumes a previous session, the agent replays its history as session updates
+    pub fn load_session(
+        &mut self,
+        id: &ConnectionId,
+        session_id: SessionId,
+        cwd: PathBuf,
+        mcp_servers: Vec<McpServer>,
+    ) -> Result<LoadSessionResponse, Error> {
+        let handle = self.handle(id)?;
+        self.check_load_session(id)?;
+        let mcp_servers = self.session_mcp_servers(id, mcp_servers)?;
+        let response = handle.load_session(session_id.clone(), cwd.clone(), mcp_servers)?;
+        self.add_session(id, session_id, cwd);
+        Ok(response)
+    }
+
+    pub fn prompt(
+        &self,
+        id: &ConnectionId,
+        session_id: SessionId,
+        content: Vec<ContentBlock>,
+    ) -> Result<PromptResponse, Error> {
+        self.handle(id)?.prompt(session_id, content)
+    }
+
+    /// Asks the agent to stop the prompt turn running in the session
+    pub fn cancel(&self, id: &ConnectionId, session_id: SessionId) -> Result<(), Error> {
+        self.handle(id)?.cancel(session_id)
+    }
+
+    pub fn set_mode(
+        &self,
+        id: &ConnectionId,
+        session_id: SessionId,
+        mode_id: SessionModeId,
+    ) -> Result<SetSessionModeResponse, Error> {
+        self.handle(id)?.set_mode(session_id, mode_id)
+    }
+
+    pub fn set_config_option(
+        &self,
+        id: &ConnectionId,
+        session_id: SessionId,
+        config_id: SessionConfigId,
+        value: SessionConfigValueId,
+    ) -> Result<SetSessionConfigOptionResponse, Error> {
+        self.handle(id)?
+            .set_config_option(session_id, config_id, value)
     }
 }
@@ -1,32 +1,45 @@
 use crate::{
     ApcClient,
-    apc::{connection::Assistant, error::Error},
+    apc::{
+        connection::{AgentDefinition, unknown},
+        error::Error,
+    },
 };
 use agent_client_protocol::{Client, ClientSideConnection};
 use std::{ffi::OsStr, process::Stdio, sync::Arc};
+use tokio::process::Child;
 use tokio::runtime::Runtime;
 use tokio::task::LocalSet;
 use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 
-pub fn stdio_connection<H, I, S>(
+/// Spawns the agent and connects to it over its stdin and stdout
+///
+/// The agent is killed when the returned [`Child`] is dropped.
+pub fn stdio_connection<H, I, S, E, K, V>(
     runtime: &Runtime,
     local_set: &LocalSet,
     client: Arc<ApcClient<H>>,
     command: &str,
     args: I,
-) -> Result<ClientSideConnection, Error>
+    env: E,
+) -> Result<(ClientSideConnection, Child), Error>
 where
     H: Client + 'static,
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
+    E: IntoIterator<Item = (K, V)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
 {
     let mut child = runtime
         .block_on(async {
             tokio::process::Command::new(command)
                 .args(args)
+                .envs(env)
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
+                .kill_on_drop(true)
                 .spawn()
         })
         .map_err(|e| Error::Connection(e.to_string()))?;
@@ -37,11 +50,14 @@ where
         .ok_or_else(|| Error::Connection("Failed to take stdin".to_string()))?
         .compat_write();
 
-    let incoming = child
-        .stdout
-        .take()
-        .ok_or_else(|| Error::Connection("Failed to take stdout".to_string()))?
-        .compat();
+    let incoming = unknown::tolerant(
+        child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::Connection("Failed to take stdout".to_string()))?,
+        local_set,
+    )
+    .compat();
 
     let (conn, handle_io) = runtime.block_on(local_set.run_until(async {
         agent_client_protocol::ClientSideConnection::new(client, outgoing, incoming, |fut| {
@@ -51,23 +67,21 @@ where
 
     runtime.spawn(handle_io);
 
-    Ok(conn)
+    Ok((conn, child))
 }
 
 pub fn connect<H: Client + 'static>(
     runtime: &Runtime,
     local_set: &LocalSet,
     client: Arc<ApcClient<H>>,
-    agent: Assistant,
-) -> Result<ClientSideConnection, Error> {
-    match agent {
-        Assistant::Copilot => stdio_connection(
-            runtime,
-            local_set,
-            client,
-            "node",
-            ["copilot-language-server", "--acp"],
-        ),
-        Assistant::Opencode => stdio_connection(runtime, local_set, client, "opencode", ["apc"]),
-    }
+    definition: &AgentDefinition,
+) -> Result<(ClientSideConnection, Child), Error> {
+    stdio_connection(
+        runtime,
+        local_set,
+        client,
+        &definition.command,
+        &definition.args,
+        &definition.env,
+    )
 }
@@ -0,0 +1,107 @@
+//! Session updates the protocol library can't decode
+//!
+//! ACP decodes `session/update` notifications into enums without a fallback variant, so an update
+//! or content type newer than the library fails to decode and is dropped before any client sees
+//! it. The agent's messages are checked first and such updates are passed on as an extension
+//! notification holding the update as sent.
+use agent_client_protocol::{ContentBlock, SessionId, SessionNotification, ToolCallContent};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader, DuplexStream};
+use tokio::task::LocalSet;
+
+/// The extension notification undecodable updates are passed on as, without the leading `_`
+/// extension methods are sent with
+pub const UNKNOWN_UPDATE_METHOD: &str = "hermes/unknown_update";
+
+fn decodes<T: DeserializeOwned>(value: &Value) -> bool {
+    serde_json::from_value::<T>(value.clone()).is_ok()
+}
+
+/// The message to pass on instead of a line read from the agent, `None` to pass it on as is
+pub fn rewrite(line: &str) -> Option<String> {
+    let message: Value = serde_json::from_str(line).ok()?;
+    if message["method"] != "session/update" {
+        return None;
+    }
+    let params = message.get("params")?;
+    if decodes::<SessionNotification>(params) {
+        return None;
+    }
+    Some(
+        json!({
+            "jsonrpc": "2.0",
+            "method": format!("_{}", UNKNOWN_UPDATE_METHOD),
+            "params": params,
+        })
+        .to_string(),
+    )
+}
+
+/// Reads the agent's messages through [`rewrite`], the returned stream is what ACP should read
+///
+/// The messages are copied by a task on `local`, so they arrive while a request is waited on.
+pub fn tolerant(incoming: impl AsyncRead + Unpin + 'static, local: &LocalSet) -> DuplexStream {
+    let (reader, mut writer) = tokio::io::duplex(64 * 1024);
+    local.spawn_local(async move {
+        let mut lines = BufReader::new(incoming).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let mut line = rewrite(&line).unwrap_or(line);
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+    reader
+}
+
+/// What an undecodable update holds that Hermes doesn't understand
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownUpdate {
+    pub session_id: SessionId,
+    /// `content_block` or `tool_call_content` when only some content of a known update is
+    /// unknown, `session_update` otherwise
+    pub kind: &'static str,
+    /// The unknown part as sent by the agent
+    pub value: Value,
+    pub tool_call_id: Option<String>,
+}
+
+impl UnknownUpdate {
+    /// Reads the parameters of a `session/update` notification that failed to decode
+    pub fn from_params(params: &Value) -> Option<Self> {
+        let session_id = SessionId::new(params["sessionId"].as_str()?);
+        let update = &params["update"];
+        let whole = || ("session_update", update.clone(), None);
+        let (kind, value, tool_call_id) = match update["sessionUpdate"].as_str() {
+            Some("user_message_chunk" | "agent_message_chunk" | "agent_thought_chunk")
+                if !decodes::<ContentBlock>(&update["content"]) =>
+            {
+                ("content_block", update["content"].clone(), None)
+            }
+            Some("tool_call" | "tool_call_update") => update["content"]
+                .as_array()
+                .and_then(|content| {
+                    content
+                        .iter()
+                        .find(|content| !decodes::<ToolCallContent>(content))
+                })
+                .map(|content| {
+                    (
+                        "tool_call_content",
+                        content.clone(),
+                        update["toolCallId"].as_str().map(str::to_string),
+                    )
+                })
+                .unwrap_or_else(whole),
+            _ => whole(),
+        };
+        Some(Self {
+            session_id,
+            kind,
+            value,
+            tool_call_id,
+        })
+    }
+}
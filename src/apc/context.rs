@@ -0,0 +1,136 @@
+use crate::apc::error::Error;
+use agent_client_protocol::ContentBlock;
+use std::path::Path;
+use std::rc::Rc;
+
+/// A reference to a context provider in a prompt, e.g. `@diagnostics` or `@buffer:main.rs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    pub provider: String,
+    pub argument: Option<String>,
+}
+
+/// Whether `c` may appear in a provider's name
+pub fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Finds the mentions in a prompt's text
+///
+/// A mention starts with `@` at the start of the text or after whitespace, so addresses such as
+/// `user@example.com` are left alone. Whatever follows a `:` up to the next whitespace is the
+/// provider's argument.
+pub fn mentions(text: &str) -> Vec<Mention> {
+    text.split_whitespace()
+        .filter_map(|word| word.strip_prefix('@'))
+        .filter_map(|word| {
+            let (provider, argument) = match word.split_once(':') {
+                Some((provider, argument)) => (provider, Some(argument.to_string())),
+                None => (word, None),
+            };
+            (!provider.is_empty() && provider.chars().all(is_name_char)).then(|| Mention {
+                provider: provider.to_string(),
+                argument: argument.filter(|argument| !argument.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// Produces the content a mention expands to, `cwd` is the working directory of the session
+/// prompted
+pub trait ContextProvider {
+    fn provide(&self, argument: Option<&str>, cwd: &Path) -> Result<Vec<ContentBlock>, String>;
+}
+
+impl<F> ContextProvider for F
+where
+    F: Fn(Option<&str>, &Path) -> Result<Vec<ContentBlock>, String>,
+{
+    fn provide(&self, argument: Option<&str>, cwd: &Path) -> Result<Vec<ContentBlock>, String> {
+        self(argument, cwd)
+    }
+}
+
+/// The context providers mentions can refer to, by name
+#[derive(Clone, Default)]
+pub struct ContextRegistry {
+    providers: Vec<(String, Rc<dyn ContextProvider>)>,
+}
+
+impl ContextRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider, replacing any registered under the same name
+    pub fn register(&mut self, name: impl Into<String>, provider: impl ContextProvider + 'static) {
+        let name = name.into();
+        self.providers.retain(|(registered, _)| *registered != name);
+        self.providers.push((name, Rc::new(provider)));
+    }
+
+    /// Removes a provider, returns whether there was one
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let count = self.providers.len();
+        self.providers.retain(|(registered, _)| registered != name);
+        self.providers.len() != count
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.providers
+            .iter()
+            .any(|(registered, _)| registered == name)
+    }
+
+    /// The names of the registered providers, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .providers
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn provide(&self, mention: &Mention, cwd: &Path) -> Result<Vec<ContentBlock>, Error> {
+        let Some((_, provider)) = self
+            .providers
+            .iter()
+            .find(|(name, _)| *name == mention.provider)
+        else {
+            return Ok(Vec::new());
+        };
+        provider
+            .provide(mention.argument.as_deref(), cwd)
+            .map_err(|e| Error::Internal(format!("@{} failed: {}", mention.provider, e)))
+    }
+
+    /// Adds the content of every mention of a registered provider after the text it appears in
+    ///
+    /// The text itself is sent unchanged so the agent sees what the content refers to. Mentions
+    /// of unknown providers are left as they are and repeated mentions are only expanded once.
+    pub fn expand(
+        &self,
+        content: Vec<ContentBlock>,
+        cwd: &Path,
+    ) -> Result<Vec<ContentBlock>, Error> {
+        let mut expanded = Vec::with_capacity(content.len());
+        let mut seen: Vec<Mention> = Vec::new();
+        for block in content {
+            let mentions = match &block {
+                ContentBlock::Text(text) => mentions(&text.text),
+                _ => Vec::new(),
+            };
+            expanded.push(block);
+            for mention in mentions {
+                if seen.contains(&mention) || !self.contains(&mention.provider) {
+                    continue;
+                }
+                expanded.extend(self.provide(&mention, cwd)?);
+                seen.push(mention);
+            }
+        }
+        Ok(expanded)
+    }
+}
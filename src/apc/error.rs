@@ -3,6 +3,7 @@ pub enum Error {
     Internal(String),
     Connection(String),
     Permissions(String),
+    AuthRequired(String),
 }
 
 impl std::fmt::Display for Error {
@@ -11,8 +12,18 @@ impl std::fmt::Display for Error {
             Error::Connection(msg) => write!(f, "Connection error: {}", msg),
             Error::Permissions(msg) => write!(f, "Permissions error: {}", msg),
             Error::Internal(msg) => write!(f, "Internal error: {}", msg),
+            Error::AuthRequired(msg) => write!(f, "Authentication required: {}", msg),
         }
     }
 }
 
 impl std::error::Error for Error {}
+
+impl From<agent_client_protocol::Error> for Error {
+    fn from(e: agent_client_protocol::Error) -> Self {
+        match e.code {
+            agent_client_protocol::ErrorCode::AuthRequired => Error::AuthRequired(e.to_string()),
+            _ => Error::Internal(e.to_string()),
+        }
+    }
+}
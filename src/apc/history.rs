@@ -0,0 +1,203 @@
+use crate::apc::error::Error;
+use agent_client_protocol::SessionId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A recorded session event, the data is the payload its autocommand was fired with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub event: String,
+    pub data: Value,
+}
+
+impl HistoryEntry {
+    /// An entry recorded now
+    ///
+    /// The buffer numbers tool call locations and diffs carry are left out, they only mean
+    /// something to the Neovim that recorded them.
+    pub fn new(event: impl Into<String>, mut data: Value) -> Self {
+        for key in ["locations", "content"] {
+            if let Some(entries) = data.get_mut(key).and_then(Value::as_array_mut) {
+                for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+                    entry.remove("bufnr");
+                }
+            }
+        }
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            event: event.into(),
+            data,
+        }
+    }
+}
+
+/// Session transcripts kept on disk, one JSONL file per session
+#[derive(Debug, Clone)]
+pub struct History {
+    dir: PathBuf,
+}
+
+impl History {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file a session is recorded to
+    ///
+    /// Bytes unfit for a file name are percent-encoded, so different session ids never share a
+    /// file.
+    pub fn path(&self, session_id: &SessionId) -> PathBuf {
+        let name: String = session_id
+            .to_string()
+            .bytes()
+            .map(|byte| {
+                if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+                    (byte as char).to_string()
+                } else {
+                    format!("%{:02X}", byte)
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.jsonl", name))
+    }
+
+    /// Appends an entry to the session's file, creating it as needed
+    pub fn record(&self, session_id: &SessionId, entry: &HistoryEntry) -> Result<(), Error> {
+        let path = self.path(session_id);
+        let write = || -> std::io::Result<()> {
+            fs::create_dir_all(&self.dir)?;
+            let mut line = serde_json::to_string(entry)?;
+            line.push('\n');
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?
+                .write_all(line.as_bytes())
+        };
+        write()
+            .map_err(|e| Error::Internal(format!("Unable to record to {}: {}", path.display(), e)))
+    }
+
+    /// The entries recorded for a session, in the order they happened
+    pub fn entries(&self, session_id: &SessionId) -> Result<Vec<HistoryEntry>, Error> {
+        let path = self.path(session_id);
+        let content = fs::read_to_string(&path).map_err(|e| {
+            Error::Internal(format!(
+                "No transcript for session {} ({}): {}",
+                session_id,
+                path.display(),
+                e
+            ))
+        })?;
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    Error::Internal(format!(
+                        "Invalid entry on line {} of {}: {}",
+                        index + 1,
+                        path.display(),
+                        e
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// The recorded session, if it was recorded with what the browser needs
+    pub fn session(&self, session_id: &SessionId) -> Result<SessionRecord, Error> {
+        SessionRecord::from_entries(&self.entries(session_id)?).ok_or_else(|| {
+            Error::Internal(format!(
+                "The transcript of session {} doesn't say how it was started",
+                session_id
+            ))
+        })
+    }
+
+    /// Every recorded session, the most recently used first
+    ///
+    /// Files that can't be read or summarized are skipped so one bad file doesn't hide the rest.
+    pub fn sessions(&self) -> Result<Vec<SessionRecord>, Error> {
+        let files = match fs::read_dir(&self.dir) {
+            Ok(files) => files,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(Error::Internal(format!(
+                    "Unable to read {}: {}",
+                    self.dir.display(),
+                    e
+                )));
+            }
+        };
+        let mut sessions: Vec<SessionRecord> = files
+            .filter_map(|file| file.ok().map(|file| file.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "jsonl")
+            })
+            .filter_map(|path| {
+                let content = fs::read_to_string(path).ok()?;
+                let entries: Vec<HistoryEntry> = content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect();
+                SessionRecord::from_entries(&entries)
+            })
+            .collect();
+        sessions.sort_by_key(|record| std::cmp::Reverse(record.updated));
+        Ok(sessions)
+    }
+}
+
+/// What the browser shows of a recorded session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionRecord {
+    pub session_id: SessionId,
+    pub agent: String,
+    pub cwd: Option<String>,
+    /// The text of the first prompt sent in the session
+    pub first_prompt: Option<String>,
+    /// When the first entry was recorded, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// When the last entry was recorded
+    pub updated: u64,
+}
+
+impl SessionRecord {
+    /// Summarizes a session's entries, `None` when they don't say which session or agent it was
+    pub fn from_entries(entries: &[HistoryEntry]) -> Option<Self> {
+        let started = entries
+            .iter()
+            .find(|entry| matches!(entry.event.as_str(), "SessionCreated" | "SessionLoaded"))?;
+        let text = |key: &str| started.data[key].as_str().map(str::to_string);
+        Some(Self {
+            session_id: SessionId::new(text("sessionId")?),
+            agent: text("agent")?,
+            cwd: text("cwd"),
+            first_prompt: entries
+                .iter()
+                .find(|entry| entry.event == "UserMessageComplete")
+                .and_then(|entry| entry.data["text"].as_str())
+                .map(str::to_string),
+            timestamp: entries.first()?.timestamp,
+            updated: entries.last()?.timestamp,
+        })
+    }
+}
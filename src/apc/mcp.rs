@@ -0,0 +1,53 @@
+use crate::apc::error::Error;
+use agent_client_protocol::{McpCapabilities, McpServer};
+
+/// The name the server is given to the agent
+pub fn name(server: &McpServer) -> &str {
+    match server {
+        McpServer::Stdio(server) => &server.name,
+        McpServer::Http(server) => &server.name,
+        McpServer::Sse(server) => &server.name,
+        _ => "",
+    }
+}
+
+/// The transport the agent reaches the server over, as written in the configuration
+pub fn transport(server: &McpServer) -> &'static str {
+    match server {
+        McpServer::Http(_) => "http",
+        McpServer::Sse(_) => "sse",
+        _ => "stdio",
+    }
+}
+
+/// Layers `servers` over `base`, a server replaces the one in `base` with the same name
+pub fn merge(base: &[McpServer], servers: Vec<McpServer>) -> Vec<McpServer> {
+    let mut merged: Vec<McpServer> = base
+        .iter()
+        .filter(|server| !servers.iter().any(|other| name(other) == name(server)))
+        .cloned()
+        .collect();
+    merged.extend(servers);
+    merged
+}
+
+/// Checks `agent` advertised the transport of every server, all agents support stdio
+pub fn check(
+    servers: &[McpServer],
+    capabilities: &McpCapabilities,
+    agent: &str,
+) -> Result<(), Error> {
+    match servers.iter().find(|server| match server {
+        McpServer::Http(_) => !capabilities.http,
+        McpServer::Sse(_) => !capabilities.sse,
+        _ => false,
+    }) {
+        Some(server) => Err(Error::Internal(format!(
+            "MCP server \"{}\" uses {}, which {} doesn't support",
+            name(server),
+            transport(server),
+            agent
+        ))),
+        None => Ok(()),
+    }
+}
@@ -1,3 +1,13 @@
 pub mod client;
+pub mod commands;
+pub mod config_options;
 pub mod connection;
+pub mod context;
 pub mod error;
+pub mod history;
+pub mod mcp;
+pub mod modes;
+pub mod permissions;
+pub mod status;
+pub mod tool_calls;
+pub mod transcript;
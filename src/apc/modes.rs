@@ -0,0 +1,51 @@
+use agent_client_protocol::{SessionId, SessionModeId, SessionModeState};
+use std::collections::HashMap;
+
+/// Keeps the modes each session supports and the one it is currently in
+#[derive(Debug, Clone, Default)]
+pub struct ModeTracker {
+    sessions: HashMap<SessionId, SessionModeState>,
+}
+
+impl ModeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the mode state reported when a session is created or loaded
+    pub fn record(&mut self, session_id: &SessionId, modes: SessionModeState) {
+        self.sessions.insert(session_id.clone(), modes);
+    }
+
+    /// Changes the current mode of a session, returns false when the session has no modes
+    pub fn set_current(&mut self, session_id: &SessionId, mode_id: SessionModeId) -> bool {
+        match self.sessions.get_mut(session_id) {
+            Some(modes) => {
+                modes.current_mode_id = mode_id;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the session advertised a mode, sessions without mode state support none
+    pub fn supports(&self, session_id: &SessionId, mode_id: &SessionModeId) -> bool {
+        self.sessions
+            .get(session_id)
+            .is_some_and(|modes| modes.available_modes.iter().any(|mode| mode.id == *mode_id))
+    }
+
+    pub fn current(&self, session_id: &SessionId) -> Option<&SessionModeId> {
+        self.sessions
+            .get(session_id)
+            .map(|modes| &modes.current_mode_id)
+    }
+
+    pub fn modes(&self, session_id: &SessionId) -> Option<SessionModeState> {
+        self.sessions.get(session_id).cloned()
+    }
+
+    pub fn clear(&mut self, session_id: &SessionId) {
+        self.sessions.remove(session_id);
+    }
+}
@@ -0,0 +1,129 @@
+use agent_client_protocol::{
+    PermissionOptionKind, RequestPermissionOutcome, RequestPermissionRequest,
+    RequestPermissionResponse, SelectedPermissionOutcome, ToolKind,
+};
+
+/// What Hermes does when an agent asks for permission to run a tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PermissionRule {
+    #[default]
+    Ask,
+    Allow,
+    Deny,
+}
+
+impl PermissionRule {
+    pub const NAMES: [&'static str; 3] = ["ask", "allow", "deny"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ask" => Some(PermissionRule::Ask),
+            "allow" => Some(PermissionRule::Allow),
+            "deny" => Some(PermissionRule::Deny),
+            _ => None,
+        }
+    }
+
+    /// The stricter of two rules, asking is stricter than allowing and denying than both
+    pub fn stricter(self, other: Self) -> Self {
+        let strictness = |rule: Self| match rule {
+            PermissionRule::Allow => 0,
+            PermissionRule::Ask => 1,
+            PermissionRule::Deny => 2,
+        };
+        if strictness(other) > strictness(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl std::fmt::Display for PermissionRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermissionRule::Ask => write!(f, "ask"),
+            PermissionRule::Allow => write!(f, "allow"),
+            PermissionRule::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// Rules for answering permission requests without asking the user, per tool kind
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PermissionPolicy {
+    default: PermissionRule,
+    kinds: Vec<(ToolKind, PermissionRule)>,
+}
+
+impl PermissionPolicy {
+    pub fn new(default: PermissionRule) -> Self {
+        Self {
+            default,
+            kinds: Vec::new(),
+        }
+    }
+
+    /// Sets the rule for tools without a rule of their own
+    pub fn with_default(mut self, default: PermissionRule) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Sets the rule for a kind of tool, replacing any previous rule for it
+    pub fn rule(mut self, kind: ToolKind, rule: PermissionRule) -> Self {
+        self.kinds.retain(|(existing, _)| *existing != kind);
+        self.kinds.push((kind, rule));
+        self
+    }
+
+    pub fn rule_for(&self, kind: Option<ToolKind>) -> PermissionRule {
+        kind.and_then(|kind| {
+            self.kinds
+                .iter()
+                .find(|(existing, _)| *existing == kind)
+                .map(|(_, rule)| *rule)
+        })
+        .unwrap_or(self.default)
+    }
+
+    /// Keeps the stricter of both policies' rules for every kind of tool
+    pub fn tightened_by(&self, other: &PermissionPolicy) -> PermissionPolicy {
+        let default = PermissionPolicy::new(self.default.stricter(other.default));
+        self.kinds
+            .iter()
+            .chain(&other.kinds)
+            .fold(default, |policy, (kind, _)| {
+                let rule = self
+                    .rule_for(Some(*kind))
+                    .stricter(other.rule_for(Some(*kind)));
+                policy.rule(*kind, rule)
+            })
+    }
+
+    /// Answers a permission request on the user's behalf
+    ///
+    /// Returns `None` when the user should be asked, either because the rule says so or because
+    /// the agent offered no option matching the rule.
+    pub fn respond(&self, request: &RequestPermissionRequest) -> Option<RequestPermissionResponse> {
+        let preferred = match self.rule_for(request.tool_call.fields.kind) {
+            PermissionRule::Ask => return None,
+            PermissionRule::Allow => [
+                PermissionOptionKind::AllowOnce,
+                PermissionOptionKind::AllowAlways,
+            ],
+            PermissionRule::Deny => [
+                PermissionOptionKind::RejectOnce,
+                PermissionOptionKind::RejectAlways,
+            ],
+        };
+        preferred
+            .iter()
+            .find_map(|kind| request.options.iter().find(|option| option.kind == *kind))
+            .map(|option| {
+                RequestPermissionResponse::new(RequestPermissionOutcome::Selected(
+                    SelectedPermissionOutcome::new(option.option_id.clone()),
+                ))
+            })
+    }
+}
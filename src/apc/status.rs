@@ -0,0 +1,175 @@
+use crate::apc::connection::{Assistant, ConnectionId, ConnectionInfo, Protocol};
+use agent_client_protocol::{AgentCapabilities, InitializeResponse, ProtocolVersion, SessionId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionState {
+    #[default]
+    Connecting,
+    Initialized,
+    Authenticated,
+    Disconnected,
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionState::Connecting => write!(f, "connecting"),
+            ConnectionState::Initialized => write!(f, "initialized"),
+            ConnectionState::Authenticated => write!(f, "authenticated"),
+            ConnectionState::Disconnected => write!(f, "disconnected"),
+        }
+    }
+}
+
+/// What is known about a connection to an agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentStatus {
+    pub agent: Assistant,
+    pub label: Option<String>,
+    pub state: ConnectionState,
+    pub protocol: Protocol,
+    pub pid: Option<u32>,
+    pub protocol_version: Option<ProtocolVersion>,
+    pub capabilities: Option<AgentCapabilities>,
+    pub sessions: Vec<SessionId>,
+    /// Sessions waiting on the agent to finish a prompt turn
+    pub prompting: Vec<SessionId>,
+    pub last_error: Option<String>,
+}
+
+/// Follows each agent connection through its lifecycle, kept after disconnecting so the last
+/// error can still be looked at
+#[derive(Debug, Clone, Default)]
+pub struct StatusTracker {
+    /// In the order the connections were made
+    connections: Vec<(ConnectionId, AgentStatus)>,
+}
+
+impl StatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_mut(&mut self, id: &ConnectionId) -> Option<&mut AgentStatus> {
+        self.connections
+            .iter_mut()
+            .find(|(tracked, _)| tracked == id)
+            .map(|(_, status)| status)
+    }
+
+    fn entry(&mut self, id: &ConnectionId) -> &mut AgentStatus {
+        let index = match self
+            .connections
+            .iter()
+            .position(|(tracked, _)| tracked == id)
+        {
+            Some(index) => index,
+            None => {
+                self.connections.push((id.clone(), AgentStatus::default()));
+                self.connections.len() - 1
+            }
+        };
+        &mut self.connections[index].1
+    }
+
+    /// Starts tracking a new connection attempt
+    ///
+    /// Closed connections to the same agent under the same label are forgotten, the new
+    /// connection takes their place.
+    pub fn connecting(&mut self, connection: &ConnectionInfo, protocol: Protocol) {
+        self.connections.retain(|(id, status)| {
+            *id != connection.id
+                && !(status.state == ConnectionState::Disconnected
+                    && status.agent == connection.agent
+                    && status.label == connection.label)
+        });
+        self.connections.push((
+            connection.id.clone(),
+            AgentStatus {
+                agent: connection.agent.clone(),
+                label: connection.label.clone(),
+                protocol,
+                ..AgentStatus::default()
+            },
+        ));
+    }
+
+    pub fn initialized(
+        &mut self,
+        id: &ConnectionId,
+        pid: Option<u32>,
+        response: &InitializeResponse,
+    ) {
+        let status = self.entry(id);
+        status.state = ConnectionState::Initialized;
+        status.pid = pid;
+        status.protocol_version = Some(response.protocol_version.clone());
+        status.capabilities = Some(response.agent_capabilities.clone());
+    }
+
+    pub fn authenticated(&mut self, id: &ConnectionId) {
+        if let Some(status) = self.get_mut(id) {
+            status.state = ConnectionState::Authenticated;
+        }
+    }
+
+    /// Marks the connection closed, its sessions and process are gone
+    pub fn disconnected(&mut self, id: &ConnectionId) {
+        if let Some(status) = self.get_mut(id) {
+            status.state = ConnectionState::Disconnected;
+            status.pid = None;
+            status.sessions.clear();
+            status.prompting.clear();
+        }
+    }
+
+    /// Records an error from the agent, a connection that never got initialized is disconnected
+    pub fn failed(&mut self, id: &ConnectionId, error: impl ToString) {
+        let status = self.entry(id);
+        if status.state == ConnectionState::Connecting {
+            status.state = ConnectionState::Disconnected;
+        }
+        status.last_error = Some(error.to_string());
+    }
+
+    pub fn session_created(&mut self, id: &ConnectionId, session_id: &SessionId) {
+        if let Some(status) = self.get_mut(id)
+            && !status.sessions.contains(session_id)
+        {
+            status.sessions.push(session_id.clone());
+        }
+    }
+
+    pub fn turn_started(&mut self, id: &ConnectionId, session_id: &SessionId) {
+        if let Some(status) = self.get_mut(id)
+            && !status.prompting.contains(session_id)
+        {
+            status.prompting.push(session_id.clone());
+        }
+    }
+
+    pub fn turn_finished(&mut self, id: &ConnectionId, session_id: &SessionId) {
+        if let Some(status) = self.get_mut(id) {
+            status.prompting.retain(|prompting| prompting != session_id);
+        }
+    }
+
+    /// Whether a prompt turn runs on any connection
+    pub fn is_prompting(&self) -> bool {
+        self.connections
+            .iter()
+            .any(|(_, status)| !status.prompting.is_empty())
+    }
+
+    pub fn get(&self, id: &ConnectionId) -> Option<&AgentStatus> {
+        self.connections
+            .iter()
+            .find(|(tracked, _)| tracked == id)
+            .map(|(_, status)| status)
+    }
+
+    /// Every connection Hermes tried to make, in the order they were made
+    pub fn connections(&self) -> Vec<(ConnectionId, AgentStatus)> {
+        self.connections.clone()
+    }
+}
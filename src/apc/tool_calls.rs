@@ -0,0 +1,68 @@
+use agent_client_protocol::{SessionId, ToolCall, ToolCallId, ToolCallUpdate};
+use std::collections::HashMap;
+
+/// Keeps the merged state of every tool call, per session, in the order they were first reported
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallTracker {
+    sessions: HashMap<SessionId, Vec<ToolCall>>,
+}
+
+impl ToolCallTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find(&mut self, session_id: &SessionId, id: &ToolCallId) -> Option<&mut ToolCall> {
+        self.sessions
+            .get_mut(session_id)?
+            .iter_mut()
+            .find(|tool_call| tool_call.tool_call_id == *id)
+    }
+
+    /// Records a newly reported tool call, replacing any previous call with the same id
+    pub fn record(&mut self, session_id: &SessionId, tool_call: ToolCall) -> ToolCall {
+        if let Some(existing) = self.find(session_id, &tool_call.tool_call_id) {
+            *existing = tool_call.clone();
+        } else {
+            self.sessions
+                .entry(session_id.clone())
+                .or_default()
+                .push(tool_call.clone());
+        }
+        tool_call
+    }
+
+    /// Applies an update to a tracked tool call and returns the merged result
+    ///
+    /// Updates for unknown tool calls are recorded when they carry enough information to build
+    /// a tool call (a title), otherwise they are ignored and `None` is returned.
+    pub fn apply(&mut self, session_id: &SessionId, update: ToolCallUpdate) -> Option<ToolCall> {
+        match self.find(session_id, &update.tool_call_id) {
+            Some(existing) => {
+                existing.update(update.fields);
+                if update.meta.is_some() {
+                    existing.meta = update.meta;
+                }
+                Some(existing.clone())
+            }
+            None => ToolCall::try_from(update)
+                .ok()
+                .map(|tool_call| self.record(session_id, tool_call)),
+        }
+    }
+
+    pub fn get(&self, session_id: &SessionId, id: &ToolCallId) -> Option<&ToolCall> {
+        self.sessions
+            .get(session_id)?
+            .iter()
+            .find(|tool_call| tool_call.tool_call_id == *id)
+    }
+
+    pub fn tool_calls(&self, session_id: &SessionId) -> Vec<ToolCall> {
+        self.sessions.get(session_id).cloned().unwrap_or_default()
+    }
+
+    pub fn clear(&mut self, session_id: &SessionId) {
+        self.sessions.remove(session_id);
+    }
+}
@@ -0,0 +1,137 @@
+use agent_client_protocol::{ContentBlock, SessionId};
+use std::{collections::HashMap, mem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageRole {
+    User,
+    Agent,
+    Thought,
+}
+
+impl std::fmt::Display for MessageRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageRole::User => write!(f, "user"),
+            MessageRole::Agent => write!(f, "agent"),
+            MessageRole::Thought => write!(f, "thought"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub role: MessageRole,
+    pub content: Vec<ContentBlock>,
+}
+
+impl Message {
+    pub fn new(role: MessageRole, content: Vec<ContentBlock>) -> Self {
+        Self { role, content }
+    }
+
+    /// The concatenated text of every text block in the message
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn accepts(&self, role: MessageRole, block: &ContentBlock) -> bool {
+        self.role == role
+            && self
+                .content
+                .last()
+                .is_none_or(|last| mem::discriminant(last) == mem::discriminant(block))
+    }
+
+    fn append(&mut self, block: ContentBlock) {
+        match (self.content.last_mut(), block) {
+            (Some(ContentBlock::Text(last)), ContentBlock::Text(text)) => {
+                last.text.push_str(&text.text)
+            }
+            (_, block) => self.content.push(block),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Conversation {
+    messages: Vec<Message>,
+    pending: Option<Message>,
+}
+
+impl Conversation {
+    fn complete(&mut self) -> Option<Message> {
+        let message = self.pending.take()?;
+        self.messages.push(message.clone());
+        Some(message)
+    }
+}
+
+/// Assembles streamed message chunks into complete messages, per session
+///
+/// A message is complete once a chunk arrives with a different role or content type, or when
+/// the turn ends.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    sessions: HashMap<SessionId, Conversation>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a streamed chunk, returning the message it completed if any
+    pub fn push(
+        &mut self,
+        session_id: &SessionId,
+        role: MessageRole,
+        block: ContentBlock,
+    ) -> Option<Message> {
+        let conversation = self.sessions.entry(session_id.clone()).or_default();
+        let completed = match &conversation.pending {
+            Some(pending) if pending.accepts(role, &block) => None,
+            _ => conversation.complete(),
+        };
+        conversation
+            .pending
+            .get_or_insert_with(|| Message::new(role, Vec::new()))
+            .append(block);
+        completed
+    }
+
+    /// Adds a message that was not streamed (e.g. a prompt sent by the user)
+    pub fn record(&mut self, session_id: &SessionId, message: Message) -> Option<Message> {
+        let conversation = self.sessions.entry(session_id.clone()).or_default();
+        let completed = conversation.complete();
+        conversation.messages.push(message);
+        completed
+    }
+
+    /// Completes the message currently being streamed, used when the turn ends
+    pub fn complete(&mut self, session_id: &SessionId) -> Option<Message> {
+        self.sessions.get_mut(session_id)?.complete()
+    }
+
+    /// Completed messages in the order they were received
+    pub fn messages(&self, session_id: &SessionId) -> Vec<Message> {
+        self.sessions
+            .get(session_id)
+            .map(|conversation| conversation.messages.clone())
+            .unwrap_or_default()
+    }
+
+    /// The message currently being streamed
+    pub fn pending(&self, session_id: &SessionId) -> Option<Message> {
+        self.sessions.get(session_id)?.pending.clone()
+    }
+
+    pub fn clear(&mut self, session_id: &SessionId) {
+        self.sessions.remove(session_id);
+    }
+}
@@ -12,11 +12,11 @@
 //!
 //! # Example
 //!
-//! ```
-//! use hermes::client::{ApcClient, ClientConfig};
+//! ```no_run
+//! use hermes::{ApcClient, ClientConfig, nvim::producer::EventHandler};
 //!
 //! let config = ClientConfig::default();
-//! let client = ApcClient::new(config);
+//! let client = ApcClient::new(config, EventHandler::default());
 //! assert_eq!(client.config().name, "hermes");
 //! ```
 
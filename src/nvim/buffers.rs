@@ -0,0 +1,65 @@
+use nvim_oxi::{Dictionary, ObjectKind, conversion::FromObject};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Finds the number of a loaded buffer that has the given file open
+///
+/// Buffers are usually named by their full path, so names are compared as they are first. Only
+/// when none matches are they resolved, e.g. to see through symlinks.
+pub fn find_buffer(path: &Path) -> Option<i32> {
+    let target = normalize(path);
+    let buffers: Vec<(PathBuf, i32)> = nvim_oxi::api::list_bufs()
+        .filter(|buffer| buffer.is_loaded())
+        .filter_map(|buffer| {
+            let name = buffer.get_name().ok()?;
+            (!name.as_os_str().is_empty()).then(|| (name, buffer.handle()))
+        })
+        .collect();
+    buffers
+        .iter()
+        .find(|(name, _)| *name == target || name == path)
+        .or_else(|| buffers.iter().find(|(name, _)| normalize(name) == target))
+        .map(|(_, handle)| *handle)
+}
+
+/// Attaches the buffer number of the entry's file, looked up once per path of an event
+fn attach_buffer_number(entry: &mut Dictionary, found: &mut HashMap<String, Option<i32>>) {
+    let path = entry
+        .get("path")
+        .and_then(|path| String::from_object(path.clone()).ok());
+    let bufnr = path.and_then(|path| {
+        *found
+            .entry(path)
+            .or_insert_with_key(|path| find_buffer(Path::new(path)))
+    });
+    if let Some(bufnr) = bufnr {
+        entry.insert("bufnr", bufnr);
+    }
+}
+
+/// Adds a `bufnr` to the locations and diffs of a tool call event when their file is open
+pub fn attach_buffer_numbers(data: &mut Dictionary) {
+    let mut found = HashMap::new();
+    for key in ["locations", "content"] {
+        if let Some(entries) = data.get_mut(key)
+            && entries.kind() == ObjectKind::Array
+        {
+            let entries = unsafe { entries.as_array_unchecked_mut() };
+            for entry in entries.iter_mut() {
+                if entry.kind() == ObjectKind::Dictionary {
+                    attach_buffer_number(
+                        unsafe { entry.as_dictionary_unchecked_mut() },
+                        &mut found,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,529 @@
+//! The `:Hermes` user command, an entry point for people who don't want to write Lua
+use crate::{
+    apc::{
+        connection::{Assistant, ConnectionDetails, ConnectionId},
+        status::{AgentStatus, ConnectionState},
+        transcript::Message,
+    },
+    nvim::{PluginState, cancel_turn, change_config_option, change_mode, create_session},
+};
+use agent_client_protocol::{
+    ContentBlock, SessionConfigOptionCategory, SessionConfigValueId, SessionId, SessionModeId,
+    TextContent,
+};
+use nvim_oxi::{
+    Dictionary,
+    api::{
+        self,
+        opts::{CreateCommandOpts, OptionOpts},
+        types::{CommandArgs, CommandComplete, CommandNArgs, LogLevel},
+    },
+    lua::Error,
+};
+use std::{rc::Rc, sync::Mutex};
+
+pub const NAME: &str = "Hermes";
+
+pub const SUBCOMMANDS: [&str; 9] = [
+    "connect",
+    "disconnect",
+    "status",
+    "prompt",
+    "cancel",
+    "mode",
+    "model",
+    "sessions",
+    "log",
+];
+
+/// A parsed `:Hermes` invocation
+///
+/// Subcommands acting on a session take its id as their first argument, when it isn't a known
+/// session the current one is used instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Subcommand {
+    /// The agent to launch and a label for the new connection
+    Connect(Option<Assistant>, Option<String>),
+    /// A connection id, label or agent name
+    Disconnect(Option<String>),
+    Status,
+    Prompt(Option<SessionId>, String),
+    Cancel(Option<SessionId>),
+    Mode(Option<SessionId>, Option<SessionModeId>),
+    Model(Option<SessionId>, Option<SessionConfigValueId>),
+    Sessions,
+    Log(Option<SessionId>),
+}
+
+impl Subcommand {
+    pub fn parse(args: &[String], sessions: &[SessionId]) -> Result<Self, Error> {
+        let Some((name, rest)) = args.split_first() else {
+            return Err(usage());
+        };
+        let (session_id, rest) = match rest.split_first() {
+            Some((first, rest)) if sessions.iter().any(|id| *id.0 == **first) => {
+                (Some(SessionId::new(first.as_str())), rest)
+            }
+            _ => (None, rest),
+        };
+        let no_more = || match rest.first() {
+            None => Ok(session_id.clone()),
+            Some(unknown) => Err(Error::RuntimeError(format!("Unknown session {}", unknown))),
+        };
+        let single = |what: &str| match rest {
+            [] => Ok(None),
+            [value] => Ok(Some(value.clone())),
+            _ => Err(Error::RuntimeError(format!(
+                "Too many arguments for \"{}\", expected a single {}",
+                name, what
+            ))),
+        };
+        let too_many = |expected: &str| {
+            Error::RuntimeError(format!(
+                "Too many arguments for \"{}\", expected {}",
+                name, expected
+            ))
+        };
+        match name.as_str() {
+            "connect" => match args.get(1..) {
+                Some([]) | None => Ok(Self::Connect(None, None)),
+                Some([agent]) => Ok(Self::Connect(Some(Assistant::from(agent.as_str())), None)),
+                Some([agent, label]) => Ok(Self::Connect(
+                    Some(Assistant::from(agent.as_str())),
+                    Some(label.clone()),
+                )),
+                Some(_) => Err(too_many("an agent and a label")),
+            },
+            "disconnect" => match args.get(1..) {
+                Some([]) | None => Ok(Self::Disconnect(None)),
+                Some([target]) => Ok(Self::Disconnect(Some(target.clone()))),
+                Some(_) => Err(too_many("a single connection")),
+            },
+            "status" => Ok(Self::Status),
+            "sessions" => Ok(Self::Sessions),
+            "prompt" if rest.is_empty() => Err(Error::RuntimeError(
+                "Missing the text to send with \"prompt\"".to_string(),
+            )),
+            "prompt" => Ok(Self::Prompt(session_id, rest.join(" "))),
+            "cancel" => no_more().map(Self::Cancel),
+            "mode" => {
+                single("mode").map(|mode| Self::Mode(session_id, mode.map(SessionModeId::new)))
+            }
+            "model" => single("model")
+                .map(|model| Self::Model(session_id, model.map(SessionConfigValueId::new))),
+            "log" => no_more().map(Self::Log),
+            _ => Err(usage()),
+        }
+    }
+}
+
+fn usage() -> Error {
+    Error::RuntimeError(format!(
+        "Usage: :{} {{{}}} [args]",
+        NAME,
+        SUBCOMMANDS.join("|")
+    ))
+}
+
+/// What `:Hermes` can complete, looked up when the user asks for completions
+pub trait Completion {
+    fn agents(&self) -> Vec<String>;
+    /// The ids and labels of the open connections
+    fn connections(&self) -> Vec<String>;
+    fn sessions(&self) -> Vec<String>;
+    fn modes(&self, session_id: Option<&str>) -> Vec<String>;
+    fn models(&self, session_id: Option<&str>) -> Vec<String>;
+}
+
+/// Completes the argument being typed at the end of `cmd_line`
+pub fn complete(arg_lead: &str, cmd_line: &str, source: &impl Completion) -> Vec<String> {
+    let words: Vec<&str> = cmd_line.split_whitespace().skip(1).collect();
+    // The words before the one being completed
+    let done = if arg_lead.is_empty() {
+        &words[..]
+    } else {
+        &words[..words.len().saturating_sub(1)]
+    };
+    let sessions = source.sessions();
+    let session = done
+        .get(1)
+        .copied()
+        .filter(|word| sessions.iter().any(|id| id == word));
+    let candidates = match done {
+        [] => SUBCOMMANDS.iter().map(ToString::to_string).collect(),
+        ["connect"] => source.agents(),
+        ["disconnect"] => source.connections(),
+        ["prompt" | "cancel" | "log"] => sessions,
+        ["mode"] => sessions.into_iter().chain(source.modes(None)).collect(),
+        ["mode", _] if session.is_some() => source.modes(session),
+        ["model"] => sessions.into_iter().chain(source.models(None)).collect(),
+        ["model", _] if session.is_some() => source.models(session),
+        _ => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(arg_lead))
+        .collect()
+}
+
+/// Summarises a connection's status on a single line
+pub fn status_line(id: &ConnectionId, status: &AgentStatus) -> String {
+    let mut line = match &status.label {
+        Some(label) => format!("{} ({}): {}", id, label, status.state),
+        None => format!("{}: {}", id, status.state),
+    };
+    if let Some(pid) = status.pid {
+        line.push_str(&format!(", pid {}", pid));
+    }
+    if status.state != ConnectionState::Disconnected {
+        line.push_str(&format!(", {} session(s)", status.sessions.len()));
+    }
+    if !status.prompting.is_empty() {
+        line.push_str(&format!(", {} prompting", status.prompting.len()));
+    }
+    if let Some(error) = &status.last_error {
+        line.push_str(&format!(", last error: {}", error));
+    }
+    line
+}
+
+/// Renders a transcript as lines of markdown, one heading per message
+pub fn log_lines(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .flat_map(|message| {
+            let text = message.text();
+            std::iter::once(format!("## {}", message.role))
+                .chain(text.lines().map(ToString::to_string).collect::<Vec<_>>())
+                .chain(std::iter::once(String::new()))
+        })
+        .collect()
+}
+
+struct StateCompletion<'a>(&'a PluginState);
+
+impl StateCompletion<'_> {
+    fn session(&self, session_id: Option<&str>) -> Option<SessionId> {
+        session_id
+            .map(SessionId::new)
+            .or_else(|| self.0.current_session().cloned())
+    }
+}
+
+impl Completion for StateCompletion<'_> {
+    fn agents(&self) -> Vec<String> {
+        self.0
+            .connection
+            .known_agents()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn connections(&self) -> Vec<String> {
+        self.0
+            .connection
+            .connections()
+            .into_iter()
+            .flat_map(|connection| {
+                std::iter::once(connection.id.to_string()).chain(connection.label)
+            })
+            .collect()
+    }
+
+    fn sessions(&self) -> Vec<String> {
+        self.0
+            .connection
+            .sessions()
+            .into_iter()
+            .map(|(session_id, _)| session_id.to_string())
+            .collect()
+    }
+
+    fn modes(&self, session_id: Option<&str>) -> Vec<String> {
+        let Some(session_id) = self.session(session_id) else {
+            return Vec::new();
+        };
+        self.0
+            .handler()
+            .modes()
+            .lock()
+            .ok()
+            .and_then(|tracker| tracker.modes(&session_id))
+            .map(|state| {
+                state
+                    .available_modes
+                    .into_iter()
+                    .map(|mode| mode.id.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn models(&self, session_id: Option<&str>) -> Vec<String> {
+        let Some(session_id) = self.session(session_id) else {
+            return Vec::new();
+        };
+        model_values(self.0, &session_id)
+            .map(|(_, values)| values)
+            .unwrap_or_default()
+    }
+}
+
+/// The id of the session's model option and the values it accepts
+fn model_values(
+    state: &PluginState,
+    session_id: &SessionId,
+) -> Option<(agent_client_protocol::SessionConfigId, Vec<String>)> {
+    let tracker = state.handler().config_options();
+    let tracker = tracker.lock().ok()?;
+    tracker
+        .options(session_id)
+        .into_iter()
+        .find(|option| option.category == Some(SessionConfigOptionCategory::Model))
+        .map(|option| {
+            let values = crate::apc::config_options::values(&option)
+                .into_iter()
+                .map(ToString::to_string)
+                .collect();
+            (option.id, values)
+        })
+}
+
+fn locked(state: &Mutex<PluginState>) -> Result<std::sync::MutexGuard<'_, PluginState>, Error> {
+    state.lock().map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+fn notify(message: &str) -> Result<(), Error> {
+    api::notify(message, LogLevel::Info, &Dictionary::new())
+        .map(|_| ())
+        .map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// The session named on the command line, or the current one
+fn session_or_current(
+    state: &Mutex<PluginState>,
+    session_id: Option<SessionId>,
+) -> Result<SessionId, Error> {
+    match session_id {
+        Some(session_id) => Ok(session_id),
+        None => locked(state)?.current_session().cloned().ok_or_else(|| {
+            Error::RuntimeError(format!(
+                "No session, start one with :{} prompt or name one of :{} sessions",
+                NAME, NAME
+            ))
+        }),
+    }
+}
+
+/// The connection named on the command line, or the oldest one
+fn connection_or_first(
+    state: &Mutex<PluginState>,
+    target: Option<String>,
+) -> Result<ConnectionId, Error> {
+    let state = locked(state)?;
+    match target {
+        Some(target) => state.resolve_connection(Some(&target)),
+        None => state.resolve_connection(None).map_err(|_| {
+            Error::RuntimeError(format!("Not connected to any agent, run :{} connect", NAME))
+        }),
+    }
+    .map(|connection| connection.id)
+}
+
+fn run(state: &Rc<Mutex<PluginState>>, subcommand: Subcommand) -> Result<(), Error> {
+    match subcommand {
+        Subcommand::Connect(agent, label) => {
+            let agent = agent.unwrap_or_default();
+            let id = super::connect_agent(
+                state,
+                ConnectionDetails {
+                    agent: agent.clone(),
+                    label,
+                    ..ConnectionDetails::default()
+                },
+            )?;
+            notify(&format!("Connected to {} as {}", agent, id))
+        }
+        Subcommand::Disconnect(target) => {
+            let id = connection_or_first(state, target)?;
+            super::disconnect_agent(state, &id.0)?;
+            notify(&format!("Disconnected {}", id))
+        }
+        Subcommand::Status => {
+            let connections = locked(state)?
+                .status()
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .connections();
+            if connections.is_empty() {
+                return notify("Not connected to any agent");
+            }
+            let lines: Vec<String> = connections
+                .into_iter()
+                .map(|(id, status)| status_line(&id, &status))
+                .collect();
+            notify(&lines.join("\n"))
+        }
+        Subcommand::Prompt(session_id, text) => {
+            let session_id = match session_id.or_else(|| {
+                locked(state)
+                    .ok()
+                    .and_then(|state| state.current_session().cloned())
+            }) {
+                Some(session_id) => session_id,
+                None => {
+                    let id = connection_or_first(state, None)?;
+                    create_session(state, Some(&id.0), None, Vec::new())?
+                }
+            };
+            super::send_prompt(
+                state,
+                None,
+                session_id,
+                vec![ContentBlock::Text(TextContent::new(text))],
+                |finished| {
+                    super::report_turn(
+                        finished.and_then(|stop_reason| {
+                            notify(&format!("Turn finished: {}", stop_reason))
+                        }),
+                    )
+                },
+            )
+        }
+        Subcommand::Cancel(session_id) => {
+            let session_id = session_or_current(state, session_id)?;
+            let running = locked(state)?
+                .status()
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .connections()
+                .iter()
+                .any(|(_, status)| status.prompting.contains(&session_id));
+            if !running {
+                return notify(&format!("No turn is running in session {}", session_id));
+            }
+            cancel_turn(state, session_id)
+        }
+        Subcommand::Mode(session_id, mode_id) => {
+            let session_id = session_or_current(state, session_id)?;
+            match mode_id {
+                Some(mode_id) => change_mode(state, session_id, mode_id),
+                None => {
+                    let modes = locked(state)?.handler().modes();
+                    let current = modes
+                        .lock()
+                        .map_err(|e| Error::RuntimeError(e.to_string()))?
+                        .current(&session_id)
+                        .map(ToString::to_string);
+                    let available = StateCompletion(&*locked(state)?).modes(Some(&session_id.0));
+                    notify(&format!(
+                        "Mode: {} (available: {})",
+                        current.unwrap_or_else(|| "none".to_string()),
+                        available.join(", ")
+                    ))
+                }
+            }
+        }
+        Subcommand::Model(session_id, value) => {
+            let session_id = session_or_current(state, session_id)?;
+            let (option_id, values) =
+                model_values(&*locked(state)?, &session_id).ok_or_else(|| {
+                    Error::RuntimeError(format!(
+                        "Session {} doesn't offer a model selection",
+                        session_id
+                    ))
+                })?;
+            match value {
+                Some(value) => change_config_option(state, session_id, option_id, value),
+                None => notify(&format!("Models: {}", values.join(", "))),
+            }
+        }
+        Subcommand::Sessions => {
+            let (sessions, current) = {
+                let state = locked(state)?;
+                (
+                    state.connection.sessions(),
+                    state.current_session().cloned(),
+                )
+            };
+            if sessions.is_empty() {
+                return notify("No sessions");
+            }
+            let lines: Vec<String> = sessions
+                .into_iter()
+                .map(|(session_id, connection)| {
+                    let marker = if Some(&session_id) == current.as_ref() {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    format!("{} {} ({})", marker, session_id, connection)
+                })
+                .collect();
+            notify(&lines.join("\n"))
+        }
+        Subcommand::Log(session_id) => {
+            let session_id = session_or_current(state, session_id)?;
+            let transcript = locked(state)?.handler().transcript();
+            let messages = transcript
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .messages(&session_id);
+            open_log(&session_id, log_lines(&messages))
+        }
+    }
+}
+
+/// Shows the transcript in a scratch buffer in a new split
+fn open_log(session_id: &SessionId, lines: Vec<String>) -> Result<(), Error> {
+    let mut buffer =
+        api::create_buf(false, true).map_err(|e| Error::RuntimeError(e.to_string()))?;
+    buffer
+        .set_lines(.., false, lines)
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    let opts = OptionOpts::builder().buffer(buffer.clone()).build();
+    api::set_option_value("filetype", "markdown", &opts)
+        .and_then(|_| api::set_option_value("modifiable", false, &opts))
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    api::command("split").map_err(|e| Error::RuntimeError(e.to_string()))?;
+    api::get_current_win()
+        .set_buf(&buffer)
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    // Naming fails when a log for the session is already open, the buffer works without it
+    let _ = buffer.set_name(format!("hermes://{}/log", session_id));
+    Ok(())
+}
+
+/// Creates the `:Hermes` command
+pub fn register(plugin_state: &Rc<Mutex<PluginState>>) -> Result<(), Error> {
+    let state = plugin_state.clone();
+    let completion = nvim_oxi::Function::from_fn(
+        move |(arg_lead, cmd_line, _cursor): (String, String, usize)| match state.lock() {
+            Ok(state) => complete(&arg_lead, &cmd_line, &StateCompletion(&state)),
+            Err(_) => Vec::new(),
+        },
+    );
+
+    let opts = CreateCommandOpts::builder()
+        .desc("Talk to ACP agents")
+        .nargs(CommandNArgs::Any)
+        .complete(CommandComplete::CustomList(completion))
+        .build();
+
+    let state = plugin_state.clone();
+    api::create_user_command(
+        NAME,
+        move |args: CommandArgs| {
+            let sessions: Vec<SessionId> = locked(&state)?
+                .connection
+                .sessions()
+                .into_iter()
+                .map(|(session_id, _)| session_id)
+                .collect();
+            run(&state, Subcommand::parse(&args.fargs, &sessions)?)
+        },
+        &opts,
+    )
+    .map_err(|e| Error::RuntimeError(e.to_string()))
+}
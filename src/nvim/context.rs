@@ -0,0 +1,263 @@
+//! The context providers Hermes ships with and the ones registered from Lua
+//!
+//! Each turns a mention in a prompt into content blocks, see [`crate::apc::context`].
+use crate::apc::context::{ContextProvider, ContextRegistry};
+use crate::nvim::mcp::{Editor, NeovimEditor, lua_json};
+use crate::nvim::prompt;
+use agent_client_protocol::{
+    ContentBlock, EmbeddedResource, EmbeddedResourceResource, ResourceLink, TextResourceContents,
+};
+use nvim_oxi::{Array, Function, Object, ObjectKind, conversion::FromObject};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::process::Command;
+
+/// Embeds text as a resource the agent reads along with the prompt
+pub fn text_resource(
+    uri: impl Into<String>,
+    text: impl Into<String>,
+    mime_type: &str,
+) -> ContentBlock {
+    ContentBlock::Resource(EmbeddedResource::new(
+        EmbeddedResourceResource::TextResourceContents(
+            TextResourceContents::new(text, uri).mime_type(mime_type.to_string()),
+        ),
+    ))
+}
+
+pub fn file_uri(path: &str) -> String {
+    format!("file://{}", path)
+}
+
+fn string(value: &Value, key: &str) -> String {
+    match &value[key] {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Formats locations as `path:line:column: text` lines, the way `:make` output reads
+fn location_lines(items: &Value, text: impl Fn(&Value) -> String) -> String {
+    items
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| {
+                    format!(
+                        "{}:{}:{}: {}",
+                        string(item, "path"),
+                        string(item, "line"),
+                        string(item, "column"),
+                        text(item)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// Formats diagnostics as returned by the editor, one per line
+pub fn diagnostics_text(diagnostics: &Value) -> String {
+    location_lines(diagnostics, |diagnostic| {
+        let message = match diagnostic["source"].as_str() {
+            Some(source) => format!("{} [{}]", string(diagnostic, "message"), source),
+            None => string(diagnostic, "message"),
+        };
+        format!("{}: {}", string(diagnostic, "severity"), message)
+    })
+}
+
+/// Formats quickfix items as returned by the editor, one per line
+pub fn quickfix_text(items: &Value) -> String {
+    location_lines(items, |item| string(item, "text"))
+}
+
+/// Links to the definitions of a symbol, the agent reads the files it needs
+pub fn symbol_links(symbols: &Value) -> Vec<ContentBlock> {
+    symbols
+        .as_array()
+        .map(|symbols| {
+            symbols
+                .iter()
+                .map(|symbol| {
+                    ContentBlock::ResourceLink(
+                        ResourceLink::new(
+                            string(symbol, "name"),
+                            file_uri(&string(symbol, "path")),
+                        )
+                        .description(format!(
+                            "{} at line {}",
+                            string(symbol, "kind"),
+                            string(symbol, "line")
+                        )),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+const BUFFER: &str = r#"(function()
+    local bufnr = vim.api.nvim_get_current_buf()
+    if _A.name then
+        bufnr = nil
+        for _, info in ipairs(vim.fn.getbufinfo({ bufloaded = 1 })) do
+            if info.name == _A.name or vim.endswith(info.name, "/" .. _A.name) then
+                bufnr = info.bufnr
+                break
+            end
+        end
+        if not bufnr then
+            return vim.NIL
+        end
+    end
+    return {
+        path = vim.api.nvim_buf_get_name(bufnr),
+        filetype = vim.bo[bufnr].filetype,
+        text = table.concat(vim.api.nvim_buf_get_lines(bufnr, 0, -1, false), "\n"),
+    }
+end)()"#;
+
+const SYMBOLS: &str = r#"(function()
+    local symbols = {}
+    local responses = vim.lsp.buf_request_sync(0, "workspace/symbol", { query = _A.name }, 2000) or {}
+    for _, response in pairs(responses) do
+        for _, symbol in ipairs(response.result or {}) do
+            if symbol.name == _A.name and symbol.location.range then
+                table.insert(symbols, {
+                    name = symbol.name,
+                    kind = vim.lsp.protocol.SymbolKind[symbol.kind] or "Symbol",
+                    path = vim.uri_to_fname(symbol.location.uri),
+                    line = symbol.location.range.start.line + 1,
+                })
+            end
+        end
+    end
+    return symbols
+end)()"#;
+
+fn diagnostics(argument: Option<&str>, _: &Path) -> Result<Vec<ContentBlock>, String> {
+    let diagnostics = NeovimEditor.diagnostics(argument)?;
+    Ok(vec![text_resource(
+        "neovim://diagnostics",
+        diagnostics_text(&diagnostics),
+        "text/plain",
+    )])
+}
+
+fn quickfix(_: Option<&str>, _: &Path) -> Result<Vec<ContentBlock>, String> {
+    let items = NeovimEditor.quickfix()?;
+    Ok(vec![text_resource(
+        "neovim://quickfix",
+        quickfix_text(&items),
+        "text/plain",
+    )])
+}
+
+/// The arguments of `git diff` for a mention's argument, a path when it names one in `cwd` and a
+/// revision otherwise
+///
+/// Arguments starting with `-` are refused, git would take them for options.
+pub fn diff_args(argument: Option<&str>, cwd: &Path) -> Result<Vec<String>, String> {
+    let mut args = vec!["diff".to_string(), "--no-color".to_string()];
+    match argument {
+        Some(argument) if argument.starts_with('-') => {
+            return Err(format!("\"{}\" is not a revision or path", argument));
+        }
+        Some(path) if cwd.join(path).exists() => args.extend(["--".to_string(), path.to_string()]),
+        Some(revision) => args.extend([revision.to_string(), "--".to_string()]),
+        None => {}
+    }
+    Ok(args)
+}
+
+/// `git diff` of the session's working directory, the argument is passed on as a revision or path
+fn diff(argument: Option<&str>, cwd: &Path) -> Result<Vec<ContentBlock>, String> {
+    let output = Command::new("git")
+        .args(diff_args(argument, cwd)?)
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| format!("Unable to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(vec![text_resource(
+        "git://diff",
+        String::from_utf8_lossy(&output.stdout),
+        "text/x-diff",
+    )])
+}
+
+/// A loaded buffer matched by name or path suffix, the current buffer without an argument
+fn buffer(argument: Option<&str>, _: &Path) -> Result<Vec<ContentBlock>, String> {
+    let buffer = lua_json(BUFFER, json!({ "name": argument }))?;
+    if buffer.is_null() {
+        return Err(format!(
+            "No loaded buffer matches \"{}\"",
+            argument.unwrap_or_default()
+        ));
+    }
+    let mime_type = match buffer["filetype"].as_str() {
+        Some("") | None => "text/plain".to_string(),
+        Some(filetype) => format!("text/x-{}", filetype),
+    };
+    Ok(vec![text_resource(
+        file_uri(&string(&buffer, "path")),
+        string(&buffer, "text"),
+        &mime_type,
+    )])
+}
+
+/// Workspace symbols named exactly like the argument, found through the attached LSP clients
+fn symbol(argument: Option<&str>, _: &Path) -> Result<Vec<ContentBlock>, String> {
+    let name = argument.ok_or("Missing a symbol name, e.g. @symbol:Name")?;
+    let links = symbol_links(&lua_json(SYMBOLS, json!({ "name": name }))?);
+    if links.is_empty() {
+        return Err(format!("No symbol named \"{}\"", name));
+    }
+    Ok(links)
+}
+
+/// The providers Hermes ships with
+pub fn builtin() -> ContextRegistry {
+    let mut registry = ContextRegistry::new();
+    registry.register("buffer", buffer);
+    registry.register("diagnostics", diagnostics);
+    registry.register("diff", diff);
+    registry.register("quickfix", quickfix);
+    registry.register("symbol", symbol);
+    registry
+}
+
+/// A Lua function given a mention's argument and the session's working directory
+pub type ContextFunction = Function<(Option<String>, String), Object>;
+
+/// A provider registered from Lua with `hermes.register_context`
+///
+/// The function gets the mention's argument and the session's working directory, and returns prompt content like `hermes.prompt`
+/// takes: a string, a content table or a list of them, nothing adds no content.
+pub struct LuaProvider(pub ContextFunction);
+
+impl ContextProvider for LuaProvider {
+    fn provide(&self, argument: Option<&str>, cwd: &Path) -> Result<Vec<ContentBlock>, String> {
+        let result = self
+            .0
+            .call((
+                argument.map(str::to_string),
+                cwd.to_string_lossy().to_string(),
+            ))
+            .map_err(|e| e.to_string())?;
+        let items = match result.kind() {
+            ObjectKind::Nil => Vec::new(),
+            ObjectKind::Array => Array::from_object(result)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .collect(),
+            _ => vec![result],
+        };
+        prompt::content_blocks(items).map_err(|e| e.to_string())
+    }
+}
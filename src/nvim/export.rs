@@ -0,0 +1,178 @@
+//! Renders a session's recorded history for `hermes.export`
+//!
+//! The entries hold the autocommand payloads, so this reads the shapes produced by [`super::parse`].
+use crate::apc::history::HistoryEntry;
+use agent_client_protocol::SessionId;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 2] = ["markdown", "json"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "markdown" => Some(Format::Markdown),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+pub fn export(session_id: &SessionId, entries: &[HistoryEntry], format: Format) -> String {
+    match format {
+        Format::Markdown => markdown(session_id, entries),
+        Format::Json => json(entries),
+    }
+}
+
+/// The entries as a pretty printed JSON array
+pub fn json(entries: &[HistoryEntry]) -> String {
+    serde_json::to_string_pretty(entries).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Formats a Unix timestamp as a UTC date and time, e.g. `2026-10-19 08:30:00 UTC`
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86_400, timestamp % 86_400);
+    // Howard Hinnant's days to civil date conversion
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn str<'a>(data: &'a Value, key: &str) -> &'a str {
+    data[key].as_str().unwrap_or_default()
+}
+
+/// The tool call state after every update recorded for it
+fn merged_tool_call(entries: &[HistoryEntry], id: &str) -> Map<String, Value> {
+    let mut merged = Map::new();
+    for entry in entries.iter().filter(|entry| {
+        matches!(
+            entry.event.as_str(),
+            "AgentToolCall" | "AgentToolCallUpdate"
+        ) && entry.data["id"] == id
+    }) {
+        if let Value::Object(fields) = &entry.data {
+            merged.extend(fields.clone());
+        }
+    }
+    merged
+}
+
+fn tool_call(out: &mut String, entries: &[HistoryEntry], data: &Value) {
+    let tool_call = Value::Object(merged_tool_call(entries, str(data, "id")));
+    out.push_str(&format!("### Tool call: {}\n\n", str(&tool_call, "title")));
+    out.push_str(&format!(
+        "- Kind: {}\n- Status: {}\n",
+        str(&tool_call, "kind"),
+        str(&tool_call, "status")
+    ));
+    for location in tool_call["locations"].as_array().into_iter().flatten() {
+        match location["line"].as_u64() {
+            Some(line) => {
+                out.push_str(&format!("- Location: {}:{}\n", str(location, "path"), line))
+            }
+            None => out.push_str(&format!("- Location: {}\n", str(location, "path"))),
+        }
+    }
+    out.push('\n');
+}
+
+fn plan(out: &mut String, data: &Value) {
+    out.push_str("### Plan\n\n");
+    for entry in data["entries"].as_array().into_iter().flatten() {
+        let status = str(entry, "status");
+        if status == "completed" {
+            out.push_str(&format!("- [x] {}\n", str(entry, "content")));
+        } else {
+            out.push_str(&format!("- [ ] {} ({})\n", str(entry, "content"), status));
+        }
+    }
+    out.push('\n');
+}
+
+fn permission(out: &mut String, data: &Value) {
+    let tool_call = &data["toolCall"];
+    let target = match tool_call["title"].as_str() {
+        Some(title) => title,
+        None => str(tool_call, "id"),
+    };
+    match str(data, "outcome") {
+        "selected" => out.push_str(&format!(
+            "**Permission:** {} ({}) for {}\n\n",
+            str(data, "name"),
+            str(data, "kind"),
+            target
+        )),
+        _ => out.push_str(&format!("**Permission:** cancelled for {}\n\n", target)),
+    }
+}
+
+/// A readable write-up of the session, tool calls are shown once with their final state
+pub fn markdown(session_id: &SessionId, entries: &[HistoryEntry]) -> String {
+    let mut out = format!("# Session {}\n\n", session_id);
+    for entry in entries {
+        let data = &entry.data;
+        match entry.event.as_str() {
+            "SessionCreated" | "SessionLoaded" => {
+                out.push_str(&format!(
+                    "- Agent: {} ({})\n- Directory: {}\n- {}: {}\n\n",
+                    str(data, "agent"),
+                    str(data, "connection"),
+                    str(data, "cwd"),
+                    if entry.event == "SessionCreated" {
+                        "Started"
+                    } else {
+                        "Loaded"
+                    },
+                    format_timestamp(entry.timestamp)
+                ));
+            }
+            "UserMessageComplete" => {
+                out.push_str(&format!("## User\n\n{}\n\n", str(data, "text")));
+            }
+            "AgentMessageComplete" => {
+                out.push_str(&format!("## Agent\n\n{}\n\n", str(data, "text")));
+            }
+            "AgentThoughtComplete" => {
+                let quoted: Vec<String> = str(data, "text")
+                    .lines()
+                    .map(|line| format!("> {}", line).trim_end().to_string())
+                    .collect();
+                out.push_str(&format!("### Thought\n\n{}\n\n", quoted.join("\n")));
+            }
+            "AgentToolCall" => tool_call(&mut out, entries, data),
+            "AgentPlan" => plan(&mut out, data),
+            "PermissionDecided" => permission(&mut out, data),
+            "PromptFinished" => match data["error"].as_str() {
+                Some(error) => out.push_str(&format!("_Turn failed: {}_\n\n", error)),
+                None => out.push_str(&format!("_Turn ended: {}_\n\n", str(data, "stopReason"))),
+            },
+            _ => {}
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
@@ -0,0 +1,439 @@
+//! Environment checks shown by `:checkhealth hermes`
+use crate::{
+    apc::{
+        connection::{AgentDefinition, Assistant, ConnectionId, Protocol},
+        status::AgentStatus,
+    },
+    nvim::{GROUP, PluginState, mcp},
+};
+use agent_client_protocol::{InitializeResponse, McpServer, ProtocolVersion};
+use nvim_oxi::{Array, Object, api, lua::Error};
+use std::{
+    ffi::OsStr,
+    net::{TcpStream, ToSocketAddrs},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// The Neovim version the `neovim-0-10` feature of nvim-oxi builds against
+pub const NEOVIM_VERSION: &str = "0.10";
+
+/// How long a socket or URL gets to accept a connection
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Commands that run the script given as their first argument rather than being the agent
+const INTERPRETERS: [&str; 5] = ["node", "bun", "deno", "python", "python3"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Ok,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn function(self) -> &'static str {
+        match self {
+            Level::Ok => "ok",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub level: Level,
+    pub message: String,
+    pub advice: Vec<String>,
+}
+
+impl Report {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self::new(Level::Ok, message, Vec::new())
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(Level::Info, message, Vec::new())
+    }
+
+    pub fn new(level: Level, message: impl Into<String>, advice: Vec<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            advice,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub reports: Vec<Report>,
+}
+
+/// Resolves a program the way the agent would be spawned, paths are checked as they are and
+/// bare names are looked up in `path`
+pub fn find_executable(program: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    if program.contains(std::path::MAIN_SEPARATOR) {
+        let program = PathBuf::from(program);
+        return is_executable(&program).then_some(program);
+    }
+    std::env::split_paths(path?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Checks the programs an agent needs resolve, a script run by an interpreter such as `node` is
+/// checked as well
+///
+/// Missing programs are errors for agents the user configured and warnings for the built-in ones,
+/// which are only needed when used.
+pub fn agent_reports(
+    agent: &Assistant,
+    definition: &AgentDefinition,
+    configured: bool,
+    path: Option<&OsStr>,
+) -> Vec<Report> {
+    let missing = if configured {
+        Level::Error
+    } else {
+        Level::Warn
+    };
+    let advice = vec![format!(
+        "Install it or set agents.{}.command in hermes.setup()",
+        agent
+    )];
+    let check = |program: &str| match find_executable(program, path) {
+        Some(found) => Report::ok(format!(
+            "{}: `{}` found at {}",
+            agent,
+            program,
+            found.display()
+        )),
+        None => Report::new(
+            missing,
+            format!("{}: `{}` not found on PATH", agent, program),
+            advice.clone(),
+        ),
+    };
+
+    let mut reports = vec![check(&definition.command)];
+    let interpreter = Path::new(&definition.command)
+        .file_name()
+        .and_then(OsStr::to_str)
+        .is_some_and(|name| INTERPRETERS.contains(&name));
+    if let Some(script) = definition
+        .args
+        .iter()
+        .find(|arg| !arg.starts_with('-'))
+        .filter(|_| interpreter)
+    {
+        reports.push(if Path::new(script).is_file() {
+            Report::ok(format!("{}: `{}` found", agent, script))
+        } else {
+            check(script)
+        });
+    }
+    reports
+}
+
+/// Reports the protocol version negotiated on a connection
+pub fn protocol_report(connection: &ConnectionId, response: &InitializeResponse) -> Report {
+    let name = response
+        .agent_info
+        .as_ref()
+        .map(|info| format!(" ({} {})", info.name, info.version))
+        .unwrap_or_default();
+    if response.protocol_version == ProtocolVersion::LATEST {
+        Report::ok(format!(
+            "{}{}: protocol version {}",
+            connection, name, response.protocol_version
+        ))
+    } else {
+        Report::new(
+            Level::Warn,
+            format!(
+                "{}{}: negotiated protocol version {}, Hermes speaks {}",
+                connection,
+                name,
+                response.protocol_version,
+                ProtocolVersion::LATEST
+            ),
+            vec!["Update the agent or Hermes so both speak the same version".to_string()],
+        )
+    }
+}
+
+/// Reports a connection asked to use a protocol other than stdio, which Hermes can't launch
+/// agents over
+pub fn transport_report(connection: &ConnectionId, status: &AgentStatus) -> Option<Report> {
+    (status.protocol != Protocol::Stdio).then(|| {
+        Report::new(
+            Level::Error,
+            format!(
+                "{}: {} connections are not supported",
+                connection, status.protocol
+            ),
+            vec!["Connect with protocol = \"stdio\", agents are launched over stdio".to_string()],
+        )
+    })
+}
+
+/// Connects on another thread, giving up after `timeout`
+///
+/// Neither resolving a host nor connecting to a Unix socket can be given a timeout directly.
+fn connect_within(
+    timeout: Duration,
+    connect: impl FnOnce() -> std::io::Result<()> + Send + 'static,
+) -> Result<(), String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(connect());
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("no answer within {}s", timeout.as_secs_f32())),
+    }
+}
+
+/// The `host:port` a URL points at, the port defaults to the scheme's
+pub fn url_address(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if host.is_empty() {
+        return None;
+    }
+    // A colon after an IPv6 address's closing bracket, or in a name, starts the port
+    if host
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| !port.contains(']'))
+    {
+        return Some(host.to_string());
+    }
+    let port = match scheme {
+        "https" => 443,
+        "http" => 80,
+        _ => return None,
+    };
+    Some(format!("{}:{}", host, port))
+}
+
+/// Checks a Unix socket accepts connections
+pub fn socket_report(name: &str, socket: &Path, timeout: Duration) -> Report {
+    let path = socket.to_path_buf();
+    match connect_within(timeout, move || UnixStream::connect(path).map(|_| ())) {
+        Ok(()) => Report::ok(format!(
+            "{}: {} accepts connections",
+            name,
+            socket.display()
+        )),
+        Err(e) => Report::new(
+            Level::Error,
+            format!("{}: can't connect to {}: {}", name, socket.display(), e),
+            vec!["Restart Neovim to listen on the socket again".to_string()],
+        ),
+    }
+}
+
+/// Checks the server behind a URL accepts connections
+pub fn url_report(name: &str, url: &str, timeout: Duration) -> Report {
+    let Some(address) = url_address(url) else {
+        return Report::new(
+            Level::Error,
+            format!("{}: \"{}\" is not an http or https URL", name, url),
+            vec![format!(
+                "Fix the url of mcp_servers.{} in hermes.setup()",
+                name
+            )],
+        );
+    };
+    let connected = connect_within(timeout, move || {
+        let addresses = address.to_socket_addrs()?;
+        let mut failed = std::io::Error::other("the host has no addresses");
+        for address in addresses {
+            match TcpStream::connect_timeout(&address, timeout) {
+                Ok(_) => return Ok(()),
+                Err(e) => failed = e,
+            }
+        }
+        Err(failed)
+    });
+    match connected {
+        Ok(()) => Report::ok(format!("{}: {} is reachable", name, url)),
+        Err(e) => Report::new(
+            Level::Error,
+            format!("{}: can't connect to {}: {}", name, url, e),
+            vec![format!("Start the server or fix mcp_servers.{}", name)],
+        ),
+    }
+}
+
+/// Checks the editor's MCP socket and the MCP servers reached over the network
+fn reachability_reports(state: &PluginState) -> Vec<Report> {
+    let mut reports: Vec<Report> = mcp::running()
+        .map(|server| socket_report("editor MCP server", server.socket(), CONNECT_TIMEOUT))
+        .into_iter()
+        .collect();
+    reports.extend(
+        state
+            .connection
+            .mcp_servers()
+            .iter()
+            .filter_map(|server| match server {
+                McpServer::Http(http) => Some(url_report(&http.name, &http.url, CONNECT_TIMEOUT)),
+                McpServer::Sse(sse) => Some(url_report(&sse.name, &sse.url, CONNECT_TIMEOUT)),
+                _ => None,
+            }),
+    );
+    if reports.is_empty() {
+        reports.push(Report::info("No sockets or URLs configured"));
+    }
+    reports
+}
+
+pub fn neovim_report(supported: bool) -> Report {
+    if supported {
+        Report::ok(format!("Neovim {} or newer", NEOVIM_VERSION))
+    } else {
+        Report::new(
+            Level::Error,
+            format!("Hermes was built for Neovim {}", NEOVIM_VERSION),
+            vec![format!("Upgrade to Neovim {} or newer", NEOVIM_VERSION)],
+        )
+    }
+}
+
+pub fn augroup_report(exists: bool) -> Report {
+    if exists {
+        Report::ok(format!("Autocommand group \"{}\" exists", GROUP))
+    } else {
+        Report::new(
+            Level::Error,
+            format!("Autocommand group \"{}\" is missing", GROUP),
+            vec![
+                "It is created when Hermes is loaded, check nothing deleted it with :augroup!"
+                    .to_string(),
+            ],
+        )
+    }
+}
+
+fn vim_flag(function: &str, argument: &str) -> Result<bool, Error> {
+    api::call_function::<_, i64>(function, (argument,))
+        .map(|result| result == 1)
+        .map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// Runs every check against the current state
+pub(crate) fn sections(state: &PluginState) -> Result<Vec<Section>, Error> {
+    let neovim = vec![
+        neovim_report(vim_flag("has", &format!("nvim-{}", NEOVIM_VERSION))?),
+        augroup_report(vim_flag("exists", &format!("#{}", GROUP))?),
+    ];
+
+    let path = std::env::var_os("PATH");
+    let agents = state
+        .connection
+        .known_agents()
+        .iter()
+        .flat_map(|agent| match state.connection.agent_definition(agent) {
+            Some(definition) => agent_reports(
+                agent,
+                &definition,
+                state.connection.is_configured(agent),
+                path.as_deref(),
+            ),
+            None => vec![Report::new(
+                Level::Error,
+                format!("{}: no command configured", agent),
+                vec![format!("Set agents.{}.command in hermes.setup()", agent)],
+            )],
+        })
+        .collect();
+
+    let tried = state
+        .status()
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .connections();
+    let mut connections: Vec<Report> = tried
+        .iter()
+        .filter_map(|(id, status)| transport_report(id, status))
+        .collect();
+    let connected = state.connection.connections();
+    if connected.is_empty() {
+        connections.push(Report::info("No agents connected"));
+    }
+    connections.extend(connected.iter().filter_map(|connection| {
+        state
+            .connection
+            .initialize_response(&connection.id)
+            .map(|response| protocol_report(&connection.id, response))
+    }));
+
+    Ok(vec![
+        Section {
+            name: "Neovim".to_string(),
+            reports: neovim,
+        },
+        Section {
+            name: "Agents".to_string(),
+            reports: agents,
+        },
+        Section {
+            name: "Sockets and URLs".to_string(),
+            reports: reachability_reports(state),
+        },
+        Section {
+            name: "Connections".to_string(),
+            reports: connections,
+        },
+    ])
+}
+
+fn health_call(expression: &str, argument: Object) -> Result<(), Error> {
+    api::call_function::<_, Object>("luaeval", (expression, argument))
+        .map(|_| ())
+        .map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// Writes the sections to the `:checkhealth` buffer through `vim.health`
+pub fn render(sections: Vec<Section>) -> Result<(), Error> {
+    for section in sections {
+        health_call("vim.health.start(_A)", Object::from(section.name))?;
+        for report in section.reports {
+            let expression = format!("vim.health.{}(_A[1], _A[2])", report.level.function());
+            let advice = match report.level {
+                Level::Warn | Level::Error if !report.advice.is_empty() => {
+                    Object::from(Array::from_iter(report.advice))
+                }
+                _ => Object::nil(),
+            };
+            health_call(
+                &expression,
+                Object::from(Array::from_iter([Object::from(report.message), advice])),
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,517 @@
+//! The MCP server Hermes hosts so agents can query the editor's state
+//!
+//! Requests arrive as JSON-RPC lines on a Unix socket [`Server`] listens on, agents reach it
+//! through `lua/hermes/mcp_bridge.lua`, which Neovim runs as a stdio MCP server.
+use agent_client_protocol::{McpServer, McpServerStdio};
+use nvim_oxi::{Array, Object, api, lua::Error};
+use serde_json::{Value, json};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Write},
+    os::{
+        fd::IntoRawFd,
+        unix::net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, mpsc},
+};
+use tokio::sync::Notify;
+
+/// The name the server is given to agents
+pub const SERVER_NAME: &str = "neovim";
+
+/// The MCP protocol revision the server speaks
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A position in a file, lines and columns start at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub path: Option<String>,
+    pub line: u64,
+    pub column: u64,
+}
+
+/// The editor state exposed through the server's tools, each returns the JSON sent to the agent
+pub trait Editor {
+    /// Diagnostics of a file, or of every loaded buffer without a path
+    fn diagnostics(&self, path: Option<&str>) -> Result<Value, String>;
+    fn definitions(&self, position: &Position) -> Result<Value, String>;
+    fn references(&self, position: &Position) -> Result<Value, String>;
+    fn buffers(&self) -> Result<Value, String>;
+    fn quickfix(&self) -> Result<Value, String>;
+    /// The last visual selection
+    fn selection(&self) -> Result<Value, String>;
+    fn cursor(&self) -> Result<Value, String>;
+}
+
+fn position_schema(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": {
+            "path": { "type": "string", "description": "File to look in, defaults to the current buffer" },
+            "line": { "type": "integer", "minimum": 1, "description": "1-based line" },
+            "column": { "type": "integer", "minimum": 1, "description": "1-based column" }
+        },
+        "required": ["line", "column"]
+    })
+}
+
+fn no_arguments() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// The tools the server offers, as returned by `tools/list`
+pub fn tools() -> Vec<Value> {
+    let tool = |name: &str, description: &str, schema: Value| json!({ "name": name, "description": description, "inputSchema": schema });
+    vec![
+        tool(
+            "diagnostics",
+            "Errors, warnings and hints reported by LSP servers and linters",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Only report this file" }
+                }
+            }),
+        ),
+        tool(
+            "lsp_definitions",
+            "Where the symbol at a position is defined, according to the attached LSP servers",
+            position_schema("Position of the symbol"),
+        ),
+        tool(
+            "lsp_references",
+            "Every reference to the symbol at a position, according to the attached LSP servers",
+            position_schema("Position of the symbol"),
+        ),
+        tool(
+            "buffers",
+            "Files open in the editor and whether they have unsaved changes",
+            no_arguments(),
+        ),
+        tool("quickfix", "Entries of the quickfix list", no_arguments()),
+        tool(
+            "selection",
+            "The text the user last selected in visual mode and where it is",
+            no_arguments(),
+        ),
+        tool(
+            "cursor",
+            "The file and position of the cursor",
+            no_arguments(),
+        ),
+    ]
+}
+
+fn position(arguments: &Value) -> Result<Position, String> {
+    let number = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(Value::as_u64)
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("\"{}\" must be a positive integer", key))
+    };
+    Ok(Position {
+        path: arguments
+            .get("path")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        line: number("line")?,
+        column: number("column")?,
+    })
+}
+
+/// Runs a tool, `Err` holds the JSON-RPC error for unknown tools and invalid arguments while tool
+/// failures are reported to the agent in the result
+pub fn call_tool(
+    editor: &impl Editor,
+    name: &str,
+    arguments: &Value,
+) -> Result<Value, (i64, String)> {
+    let invalid = |message: String| (INVALID_PARAMS, message);
+    let result = match name {
+        "diagnostics" => editor.diagnostics(arguments.get("path").and_then(Value::as_str)),
+        "lsp_definitions" => editor.definitions(&position(arguments).map_err(invalid)?),
+        "lsp_references" => editor.references(&position(arguments).map_err(invalid)?),
+        "buffers" => editor.buffers(),
+        "quickfix" => editor.quickfix(),
+        "selection" => editor.selection(),
+        "cursor" => editor.cursor(),
+        _ => return Err(invalid(format!("Unknown tool \"{}\"", name))),
+    };
+    Ok(match result {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": value.to_string() }],
+            "isError": false
+        }),
+        Err(message) => json!({
+            "content": [{ "type": "text", "text": message }],
+            "isError": true
+        }),
+    })
+}
+
+/// Answers a JSON-RPC message, notifications get no answer
+pub fn handle(editor: &impl Editor, message: &Value) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") }
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => match params.get("name").and_then(Value::as_str) {
+            Some(name) => call_tool(
+                editor,
+                name,
+                params.get("arguments").unwrap_or(&Value::Null),
+            ),
+            None => Err((INVALID_PARAMS, "Missing the tool \"name\"".to_string())),
+        },
+        _ => Err((METHOD_NOT_FOUND, format!("Unknown method \"{}\"", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message }
+        }),
+    })
+}
+
+/// Handles one line read from the socket, returning the line to answer with
+pub fn handle_line(editor: &impl Editor, line: &str) -> Option<String> {
+    match serde_json::from_str::<Value>(line) {
+        Ok(message) => handle(editor, &message).map(|response| response.to_string()),
+        Err(e) => Some(
+            json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": -32700, "message": e.to_string() }
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// A request read from the socket and where its answer goes
+type Request = (String, mpsc::Sender<Option<String>>);
+
+#[derive(Default)]
+struct Pending {
+    requests: Mutex<VecDeque<Request>>,
+    ready: Notify,
+}
+
+/// Listens on the server's socket
+///
+/// Connections are read on their own threads while requests are answered on the main thread,
+/// where the editor's state can be accessed: by the task [`Server::serve`] returns while Hermes
+/// waits on an agent, which keeps Neovim busy, and through [`Server::answer`] otherwise.
+#[derive(Clone)]
+pub struct Server {
+    pending: Arc<Pending>,
+    socket: PathBuf,
+}
+
+impl Server {
+    /// Listens on `socket`, replacing a stale socket file
+    ///
+    /// The returned stream receives a byte for every request, letting an idle Neovim know it
+    /// has requests to answer.
+    pub fn bind(socket: &Path) -> std::io::Result<(Self, UnixStream)> {
+        let _ = std::fs::remove_file(socket);
+        let listener = UnixListener::bind(socket)?;
+        let (wake, woken) = UnixStream::pair()?;
+        // A full buffer already has Neovim looking, the byte isn't needed
+        wake.set_nonblocking(true)?;
+        let (server, wake) = (
+            Self {
+                pending: Arc::new(Pending::default()),
+                socket: socket.to_path_buf(),
+            },
+            Arc::new(wake),
+        );
+        let pending = server.pending.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (pending, wake) = (pending.clone(), wake.clone());
+                std::thread::spawn(move || forward(stream, &pending, &wake));
+            }
+        });
+        Ok((server, woken))
+    }
+
+    /// The socket the server listens on
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Answers the requests waiting, must be called on the main thread
+    pub fn answer(&self, editor: &impl Editor) {
+        loop {
+            let request = match self.pending.requests.lock() {
+                Ok(mut requests) => requests.pop_front(),
+                Err(_) => None,
+            };
+            let Some((line, reply)) = request else {
+                break;
+            };
+            let _ = reply.send(handle_line(editor, &line));
+        }
+    }
+
+    /// Answers requests as they arrive, as long as the runtime the task is spawned on runs
+    pub async fn serve(self, editor: impl Editor) {
+        loop {
+            self.pending.ready.notified().await;
+            self.answer(&editor);
+        }
+    }
+}
+
+/// Queues the requests read from a connection one at a time and writes back their answers
+fn forward(stream: UnixStream, pending: &Pending, wake: &UnixStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let (reply, answer) = mpsc::channel();
+        pending
+            .requests
+            .lock()
+            .map_err(|e| std::io::Error::other(e.to_string()))?
+            .push_back((line?, reply));
+        pending.ready.notify_one();
+        let _ = (&*wake).write(&[0]);
+        if let Ok(Some(response)) = answer.recv() {
+            writeln!(writer, "{}", response)?;
+        }
+    }
+    Ok(())
+}
+
+static SERVER: OnceLock<Server> = OnceLock::new();
+
+/// The server listening for this Neovim, once started
+pub fn running() -> Option<Server> {
+    SERVER.get().cloned()
+}
+
+const DIAGNOSTICS: &str = r#"(function()
+    local bufnr = _A.path and vim.fn.bufnr(_A.path) or nil
+    if bufnr == -1 then
+        return {}
+    end
+    return vim.tbl_map(function(d)
+        return {
+            path = vim.api.nvim_buf_get_name(d.bufnr),
+            line = d.lnum + 1,
+            column = d.col + 1,
+            severity = vim.diagnostic.severity[d.severity],
+            message = d.message,
+            source = d.source,
+        }
+    end, vim.diagnostic.get(bufnr))
+end)()"#;
+
+const LSP_LOCATIONS: &str = r#"(function()
+    local bufnr = _A.path and vim.fn.bufadd(_A.path) or vim.api.nvim_get_current_buf()
+    vim.fn.bufload(bufnr)
+    -- The cursor is placed in a hidden window, the user's windows keep theirs
+    local win = vim.api.nvim_open_win(bufnr, false, {
+        relative = "editor", row = 0, col = 0, width = 1, height = 1, hide = true, noautocmd = true,
+    })
+    local ok, locations = pcall(function()
+        local row = math.min(_A.line, vim.api.nvim_buf_line_count(bufnr))
+        local line = vim.api.nvim_buf_get_lines(bufnr, row - 1, row, false)[1] or ""
+        vim.api.nvim_win_set_cursor(win, { row, math.min(_A.column - 1, #line) })
+        local locations = {}
+        for _, client in ipairs(vim.lsp.get_clients({ bufnr = bufnr, method = _A.method })) do
+            -- Each client counts characters in the encoding it negotiated
+            local params = vim.lsp.util.make_position_params(win, client.offset_encoding)
+            params.context = { includeDeclaration = true }
+            local response = client.request_sync(_A.method, params, 2000, bufnr) or {}
+            local result = response.result or {}
+            if result.uri or result.targetUri then
+                result = { result }
+            end
+            for _, location in ipairs(result) do
+                local range = location.range or location.targetSelectionRange
+                table.insert(locations, {
+                    path = vim.uri_to_fname(location.uri or location.targetUri),
+                    line = range.start.line + 1,
+                    column = range.start.character + 1,
+                })
+            end
+        end
+        return locations
+    end)
+    vim.api.nvim_win_close(win, true)
+    if not ok then
+        error(locations)
+    end
+    return locations
+end)()"#;
+
+const QUICKFIX: &str = r#"vim.tbl_map(function(item)
+    return {
+        path = item.bufnr > 0 and vim.api.nvim_buf_get_name(item.bufnr) or nil,
+        line = item.lnum,
+        column = item.col,
+        type = item.type,
+        text = item.text,
+    }
+end, vim.fn.getqflist())"#;
+
+const SELECTION: &str = r#"(function()
+    local first, last = vim.fn.getpos("'<"), vim.fn.getpos("'>")
+    if first[2] == 0 then
+        return vim.NIL
+    end
+    local mode = vim.fn.visualmode()
+    return {
+        path = vim.api.nvim_buf_get_name(first[1]),
+        start = { line = first[2], column = first[3] },
+        ["end"] = { line = last[2], column = math.min(last[3], vim.v.maxcol) },
+        text = table.concat(vim.fn.getregion(first, last, { type = mode ~= "" and mode or "v" }), "\n"),
+    }
+end)()"#;
+
+const BUFFERS: &str = r#"vim.tbl_map(function(info)
+    return { bufnr = info.bufnr, path = info.name, modified = info.changed == 1 }
+end, vim.fn.getbufinfo({ buflisted = 1 }))"#;
+
+const CURSOR: &str = r#"(function()
+    local cursor = vim.api.nvim_win_get_cursor(0)
+    return {
+        path = vim.api.nvim_buf_get_name(0),
+        line = cursor[1],
+        column = cursor[2] + 1,
+    }
+end)()"#;
+
+/// Evaluates a Lua expression and converts its result to JSON through `vim.json`
+pub(crate) fn lua_json(expression: &str, argument: Value) -> Result<Value, String> {
+    let encoded: String = api::call_function(
+        "luaeval",
+        (
+            format!("vim.json.encode({})", expression),
+            super::parse::json_to_object(argument),
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&encoded).map_err(|e| e.to_string())?;
+    // `vim.json` can't tell an empty list from an empty table
+    Ok(match value {
+        Value::Object(map) if map.is_empty() => Value::Array(Vec::new()),
+        value => value,
+    })
+}
+
+fn position_argument(position: &Position, method: &str) -> Value {
+    json!({
+        "path": position.path,
+        "line": position.line,
+        "column": position.column,
+        "method": method,
+    })
+}
+
+/// Answers the tools from the running Neovim
+pub struct NeovimEditor;
+
+impl Editor for NeovimEditor {
+    fn diagnostics(&self, path: Option<&str>) -> Result<Value, String> {
+        lua_json(DIAGNOSTICS, json!({ "path": path }))
+    }
+
+    fn definitions(&self, position: &Position) -> Result<Value, String> {
+        lua_json(
+            LSP_LOCATIONS,
+            position_argument(position, "textDocument/definition"),
+        )
+    }
+
+    fn references(&self, position: &Position) -> Result<Value, String> {
+        lua_json(
+            LSP_LOCATIONS,
+            position_argument(position, "textDocument/references"),
+        )
+    }
+
+    fn buffers(&self) -> Result<Value, String> {
+        lua_json(BUFFERS, Value::Null)
+    }
+
+    fn quickfix(&self) -> Result<Value, String> {
+        lua_json(QUICKFIX, Value::Null)
+    }
+
+    fn selection(&self) -> Result<Value, String> {
+        lua_json(SELECTION, Value::Null)
+    }
+
+    fn cursor(&self) -> Result<Value, String> {
+        lua_json(CURSOR, Value::Null)
+    }
+}
+
+/// The stdio MCP server agents are given, it runs the bridge script with Neovim itself
+pub fn stdio_server(nvim: PathBuf, bridge: PathBuf, socket: PathBuf) -> McpServer {
+    McpServer::Stdio(McpServerStdio::new(SERVER_NAME, nvim).args(vec![
+        "--clean".to_string(),
+        "--headless".to_string(),
+        "-l".to_string(),
+        bridge.to_string_lossy().to_string(),
+        socket.to_string_lossy().to_string(),
+    ]))
+}
+
+fn runtime_error(e: impl ToString) -> Error {
+    Error::RuntimeError(e.to_string())
+}
+
+/// Starts listening on the server's socket, once per Neovim, and returns the server to give agents
+///
+/// Connections made before sessions get the server are answered once a task from
+/// [`Server::serve`] runs next to them.
+pub fn start() -> Result<McpServer, Error> {
+    let run: String = api::call_function("stdpath", ("run",)).map_err(runtime_error)?;
+    let socket = PathBuf::from(run).join(format!("hermes-{}.sock", std::process::id()));
+    let bridge = api::get_runtime_file("lua/hermes/mcp_bridge.lua", false)
+        .map_err(runtime_error)?
+        .next()
+        .ok_or_else(|| {
+            Error::RuntimeError(
+                "lua/hermes/mcp_bridge.lua is not on the runtimepath, keep the lua directory next to the library"
+                    .to_string(),
+            )
+        })?;
+    if SERVER.get().is_none() {
+        let (server, woken) = Server::bind(&socket).map_err(|e| {
+            Error::RuntimeError(format!("Unable to listen on {}: {}", socket.display(), e))
+        })?;
+        api::call_function::<_, Object>(
+            "luaeval",
+            (
+                "require('hermes.mcp').watch(_A[1], _A[2])",
+                Array::from_iter([
+                    Object::from(woken.into_raw_fd() as i64),
+                    Object::from(socket.to_string_lossy().to_string()),
+                ]),
+            ),
+        )
+        .map_err(runtime_error)?;
+        let _ = SERVER.set(server);
+    }
+    let nvim: String = api::get_vvar("progpath").map_err(runtime_error)?;
+    Ok(stdio_server(PathBuf::from(nvim), bridge, socket))
+}
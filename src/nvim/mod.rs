@@ -1,26 +1,56 @@
+pub mod buffers;
+pub mod command;
+pub mod context;
+pub mod export;
+pub mod health;
+pub mod mcp;
 pub mod parse;
 pub mod producer;
+pub mod project;
+pub mod prompt;
+pub mod setup;
 
 use crate::{
     apc::{
         self,
         client::{ApcClient, ClientConfig},
-        connection::{Assistant, ConnectionDetails, ConnectionManager, Protocol},
+        commands, config_options,
+        connection::{
+            Assistant, ConnectionDetails, ConnectionHandle, ConnectionId, ConnectionInfo,
+            ConnectionManager, Protocol,
+        },
+        context::ContextRegistry,
+        history::{History, SessionRecord},
+        status::StatusTracker,
     },
-    nvim::producer::EventHandler,
+    nvim::{producer::EventHandler, setup::Setup},
+};
+use agent_client_protocol::{
+    AuthMethodId, ContentBlock, McpServer, PromptResponse, SessionConfigId, SessionConfigOption,
+    SessionConfigValueId, SessionId, SessionModeId, SessionModeState, TextContent,
 };
 use nvim_oxi::{
-    Dictionary, Function,
+    Array, Dictionary, Function, Object, ObjectKind,
     api::opts::CreateAugroupOpts,
+    conversion::FromObject,
     lua::{Error, Poppable, Pushable, ffi::State},
 };
 use std::{
-    rc::Rc,
+    path::{Path, PathBuf},
+    rc::{Rc, Weak},
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 const GROUP: &str = "hermes";
 
+/// Where session transcripts are recorded, below Neovim's state directory
+fn history_dir() -> Result<PathBuf, Error> {
+    let state: String = nvim_oxi::api::call_function("stdpath", ("state",))
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    Ok(PathBuf::from(state).join("hermes").join("sessions"))
+}
+
 impl From<apc::error::Error> for Error {
     fn from(e: apc::error::Error) -> Self {
         Error::RuntimeError(e.to_string())
@@ -35,17 +65,19 @@ impl From<apc::error::Error> for Error {
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// use hermes::nvim::PluginState;
 ///
 /// // Create with default configuration
-/// let state = PluginState::new();
-///
-/// // Access the client
-/// let client = state.client();
+/// let state = PluginState::new()?;
+/// # Ok::<(), nvim_oxi::lua::Error>(())
 /// ```
 pub struct PluginState {
     connection: ConnectionManager<EventHandler>,
+    handler: EventHandler,
+    status: Arc<Mutex<StatusTracker>>,
+    current_session: Option<SessionId>,
+    context: ContextRegistry,
 }
 
 impl PluginState {
@@ -54,14 +86,170 @@ impl PluginState {
     }
 
     pub fn with_config(config: ClientConfig) -> Result<Self, Error> {
-        let client = Arc::new(ApcClient::new(config, EventHandler::new(GROUP.to_string())));
+        Self::with_setup(Setup {
+            client: config,
+            ..Setup::default()
+        })
+    }
+
+    pub fn with_setup(setup: Setup) -> Result<Self, Error> {
+        let handler =
+            EventHandler::new(GROUP.to_string()).with_history(History::new(history_dir()?));
+        let status = Arc::new(Mutex::new(StatusTracker::new()));
 
         nvim_oxi::api::create_augroup(GROUP, &CreateAugroupOpts::default()).unwrap();
 
         Ok(Self {
-            connection: ConnectionManager::new(client).map_err(Error::from)?,
+            connection: Self::connection_manager(setup, &handler, &status)?,
+            handler,
+            status,
+            current_session: None,
+            context: context::builtin(),
         })
     }
+
+    fn connection_manager(
+        setup: Setup,
+        handler: &EventHandler,
+        status: &Arc<Mutex<StatusTracker>>,
+    ) -> Result<ConnectionManager<EventHandler>, Error> {
+        let observer = handler.clone();
+        let client = Arc::new(
+            ApcClient::new(setup.client, handler.clone())
+                .with_permission_observer(Arc::new(move |request, response| {
+                    observer.permission_decided(request, response)
+                }))
+                .with_permissions(setup.permissions)
+                .with_fs_roots(setup.fs_roots),
+        );
+        let mut connection = ConnectionManager::new(client)
+            .map_err(Error::from)?
+            .with_status(status.clone())
+            .with_mcp_servers(setup.mcp_servers);
+        for (agent, definition) in setup.agents {
+            connection.define_agent(agent, definition);
+        }
+        if let Some(server) = mcp::running() {
+            connection.spawn_local(server.serve(mcp::NeovimEditor));
+        }
+        Ok(connection)
+    }
+
+    /// Applies a new configuration
+    ///
+    /// Existing connections are closed since agents only learn about the client's capabilities
+    /// when connecting. Returns them with the sessions they had, `AgentDisconnected` is left to
+    /// the caller so it fires once the state is unlocked.
+    pub fn configure(
+        &mut self,
+        setup: Setup,
+    ) -> Result<Vec<(ConnectionInfo, Vec<SessionId>)>, Error> {
+        let closed = self
+            .connection
+            .connections()
+            .into_iter()
+            .map(|connection| {
+                let sessions = self.close(&connection)?;
+                Ok((connection, sessions))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.connection = Self::connection_manager(setup, &self.handler, &self.status)?;
+        self.current_session = None;
+        Ok(closed)
+    }
+
+    /// Closes a connection and forgets what the handler tracked for its sessions
+    ///
+    /// Returns the sessions that belonged to the connection.
+    fn close(&mut self, connection: &ConnectionInfo) -> Result<Vec<SessionId>, Error> {
+        let sessions = self
+            .connection
+            .disconnect(&connection.id)
+            .map_err(Error::from)?;
+        for session_id in &sessions {
+            self.handler
+                .forget_session(session_id)
+                .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        }
+        if self
+            .current_session
+            .as_ref()
+            .is_some_and(|current| sessions.contains(current))
+        {
+            self.current_session = None;
+        }
+        Ok(sessions)
+    }
+
+    /// The session most recently created or prompted, used when a command doesn't name one
+    pub fn current_session(&self) -> Option<&SessionId> {
+        self.current_session.as_ref()
+    }
+
+    pub fn handler(&self) -> &EventHandler {
+        &self.handler
+    }
+
+    /// The status of every connection Hermes tried to make
+    pub fn status(&self) -> Arc<Mutex<StatusTracker>> {
+        self.status.clone()
+    }
+
+    /// Where sessions are recorded
+    pub fn history(&self) -> Result<&History, Error> {
+        self.handler
+            .history()
+            .ok_or_else(|| Error::RuntimeError("Sessions aren't recorded".to_string()))
+    }
+
+    /// The recorded sessions, of one agent when given, the most recently used first
+    ///
+    /// Reading them needs no connection, only resuming one does.
+    pub fn recorded_sessions(&self, agent: Option<&str>) -> Result<Vec<SessionRecord>, Error> {
+        Ok(self
+            .history()?
+            .sessions()?
+            .into_iter()
+            .filter(|record| agent.is_none_or(|agent| record.agent == agent))
+            .collect())
+    }
+
+    /// The providers `@` mentions in prompts expand through
+    pub fn context(&self) -> &ContextRegistry {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut ContextRegistry {
+        &mut self.context
+    }
+
+    /// Finds a connection by id, label or agent name, the oldest connection when no target is given
+    fn resolve_connection(&self, target: Option<&str>) -> Result<ConnectionInfo, Error> {
+        let id = match target {
+            Some(target) => self
+                .connection
+                .resolve(target)
+                .ok_or_else(|| Error::RuntimeError(format!("Not connected to {}", target)))?,
+            None => self
+                .connection
+                .connections()
+                .into_iter()
+                .next()
+                .map(|connection| connection.id)
+                .ok_or_else(|| Error::RuntimeError("Not connected to any agent".to_string()))?,
+        };
+        self.connection
+            .connection_info(&id)
+            .ok_or_else(|| Error::RuntimeError(format!("Not connected to {}", id)))
+    }
+
+    /// The connection a session was created or loaded on
+    fn session_connection(&self, session_id: &SessionId) -> Result<ConnectionInfo, Error> {
+        self.connection
+            .session_connection(session_id)
+            .and_then(|id| self.connection.connection_info(&id))
+            .ok_or_else(|| Error::RuntimeError(format!("Unknown session {}", session_id)))
+    }
 }
 
 impl Default for PluginState {
@@ -74,6 +262,7 @@ impl Default for PluginState {
 pub struct ConnectionArgs {
     pub agent: Option<Assistant>,
     pub protocol: Option<Protocol>,
+    pub label: Option<String>,
 }
 
 impl From<ConnectionArgs> for ConnectionDetails {
@@ -81,6 +270,7 @@ impl From<ConnectionArgs> for ConnectionDetails {
         ConnectionDetails {
             agent: args.agent.unwrap_or_default(),
             protocol: args.protocol.unwrap_or_default(),
+            label: args.label,
         }
     }
 }
@@ -117,7 +307,13 @@ impl Poppable for ConnectionArgs {
             })
             .transpose()?;
 
-        Ok(Self { agent, protocol })
+        let label = string_arg(&table, "label")?;
+
+        Ok(Self {
+            agent,
+            protocol,
+            label,
+        })
     }
 }
 
@@ -134,6 +330,10 @@ impl Pushable for ConnectionArgs {
                 dict.insert("protocol", protocol.to_string());
             }
 
+            if let Some(label) = self.label {
+                dict.insert("label", label);
+            }
+
             dict
         });
 
@@ -142,21 +342,1053 @@ impl Pushable for ConnectionArgs {
     }
 }
 
+fn string_arg(table: &Dictionary, key: &str) -> Result<Option<String>, Error> {
+    table
+        .get(key)
+        .map(|v: &Object| {
+            String::from_object(v.clone()).map_err(|_| {
+                Error::RuntimeError(format!("Invalid input for \"{}\", must be a string", key))
+            })
+        })
+        .transpose()
+}
+
+/// The connection to use, named by `connection` or, for tables written before connections had
+/// ids, by `agent`
+fn connection_arg(table: &Dictionary) -> Result<Option<String>, Error> {
+    match string_arg(table, "connection")? {
+        Some(connection) => Ok(Some(connection)),
+        None => string_arg(table, "agent"),
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SessionArgs {
+    /// A connection id, label or agent name
+    pub connection: Option<String>,
+    pub cwd: Option<PathBuf>,
+    /// Passed along with the servers from `setup`, replacing those with the same name
+    pub mcp_servers: Vec<McpServer>,
+}
+
+impl Poppable for SessionArgs {
+    unsafe fn pop(state: *mut State) -> Result<Self, Error> {
+        let table = unsafe { Dictionary::pop(state)? };
+
+        Ok(Self {
+            connection: connection_arg(&table)?,
+            cwd: cwd_arg(&table)?,
+            mcp_servers: mcp_servers_arg(&table)?,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct LoadSessionArgs {
+    pub connection: Option<String>,
+    pub session_id: SessionId,
+    pub cwd: Option<PathBuf>,
+    pub mcp_servers: Vec<McpServer>,
+}
+
+impl Poppable for LoadSessionArgs {
+    unsafe fn pop(state: *mut State) -> Result<Self, Error> {
+        let table = unsafe { Dictionary::pop(state)? };
+        Ok(Self {
+            connection: connection_arg(&table)?,
+            session_id: SessionId::new(session_id_arg(&table)?),
+            cwd: cwd_arg(&table)?,
+            mcp_servers: mcp_servers_arg(&table)?,
+        })
+    }
+}
+
+fn session_id_arg(table: &Dictionary) -> Result<String, Error> {
+    table
+        .get("sessionId")
+        .ok_or_else(|| Error::RuntimeError("Missing \"sessionId\"".to_string()))
+        .and_then(|v: &Object| {
+            String::from_object(v.clone()).map_err(|_| {
+                Error::RuntimeError("Invalid input for \"sessionId\", must be a string".to_string())
+            })
+        })
+}
+
+fn mcp_servers_arg(table: &Dictionary) -> Result<Vec<McpServer>, Error> {
+    table
+        .get("mcp_servers")
+        .map(|v: &Object| setup::mcp_servers(v, "mcp_servers"))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+fn cwd_arg(table: &Dictionary) -> Result<Option<PathBuf>, Error> {
+    table
+        .get("cwd")
+        .map(|v: &Object| {
+            String::from_object(v.clone())
+                .map(PathBuf::from)
+                .map_err(|_| {
+                    Error::RuntimeError("Invalid input for \"cwd\", must be a string".to_string())
+                })
+        })
+        .transpose()
+}
+
+#[derive(Clone)]
+pub struct PromptArgs {
+    pub connection: Option<String>,
+    pub session_id: SessionId,
+    pub content: Vec<ContentBlock>,
+}
+
+impl Poppable for PromptArgs {
+    unsafe fn pop(state: *mut State) -> Result<Self, Error> {
+        let table = unsafe { Dictionary::pop(state)? };
+
+        let session_id = session_id_arg(&table)?;
+
+        let content = match table.get("content") {
+            Some(v) if v.kind() == ObjectKind::String => vec![v.clone()],
+            Some(v) => Vec::<Object>::from_object(v.clone()).map_err(|_| {
+                Error::RuntimeError(
+                    "Invalid input for \"content\", must be a string or a list".to_string(),
+                )
+            })?,
+            None => return Err(Error::RuntimeError("Missing \"content\"".to_string())),
+        };
+
+        Ok(Self {
+            connection: connection_arg(&table)?,
+            session_id: SessionId::new(session_id),
+            content: prompt::content_blocks(content)?,
+        })
+    }
+}
+
+/// Fires `AgentAuthRequired` when a request failed because the agent needs the user to log in
+///
+/// The plugin state must not be locked, autocommand callbacks are free to call back into Hermes.
+fn request_error(
+    state: &Mutex<PluginState>,
+    connection: &ConnectionInfo,
+    session_id: Option<&SessionId>,
+    error: apc::error::Error,
+) -> Error {
+    if let apc::error::Error::AuthRequired(_) = error {
+        let notified = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))
+            .map(|state| {
+                (
+                    state.handler().clone(),
+                    state.connection.auth_methods(&connection.id),
+                )
+            })
+            .and_then(|(handler, methods)| {
+                handler
+                    .auth_required(connection, session_id, methods)
+                    .map_err(|e| Error::RuntimeError(e.to_string()))
+            });
+        if let Err(e) = notified {
+            return e;
+        }
+    }
+    Error::from(error)
+}
+
+/// Launches an agent on a new connection, firing the connection lifecycle autocommands around it
+fn connect_agent(
+    state: &Mutex<PluginState>,
+    details: ConnectionDetails,
+) -> Result<ConnectionId, Error> {
+    let handler = state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .clone();
+    let connection = ConnectionInfo {
+        id: ConnectionId::generate(&details.agent),
+        agent: details.agent.clone(),
+        label: details.label.clone(),
+    };
+    handler
+        .agent_connecting(&connection, &details.protocol)
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    let connected = {
+        let mut state = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        state
+            .connection
+            .connect_as(connection.id.clone(), details)
+            .map(|_| {
+                state
+                    .connection
+                    .initialize_response(&connection.id)
+                    .cloned()
+            })
+    };
+    match connected {
+        Ok(response) => {
+            if let Some(response) = response {
+                handler
+                    .agent_connected(&connection, response)
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+            }
+            Ok(connection.id)
+        }
+        Err(error) => {
+            handler
+                .agent_connection_failed(&connection, &error.to_string())
+                .map_err(|e| Error::RuntimeError(e.to_string()))?;
+            Err(Error::from(error))
+        }
+    }
+}
+
+fn working_directory(cwd: Option<PathBuf>) -> Result<PathBuf, Error> {
+    match cwd {
+        Some(cwd) => Ok(cwd),
+        None => std::env::current_dir().map_err(|e| Error::RuntimeError(e.to_string())),
+    }
+}
+
+/// Records a created or loaded session with the modes and config options it reported and makes
+/// it the current session
+fn record_session(
+    state: &Mutex<PluginState>,
+    connection: &ConnectionInfo,
+    session_id: &SessionId,
+    cwd: &Path,
+    modes: Option<SessionModeState>,
+    config_options: Option<Vec<SessionConfigOption>>,
+) -> Result<EventHandler, Error> {
+    let mut state = state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    state
+        .connection
+        .add_session(&connection.id, session_id.clone(), cwd.to_path_buf());
+    if let Some(modes) = modes {
+        state
+            .handler()
+            .record_modes(session_id, modes)
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    }
+    if let Some(options) = config_options {
+        state
+            .handler()
+            .record_config_options(session_id, options)
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    }
+    state.current_session = Some(session_id.clone());
+    Ok(state.handler().clone())
+}
+
+/// Creates a session and records the modes and config options it reported
+///
+/// The session is created on the connection `target` names, or the oldest connection.
+fn create_session(
+    state: &Mutex<PluginState>,
+    target: Option<&str>,
+    cwd: Option<PathBuf>,
+    mcp_servers: Vec<McpServer>,
+) -> Result<SessionId, Error> {
+    let cwd = working_directory(cwd)?;
+    let (connection, handle, mcp_servers) = {
+        let state = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        let connection = state.resolve_connection(target)?;
+        let mcp_servers = state
+            .connection
+            .session_mcp_servers(&connection.id, mcp_servers)?;
+        let handle = state.connection.handle(&connection.id)?;
+        (connection, handle, mcp_servers)
+    };
+    let response = handle
+        .new_session(cwd.clone(), mcp_servers)
+        .map_err(|error| request_error(state, &connection, None, error))?;
+    record_session(
+        state,
+        &connection,
+        &response.session_id,
+        &cwd,
+        response.modes,
+        response.config_options,
+    )?
+    .session_created(&connection, &response.session_id, &cwd)
+    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    Ok(response.session_id)
+}
+
+/// Resumes a session the agent knows about, its history is replayed through the usual autocommands
+fn load_session(
+    state: &Mutex<PluginState>,
+    target: Option<&str>,
+    session_id: SessionId,
+    cwd: Option<PathBuf>,
+    mcp_servers: Vec<McpServer>,
+) -> Result<(), Error> {
+    let cwd = working_directory(cwd)?;
+    let (handler, connection, handle, mcp_servers) = {
+        let state = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        let connection = state.resolve_connection(target)?;
+        state.connection.check_load_session(&connection.id)?;
+        let mcp_servers = state
+            .connection
+            .session_mcp_servers(&connection.id, mcp_servers)?;
+        let handle = state.connection.handle(&connection.id)?;
+        (state.handler().clone(), connection, handle, mcp_servers)
+    };
+    handler
+        .start_replay(&session_id)
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    let response = handle.load_session(session_id.clone(), cwd.clone(), mcp_servers);
+    handler
+        .finish_replay(&session_id)
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    let response =
+        response.map_err(|error| request_error(state, &connection, Some(&session_id), error))?;
+    record_session(
+        state,
+        &connection,
+        &session_id,
+        &cwd,
+        response.modes,
+        response.config_options,
+    )?
+    .session_loaded(&connection, &session_id, &cwd)
+    .map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// Loads a recorded session on a connection to the agent it was recorded with
+///
+/// The session is loaded in the directory it was started in, `target` picks another connection.
+fn resume_session(
+    state: &Mutex<PluginState>,
+    target: Option<&str>,
+    session_id: SessionId,
+    mcp_servers: Vec<McpServer>,
+) -> Result<(), Error> {
+    let record = state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .history()?
+        .session(&session_id)?;
+    load_session(
+        state,
+        Some(target.unwrap_or(&record.agent)),
+        session_id,
+        record.cwd.map(PathBuf::from),
+        mcp_servers,
+    )
+}
+
+/// Closes a connection and forgets the sessions it owned
+fn disconnect_agent(state: &Mutex<PluginState>, target: &str) -> Result<(), Error> {
+    let (handler, connection, sessions) = {
+        let mut state = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        let connection = state.resolve_connection(Some(target))?;
+        let sessions = state.close(&connection)?;
+        (state.handler().clone(), connection, sessions)
+    };
+    handler
+        .agent_disconnected(&connection, &sessions)
+        .map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// A handle to the connection a session belongs to, requests on it are made without the plugin
+/// state locked since the handler fires autocommands while they are waited on
+fn session_handle(
+    state: &Mutex<PluginState>,
+    session_id: &SessionId,
+) -> Result<ConnectionHandle, Error> {
+    let state = state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    let connection = state.session_connection(session_id)?;
+    Ok(state.connection.handle(&connection.id)?)
+}
+
+/// Asks the agent owning the session to stop its current turn
+///
+/// Neovim is busy while a turn is waited on, so this is called from an autocommand fired during
+/// the turn. The notification is then sent alongside the prompt.
+fn cancel_turn(state: &Mutex<PluginState>, session_id: SessionId) -> Result<(), Error> {
+    session_handle(state, &session_id)?
+        .cancel(session_id)
+        .map_err(Error::from)
+}
+
+/// Switches the session to one of the modes it advertised
+fn change_mode(
+    state: &Mutex<PluginState>,
+    session_id: SessionId,
+    mode_id: SessionModeId,
+) -> Result<(), Error> {
+    let mode_tracker = state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .modes();
+    if !mode_tracker
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .supports(&session_id, &mode_id)
+    {
+        return Err(Error::RuntimeError(format!(
+            "Mode \"{}\" is not available in session {}",
+            mode_id, session_id
+        )));
+    }
+    session_handle(state, &session_id)?
+        .set_mode(session_id.clone(), mode_id.clone())
+        .map_err(Error::from)?;
+    mode_tracker
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .set_current(&session_id, mode_id);
+    Ok(())
+}
+
+/// Sets a config option after checking the value is one the agent offered
+fn change_config_option(
+    state: &Mutex<PluginState>,
+    session_id: SessionId,
+    option_id: SessionConfigId,
+    value: SessionConfigValueId,
+) -> Result<(), Error> {
+    let option_tracker = state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .config_options();
+    {
+        let tracker = option_tracker
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        let option = tracker.get(&session_id, &option_id).ok_or_else(|| {
+            Error::RuntimeError(format!(
+                "Unknown config option \"{}\" for session {}",
+                option_id, session_id
+            ))
+        })?;
+        let values = config_options::values(option);
+        if !values.contains(&&value) {
+            return Err(Error::RuntimeError(format!(
+                "Invalid value \"{}\" for config option \"{}\", expected one of: {}",
+                value,
+                option_id,
+                values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )));
+        }
+    }
+    let response = session_handle(state, &session_id)?
+        .set_config_option(session_id.clone(), option_id, value)
+        .map_err(Error::from)?;
+    option_tracker
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .update(&session_id, response.config_options);
+    Ok(())
+}
+
+/// How long every tick of lua/hermes/loop.lua lets prompt turns run
+const TURN_TICK: Duration = Duration::from_millis(5);
+
+/// Starts the timer driving prompt turns, it stops by itself once no turn is left
+fn drive_turns() -> Result<(), Error> {
+    nvim_oxi::api::call_function::<_, Object>("luaeval", ("require('hermes.loop').start()",))
+        .map(|_| ())
+        .map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// Sends a prompt and records the turn, returning once the turn started
+///
+/// The connection defaults to the one the session was created on. Mentions are expanded and the
+/// content checked against what the agent accepts before the turn is recorded, so the transcript
+/// holds what the agent was sent. Providers run without
+/// the plugin state locked since Lua providers may call back into Hermes. The turn runs while
+/// Neovim is idle, `finished` gets the stop reason once `PromptFinished` fired.
+fn send_prompt(
+    state: &Rc<Mutex<PluginState>>,
+    target: Option<&str>,
+    session_id: SessionId,
+    content: Vec<ContentBlock>,
+    finished: impl FnOnce(Result<String, Error>) + 'static,
+) -> Result<(), Error> {
+    let (handler, connection, handle, context, cwd) = {
+        let state = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        let connection = match target {
+            Some(target) => state.resolve_connection(Some(target))?,
+            None => state.session_connection(&session_id)?,
+        };
+        let handle = state.connection.handle(&connection.id)?;
+        let cwd = working_directory(
+            state
+                .connection
+                .session_cwd(&session_id)
+                .map(Path::to_path_buf),
+        )?;
+        (
+            state.handler().clone(),
+            connection,
+            handle,
+            state.context().clone(),
+            cwd,
+        )
+    };
+    let content = {
+        let content = context.expand(content, &cwd)?;
+        let mut state = state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        let content = state.connection.prompt_content(&connection.id, content)?;
+        state.current_session = Some(session_id.clone());
+        content
+    };
+    handler
+        .record_prompt(&session_id, content.clone())
+        .and_then(|_| handler.prompt_started(&connection, &session_id))
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    // The turn lives on the local set, which the plugin state holds on to
+    let state = Rc::downgrade(state);
+    handle.spawn_prompt(session_id.clone(), content, move |response| {
+        finished(finish_turn(
+            &state,
+            &handler,
+            &connection,
+            &session_id,
+            response,
+        ))
+    });
+    drive_turns()
+}
+
+/// Records the end of a turn and fires `PromptFinished`, returning the stop reason
+fn finish_turn(
+    state: &Weak<Mutex<PluginState>>,
+    handler: &EventHandler,
+    connection: &ConnectionInfo,
+    session_id: &SessionId,
+    response: Result<PromptResponse, apc::error::Error>,
+) -> Result<String, Error> {
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            handler
+                .prompt_finished(connection, session_id, Err(&error.to_string()))
+                .map_err(|e| Error::RuntimeError(e.to_string()))?;
+            return Err(match state.upgrade() {
+                Some(state) => request_error(&state, connection, Some(session_id), error),
+                None => Error::from(error),
+            });
+        }
+    };
+    handler
+        .complete_turn(session_id, response.stop_reason)
+        .and_then(|_| handler.prompt_finished(connection, session_id, Ok(response.stop_reason)))
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    parse::enum_name(&response.stop_reason).map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// Reports a turn that failed after its prompt call returned, nobody is left to return it to
+fn report_turn<T>(finished: Result<T, Error>) {
+    if let Err(error) = finished {
+        let _ = nvim_oxi::api::notify(
+            &error.to_string(),
+            nvim_oxi::api::types::LogLevel::Error,
+            &Dictionary::new(),
+        );
+    }
+}
+
 #[nvim_oxi::plugin]
 pub fn api() -> nvim_oxi::Result<Dictionary> {
     let plugin_state = Rc::new(Mutex::new(PluginState::new()?));
+    command::register(&plugin_state)?;
 
-    let connect: Function<Option<ConnectionArgs>, Result<(), Error>> =
+    let state = plugin_state.clone();
+    let setup: Function<Option<Setup>, Result<(), Error>> =
+        Function::from_fn(move |setup: Option<Setup>| {
+            // Resolved before locking since asking to trust the project waits on the user
+            let mut setup = setup.unwrap_or_default();
+            project::apply(&mut setup)?;
+            if setup.editor_mcp {
+                // A server of the same name from the configuration takes its place
+                setup.mcp_servers = apc::mcp::merge(&[mcp::start()?], setup.mcp_servers);
+            }
+            let (handler, closed) = {
+                let mut state = state
+                    .lock()
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+                let closed = state.configure(setup)?;
+                (state.handler().clone(), closed)
+            };
+            for (connection, sessions) in closed {
+                handler
+                    .agent_disconnected(&connection, &sessions)
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+            }
+            Ok(())
+        });
+
+    let state = plugin_state.clone();
+    let connect: Function<Option<ConnectionArgs>, Result<String, Error>> =
         Function::from_fn(move |arg: Option<ConnectionArgs>| {
-            let details = arg.map(ConnectionDetails::from).unwrap_or_default();
-            plugin_state
+            connect_agent(&state, arg.map(ConnectionDetails::from).unwrap_or_default())
+                .map(|id| id.to_string())
+        });
+
+    let state = plugin_state.clone();
+    let disconnect: Function<String, Result<(), Error>> =
+        Function::from_fn(move |target: String| disconnect_agent(&state, &target));
+
+    let state = plugin_state.clone();
+    let auth_methods: Function<String, Result<Array, Error>> =
+        Function::from_fn(move |target: String| {
+            let methods = {
+                let state = state
+                    .lock()
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+                let connection = state.resolve_connection(Some(&target))?;
+                state.connection.auth_methods(&connection.id)
+            };
+            methods
+                .into_iter()
+                .map(|method| {
+                    parse::auth_method(method)
+                        .map(Object::from)
+                        .map_err(|e| Error::RuntimeError(e.to_string()))
+                })
+                .collect::<Result<Array, Error>>()
+        });
+
+    let state = plugin_state.clone();
+    let authenticate: Function<(String, String), Result<(), Error>> =
+        Function::from_fn(move |(target, method_id): (String, String)| {
+            let method_id = AuthMethodId::new(method_id);
+            let handle = {
+                let state = state
+                    .lock()
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+                let connection = state.resolve_connection(Some(&target))?;
+                if !state
+                    .connection
+                    .auth_methods(&connection.id)
+                    .iter()
+                    .any(|method| method.id == method_id)
+                {
+                    return Err(Error::RuntimeError(format!(
+                        "Authentication method \"{}\" is not supported by {}",
+                        method_id, connection.id
+                    )));
+                }
+                state.connection.handle(&connection.id)?
+            };
+            handle.authenticate(method_id).map_err(Error::from)?;
+            Ok(())
+        });
+
+    let state = plugin_state.clone();
+    let new_session: Function<Option<SessionArgs>, Result<String, Error>> =
+        Function::from_fn(move |arg: Option<SessionArgs>| {
+            let SessionArgs {
+                connection,
+                cwd,
+                mcp_servers,
+            } = arg.unwrap_or_default();
+            create_session(&state, connection.as_deref(), cwd, mcp_servers).map(|id| id.to_string())
+        });
+
+    let state = plugin_state.clone();
+    let load: Function<LoadSessionArgs, Result<(), Error>> =
+        Function::from_fn(move |args: LoadSessionArgs| {
+            load_session(
+                &state,
+                args.connection.as_deref(),
+                args.session_id,
+                args.cwd,
+                args.mcp_servers,
+            )
+        });
+
+    let state = plugin_state.clone();
+    let prompt: Function<PromptArgs, Result<(), Error>> =
+        Function::from_fn(move |args: PromptArgs| {
+            send_prompt(
+                &state,
+                args.connection.as_deref(),
+                args.session_id,
+                args.content,
+                report_turn,
+            )
+        });
+
+    let registry = plugin_state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .commands();
+    let state = plugin_state.clone();
+    let cancel: Function<String, Result<(), Error>> =
+        Function::from_fn(move |session_id: String| {
+            cancel_turn(&state, SessionId::new(session_id))
+        });
+
+    let available_commands: Function<String, Result<Array, Error>> = {
+        let registry = registry.clone();
+        Function::from_fn(move |session_id: String| {
+            let commands = registry
                 .lock()
                 .map_err(|e| Error::RuntimeError(e.to_string()))?
-                .connection
-                .connect(details.clone())
-                .map_err(Error::from)?;
-            Ok(())
+                .commands(&SessionId::new(session_id));
+            commands
+                .into_iter()
+                .map(|command| {
+                    parse::available_command(command)
+                        .map(Object::from)
+                        .map_err(|e| Error::RuntimeError(e.to_string()))
+                })
+                .collect::<Result<Array, Error>>()
+        })
+    };
+
+    let state = plugin_state.clone();
+    let run_command: Function<(String, String, Option<String>), Result<(), Error>> =
+        Function::from_fn(
+            move |(session_id, name, input): (String, String, Option<String>)| {
+                let session_id = SessionId::new(session_id);
+                let text = registry
+                    .lock()
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?
+                    .get(&session_id, &name)
+                    .map(|command| commands::invocation(command, input.as_deref()))
+                    .ok_or_else(|| {
+                        Error::RuntimeError(format!(
+                            "Unknown command \"{}\" for session {}",
+                            name, session_id
+                        ))
+                    })?;
+                send_prompt(
+                    &state,
+                    None,
+                    session_id,
+                    vec![ContentBlock::Text(TextContent::new(text))],
+                    report_turn,
+                )
+            },
+        );
+
+    let mode_tracker = plugin_state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .modes();
+    let modes: Function<String, Result<Object, Error>> = {
+        let mode_tracker = mode_tracker.clone();
+        Function::from_fn(move |session_id: String| {
+            let modes = mode_tracker
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .modes(&SessionId::new(session_id));
+            match modes {
+                Some(modes) => parse::session_modes(modes)
+                    .map(Object::from)
+                    .map_err(|e| Error::RuntimeError(e.to_string())),
+                None => Ok(Object::nil()),
+            }
+        })
+    };
+
+    let current_mode: Function<String, Result<Option<String>, Error>> = {
+        Function::from_fn(move |session_id: String| {
+            Ok(mode_tracker
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .current(&SessionId::new(session_id))
+                .map(ToString::to_string))
+        })
+    };
+
+    let state = plugin_state.clone();
+    let set_mode: Function<(String, String), Result<(), Error>> =
+        Function::from_fn(move |(session_id, mode_id): (String, String)| {
+            change_mode(
+                &state,
+                SessionId::new(session_id),
+                SessionModeId::new(mode_id),
+            )
         });
 
-    Ok(Dictionary::from_iter([("connect", connect)]))
+    let option_tracker = plugin_state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .config_options();
+    let get_config_options: Function<String, Result<Array, Error>> = {
+        Function::from_fn(move |session_id: String| {
+            let options = option_tracker
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .options(&SessionId::new(session_id));
+            options
+                .into_iter()
+                .map(|option| {
+                    parse::config_option(option)
+                        .map(Object::from)
+                        .map_err(|e| Error::RuntimeError(e.to_string()))
+                })
+                .collect::<Result<Array, Error>>()
+        })
+    };
+
+    let state = plugin_state.clone();
+    let set_config_option: Function<(String, String, String), Result<(), Error>> =
+        Function::from_fn(
+            move |(session_id, option_id, value): (String, String, String)| {
+                change_config_option(
+                    &state,
+                    SessionId::new(session_id),
+                    SessionConfigId::new(option_id),
+                    SessionConfigValueId::new(value),
+                )
+            },
+        );
+
+    // Read from the tracker directly so autocommand callbacks can query it mid prompt
+    let tracker = plugin_state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .tool_calls();
+    let tool_calls: Function<String, Result<Array, Error>> =
+        Function::from_fn(move |session_id: String| {
+            let tool_calls = tracker
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .tool_calls(&SessionId::new(session_id));
+            tool_calls
+                .into_iter()
+                .map(|tool_call| {
+                    producer::tool_call_data(tool_call)
+                        .map(Object::from)
+                        .map_err(|e| Error::RuntimeError(e.to_string()))
+                })
+                .collect::<Result<Array, Error>>()
+        });
+
+    let transcript_state = plugin_state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .transcript();
+    let transcript: Function<String, Result<Array, Error>> =
+        Function::from_fn(move |session_id: String| {
+            let session_id = SessionId::new(session_id);
+            let (messages, pending) = {
+                let transcript = transcript_state
+                    .lock()
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?;
+                (
+                    transcript.messages(&session_id),
+                    transcript.pending(&session_id),
+                )
+            };
+            messages
+                .into_iter()
+                .map(|message| (message, true))
+                .chain(pending.map(|message| (message, false)))
+                .map(|(message, complete)| {
+                    parse::message_event(message)
+                        .map(|mut dict| {
+                            dict.insert("complete", complete);
+                            Object::from(dict)
+                        })
+                        .map_err(|e| Error::RuntimeError(e.to_string()))
+                })
+                .collect::<Result<Array, Error>>()
+        });
+
+    let history = plugin_state
+        .lock()
+        .map_err(|e| Error::RuntimeError(e.to_string()))?
+        .handler()
+        .history()
+        .cloned();
+    let export_session: Function<(String, Option<Dictionary>), Result<String, Error>> =
+        Function::from_fn(move |(session_id, opts): (String, Option<Dictionary>)| {
+            let format = match opts
+                .as_ref()
+                .map(|opts| string_arg(opts, "format"))
+                .transpose()?
+            {
+                Some(Some(name)) => export::Format::from_name(&name).ok_or_else(|| {
+                    Error::RuntimeError(format!(
+                        "Invalid input for \"format\", must be one of: {}",
+                        export::Format::NAMES.join(", ")
+                    ))
+                })?,
+                _ => export::Format::default(),
+            };
+            let history = history
+                .as_ref()
+                .ok_or_else(|| Error::RuntimeError("Sessions aren't recorded".to_string()))?;
+            let session_id = SessionId::new(session_id);
+            let entries = history.entries(&session_id)?;
+            Ok(export::export(&session_id, &entries, format))
+        });
+
+    let state = plugin_state.clone();
+    let sessions: Function<Option<String>, Result<Array, Error>> =
+        Function::from_fn(move |agent: Option<String>| {
+            let records = state
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?
+                .recorded_sessions(agent.as_deref())?;
+            Ok(Array::from_iter(
+                records.into_iter().map(parse::session_record),
+            ))
+        });
+
+    let state = plugin_state.clone();
+    let resume: Function<(String, Option<Dictionary>), Result<(), Error>> =
+        Function::from_fn(move |(session_id, opts): (String, Option<Dictionary>)| {
+            let opts = opts.unwrap_or_default();
+            resume_session(
+                &state,
+                connection_arg(&opts)?.as_deref(),
+                SessionId::new(session_id),
+                mcp_servers_arg(&opts)?,
+            )
+        });
+
+    // Like the trackers, read without locking the plugin state so it works mid prompt
+    let (status_tracker, status_modes) = {
+        let state = plugin_state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        (state.status(), state.handler().modes())
+    };
+    let status: Function<(), Result<Dictionary, Error>> = Function::from_fn(move |()| {
+        let connections = status_tracker
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?
+            .connections();
+        let modes = status_modes
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+        connections
+            .into_iter()
+            .map(|(id, status)| {
+                parse::agent_status(status, |session_id| modes.current(session_id).cloned())
+                    .map(|data| (id.to_string(), Object::from(data)))
+                    .map_err(|e| Error::RuntimeError(e.to_string()))
+            })
+            .collect::<Result<Dictionary, Error>>()
+    });
+
+    let mcp_answer: Function<(), ()> = Function::from_fn(move |_| {
+        if let Some(server) = mcp::running() {
+            server.answer(&mcp::NeovimEditor);
+        }
+    });
+
+    let state = plugin_state.clone();
+    let drive: Function<(), Result<bool, Error>> = Function::from_fn(move |()| {
+        let (driver, status) = {
+            let state = state
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?;
+            (state.connection.driver(), state.status.clone())
+        };
+        driver.run_for(TURN_TICK)?;
+        let prompting = status
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?
+            .is_prompting();
+        Ok(prompting)
+    });
+
+    let state = plugin_state.clone();
+    let register_context: Function<(String, context::ContextFunction), Result<(), Error>> =
+        Function::from_fn(
+            move |(name, provider): (String, context::ContextFunction)| {
+                if name.is_empty() || !name.chars().all(apc::context::is_name_char) {
+                    return Err(Error::RuntimeError(format!(
+                        "Invalid context name \"{}\", use letters, digits, \"_\" and \"-\"",
+                        name
+                    )));
+                }
+                state
+                    .lock()
+                    .map_err(|e| Error::RuntimeError(e.to_string()))?
+                    .context_mut()
+                    .register(name, context::LuaProvider(provider));
+                Ok(())
+            },
+        );
+
+    let state = plugin_state.clone();
+    let contexts: Function<(), Result<Vec<String>, Error>> = Function::from_fn(move |()| {
+        Ok(state
+            .lock()
+            .map_err(|e| Error::RuntimeError(e.to_string()))?
+            .context()
+            .names())
+    });
+
+    let state = plugin_state.clone();
+    let health: Function<(), Result<(), Error>> = Function::from_fn(move |()| {
+        let sections = {
+            let state = state
+                .lock()
+                .map_err(|e| Error::RuntimeError(e.to_string()))?;
+            health::sections(&state)?
+        };
+        health::render(sections)
+    });
+
+    Ok(Dictionary::from_iter([
+        ("setup", Object::from(setup)),
+        ("connect", Object::from(connect)),
+        ("disconnect", Object::from(disconnect)),
+        ("auth_methods", Object::from(auth_methods)),
+        ("authenticate", Object::from(authenticate)),
+        ("new_session", Object::from(new_session)),
+        ("load_session", Object::from(load)),
+        ("sessions", Object::from(sessions)),
+        ("resume", Object::from(resume)),
+        ("prompt", Object::from(prompt)),
+        ("cancel", Object::from(cancel)),
+        ("commands", Object::from(available_commands)),
+        ("run_command", Object::from(run_command)),
+        ("modes", Object::from(modes)),
+        ("current_mode", Object::from(current_mode)),
+        ("set_mode", Object::from(set_mode)),
+        ("config_options", Object::from(get_config_options)),
+        ("set_config_option", Object::from(set_config_option)),
+        ("tool_calls", Object::from(tool_calls)),
+        ("transcript", Object::from(transcript)),
+        ("export", Object::from(export_session)),
+        ("status", Object::from(status)),
+        ("health", Object::from(health)),
+        ("register_context", Object::from(register_context)),
+        ("contexts", Object::from(contexts)),
+        // Called by the modules in lua/hermes rather than by users
+        ("_mcp_answer", Object::from(mcp_answer)),
+        ("_drive", Object::from(drive)),
+    ]))
 }
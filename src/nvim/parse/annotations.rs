@@ -1,10 +1,14 @@
-use agent_client_protocol::Annotations;
+use crate::nvim::parse::json::{enum_name, meta_to_dictionary};
+use agent_client_protocol::{Annotations, Result};
 use nvim_oxi::Dictionary;
 
-pub fn parse_annotations(annotations: Annotations) -> Dictionary {
+pub fn parse_annotations(annotations: Annotations) -> Result<Dictionary> {
     let mut annotations_dict = Dictionary::new();
     if let Some(audience) = annotations.audience {
-        let roles: Vec<String> = audience.iter().map(|r| format!("{:?}", r)).collect();
+        let roles = audience
+            .iter()
+            .map(enum_name)
+            .collect::<Result<Vec<String>>>()?;
         annotations_dict.insert("audience", nvim_oxi::Array::from_iter(roles));
     }
     if let Some(last_modified) = annotations.last_modified {
@@ -13,5 +17,8 @@ pub fn parse_annotations(annotations: Annotations) -> Dictionary {
     if let Some(priority) = annotations.priority {
         annotations_dict.insert("priority", priority);
     }
-    annotations_dict
+    if let Some(meta) = annotations.meta {
+        annotations_dict.insert("meta", meta_to_dictionary(meta));
+    }
+    Ok(annotations_dict)
 }
@@ -0,0 +1,16 @@
+use crate::nvim::parse::json::meta_to_dictionary;
+use agent_client_protocol::{AuthMethod, Result};
+use nvim_oxi::Dictionary;
+
+pub fn auth_method(method: AuthMethod) -> Result<Dictionary> {
+    let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    data.insert("id", method.id.to_string());
+    data.insert("name", method.name);
+    if let Some(description) = method.description {
+        data.insert("description", description);
+    }
+    if let Some(meta) = method.meta {
+        data.insert("meta", meta_to_dictionary(meta));
+    }
+    Ok(data)
+}
@@ -1,28 +1,51 @@
-use agent_client_protocol::{AvailableCommandInput, AvailableCommandsUpdate, Result};
+use crate::nvim::parse::{json::meta_to_dictionary, unknown::unknown_event};
+use agent_client_protocol::{
+    AvailableCommand, AvailableCommandInput, AvailableCommandsUpdate, Result,
+};
 use nvim_oxi::Dictionary;
 
+fn command_input(input: AvailableCommandInput) -> Result<Dictionary> {
+    match input {
+        AvailableCommandInput::Unstructured(input) => {
+            let mut dict = nvim_oxi::Dictionary::new();
+            dict.insert("type", "unstructured");
+            dict.insert("hint", input.hint);
+            if let Some(meta) = input.meta {
+                dict.insert("meta", meta_to_dictionary(meta));
+            }
+            Ok(dict)
+        }
+        other => unknown_event("command_input", &other).map(|mut dict| {
+            dict.insert("type", "unknown");
+            dict
+        }),
+    }
+}
+
+pub fn available_command(command: AvailableCommand) -> Result<Dictionary> {
+    let mut dict = nvim_oxi::Dictionary::new();
+    dict.insert("name", command.name);
+    dict.insert("description", command.description);
+    if let Some(input) = command.input {
+        dict.insert("input", command_input(input)?);
+    }
+    if let Some(meta) = command.meta {
+        dict.insert("meta", meta_to_dictionary(meta));
+    }
+    Ok(dict)
+}
+
 pub fn available_commands_event(update: AvailableCommandsUpdate) -> Result<Dictionary> {
     let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
-    let commands = update.available_commands.into_iter().map(|command| {
-        let mut dict = nvim_oxi::Dictionary::new();
-        dict.insert("name", command.name);
-        dict.insert("description", command.description);
-        if let Some(AvailableCommandInput::Unstructured(input)) = command.input {
-            dict.insert(
-                "input",
-                Dictionary::from_iter(vec![
-                    ("hint", input.hint),
-                    // TODO: get meta figured out
-                    // ("meta", input.meta)
-                ]),
-            );
-        }
-        dict
-    });
+    let commands = update
+        .available_commands
+        .into_iter()
+        .map(available_command)
+        .collect::<Result<Vec<Dictionary>>>()?;
     data.insert("commands", nvim_oxi::Array::from_iter(commands));
 
     if let Some(meta) = update.meta {
-        data.insert("meta", format!("{:?}", meta));
+        data.insert("meta", meta_to_dictionary(meta));
     }
 
     Ok(data)
@@ -0,0 +1,16 @@
+use crate::nvim::parse::{annotations::parse_annotations, json::meta_to_dictionary};
+use agent_client_protocol::{AudioContent, Result};
+use nvim_oxi::Dictionary;
+
+pub fn audio_event(audio: AudioContent) -> Result<(Dictionary, String)> {
+    let mut dict: Dictionary = Dictionary::new();
+    dict.insert("data", audio.data);
+    dict.insert("mimeType", audio.mime_type);
+    if let Some(annotations) = audio.annotations {
+        dict.insert("annotations", parse_annotations(annotations)?);
+    }
+    if let Some(meta) = audio.meta {
+        dict.insert("meta", meta_to_dictionary(meta));
+    }
+    Ok((dict, "Audio".to_string()))
+}
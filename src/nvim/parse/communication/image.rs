@@ -1,4 +1,4 @@
-use crate::nvim::parse::annotations::parse_annotations;
+use crate::nvim::parse::{annotations::parse_annotations, json::meta_to_dictionary};
 use agent_client_protocol::{ImageContent, Result};
 use nvim_oxi::Dictionary;
 
@@ -10,10 +10,10 @@ pub fn image_event(image: ImageContent) -> Result<(Dictionary, String)> {
         dict.insert("uri", uri);
     }
     if let Some(annotations) = image.annotations {
-        dict.insert("annotations", parse_annotations(annotations));
+        dict.insert("annotations", parse_annotations(annotations)?);
     }
     if let Some(meta) = image.meta {
-        dict.insert("meta", format!("{:?}", meta));
+        dict.insert("meta", meta_to_dictionary(meta));
     }
     Ok((dict, "Image".to_string()))
 }
@@ -1,23 +1,35 @@
+mod audio;
 mod image;
 mod resource;
 mod resource_link;
 mod text;
 
+pub use audio::audio_event;
 pub use image::image_event;
 pub use resource::resource_event;
 pub use resource_link::resource_link_event;
 pub use text::text_event;
 
-use agent_client_protocol::{ContentBlock, Error as AcpError, Result};
+use crate::nvim::parse::unknown::{UNKNOWN, unknown_event};
+use agent_client_protocol::{ContentBlock, Error, Result};
 use nvim_oxi::Dictionary;
 
+/// The `type` the protocol names a content block with, e.g. `resource_link`
+pub fn content_type(content: &ContentBlock) -> Result<String> {
+    let value = serde_json::to_value(content).map_err(Error::into_internal_error)?;
+    Ok(value["type"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| UNKNOWN.to_lowercase()))
+}
+
 pub fn communication(content: ContentBlock) -> Result<(Dictionary, String)> {
     match content {
         ContentBlock::Resource(block) => resource::resource_event(block),
         ContentBlock::ResourceLink(block) => resource_link::resource_link_event(block),
         ContentBlock::Image(image) => image::image_event(image),
         ContentBlock::Text(text) => text::text_event(text),
-        ContentBlock::Audio(_) => Err(AcpError::method_not_found()),
-        _ => Err(AcpError::method_not_found()),
+        ContentBlock::Audio(audio) => audio::audio_event(audio),
+        other => unknown_event("content_block", &other).map(|dict| (dict, UNKNOWN.to_string())),
     }
 }
@@ -1,4 +1,4 @@
-use crate::nvim::parse::annotations::parse_annotations;
+use crate::nvim::parse::{annotations::parse_annotations, json::meta_to_dictionary};
 use agent_client_protocol::{EmbeddedResource, EmbeddedResourceResource, Result};
 use nvim_oxi::Dictionary;
 
@@ -29,10 +29,10 @@ pub fn resource_event(block: EmbeddedResource) -> Result<(Dictionary, String)> {
     dict.insert("resource", resource_dict);
 
     if let Some(annotations) = block.annotations {
-        dict.insert("annotations", parse_annotations(annotations));
+        dict.insert("annotations", parse_annotations(annotations)?);
     }
     if let Some(meta) = block.meta {
-        dict.insert("meta", format!("{:?}", meta));
+        dict.insert("meta", meta_to_dictionary(meta));
     }
     Ok((dict, "Resource".to_string()))
 }
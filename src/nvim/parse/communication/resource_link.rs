@@ -1,4 +1,4 @@
-use crate::nvim::parse::annotations::parse_annotations;
+use crate::nvim::parse::{annotations::parse_annotations, json::meta_to_dictionary};
 use agent_client_protocol::{ResourceLink, Result};
 use nvim_oxi::Dictionary;
 
@@ -19,10 +19,10 @@ pub fn resource_link_event(block: ResourceLink) -> Result<(Dictionary, String)>
         dict.insert("title", title);
     }
     if let Some(annotations) = block.annotations {
-        dict.insert("annotations", parse_annotations(annotations));
+        dict.insert("annotations", parse_annotations(annotations)?);
     }
     if let Some(meta) = block.meta {
-        dict.insert("meta", format!("{:?}", meta));
+        dict.insert("meta", meta_to_dictionary(meta));
     }
     Ok((dict, "ResourceLink".to_string()))
 }
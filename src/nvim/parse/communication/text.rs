@@ -1,4 +1,4 @@
-use crate::nvim::parse::annotations::parse_annotations;
+use crate::nvim::parse::{annotations::parse_annotations, json::meta_to_dictionary};
 use agent_client_protocol::{Result, TextContent};
 use nvim_oxi::Dictionary;
 
@@ -6,10 +6,10 @@ pub fn text_event(text: TextContent) -> Result<(Dictionary, String)> {
     let mut dict: Dictionary = Dictionary::new();
     dict.insert("text", text.text);
     if let Some(annotations) = text.annotations {
-        dict.insert("annotations", parse_annotations(annotations));
+        dict.insert("annotations", parse_annotations(annotations)?);
     }
     if let Some(meta) = text.meta {
-        dict.insert("meta", format!("{:?}", meta));
+        dict.insert("meta", meta_to_dictionary(meta));
     }
     Ok((dict, "Text".to_string()))
 }
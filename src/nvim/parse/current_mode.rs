@@ -1,11 +1,38 @@
-use agent_client_protocol::{CurrentModeUpdate, Result};
+use crate::nvim::parse::json::meta_to_dictionary;
+use agent_client_protocol::{CurrentModeUpdate, Result, SessionModeState};
 use nvim_oxi::Dictionary;
 
 pub fn current_mode_event(update: CurrentModeUpdate) -> Result<Dictionary> {
     let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
     data.insert("id", update.current_mode_id.to_string());
     if let Some(meta) = update.meta {
-        data.insert("meta", format!("{:?}", meta));
+        data.insert("meta", meta_to_dictionary(meta));
+    }
+    Ok(data)
+}
+
+/// Describes the modes a session supports and the one it is currently in
+pub fn session_modes(modes: SessionModeState) -> Result<Dictionary> {
+    let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    data.insert("currentModeId", modes.current_mode_id.to_string());
+    let available_modes = modes.available_modes.into_iter().map(|mode| {
+        let mut dict = nvim_oxi::Dictionary::new();
+        dict.insert("id", mode.id.to_string());
+        dict.insert("name", mode.name);
+        if let Some(description) = mode.description {
+            dict.insert("description", description);
+        }
+        if let Some(meta) = mode.meta {
+            dict.insert("meta", meta_to_dictionary(meta));
+        }
+        dict
+    });
+    data.insert(
+        "availableModes",
+        nvim_oxi::Array::from_iter(available_modes),
+    );
+    if let Some(meta) = modes.meta {
+        data.insert("meta", meta_to_dictionary(meta));
     }
     Ok(data)
 }
@@ -0,0 +1,35 @@
+use crate::nvim::parse::{
+    auth_method,
+    json::{json_to_object, meta_to_dictionary},
+};
+use agent_client_protocol::{Error, InitializeResponse, Result};
+use nvim_oxi::{Array, Dictionary};
+
+/// Converts the agent's answer to `initialize`, capabilities keep their ACP field names
+pub fn initialize_response(response: InitializeResponse) -> Result<Dictionary> {
+    let mut data = Dictionary::new();
+    let version =
+        serde_json::to_value(response.protocol_version).map_err(Error::into_internal_error)?;
+    data.insert("protocolVersion", json_to_object(version));
+    let capabilities =
+        serde_json::to_value(response.agent_capabilities).map_err(Error::into_internal_error)?;
+    data.insert("agentCapabilities", json_to_object(capabilities));
+    data.insert(
+        "authMethods",
+        Array::from_iter(
+            response
+                .auth_methods
+                .into_iter()
+                .map(auth_method)
+                .collect::<Result<Vec<Dictionary>>>()?,
+        ),
+    );
+    if let Some(info) = response.agent_info {
+        let info = serde_json::to_value(info).map_err(Error::into_internal_error)?;
+        data.insert("agentInfo", json_to_object(info));
+    }
+    if let Some(meta) = response.meta {
+        data.insert("meta", meta_to_dictionary(meta));
+    }
+    Ok(data)
+}
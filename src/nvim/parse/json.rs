@@ -0,0 +1,68 @@
+use agent_client_protocol::{Error, Meta, Result};
+use nvim_oxi::{Array, Dictionary, Object, ObjectKind, conversion::FromObject};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Converts a JSON value into a Lua compatible object, objects and arrays become tables
+pub fn json_to_object(value: Value) -> Object {
+    match value {
+        Value::Null => Object::nil(),
+        Value::Bool(b) => Object::from(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Object::from(i),
+            None => Object::from(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => Object::from(s),
+        Value::Array(values) => {
+            Object::from(Array::from_iter(values.into_iter().map(json_to_object)))
+        }
+        Value::Object(map) => Object::from(meta_to_dictionary(map)),
+    }
+}
+
+/// Converts an ACP `_meta` map into a dictionary
+pub fn meta_to_dictionary(meta: Meta) -> Dictionary {
+    Dictionary::from_iter(
+        meta.into_iter()
+            .map(|(key, value)| (key, json_to_object(value))),
+    )
+}
+
+/// The ACP wire spelling of an enum (e.g. "in_progress"), kept independent of its Debug output
+pub fn enum_name<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value).map_err(Error::into_internal_error)? {
+        Value::String(name) => Ok(name),
+        other => Err(Error::internal_error().data(other)),
+    }
+}
+
+/// Converts a Lua object back into JSON, the inverse of [`json_to_object`]
+///
+/// Functions and other values JSON can't hold become `null`.
+pub fn object_to_json(object: Object) -> Value {
+    match object.kind() {
+        ObjectKind::Boolean => Value::Bool(bool::from_object(object).unwrap_or_default()),
+        ObjectKind::Integer => Value::from(i64::from_object(object).unwrap_or_default()),
+        ObjectKind::Float => Value::from(f64::from_object(object).unwrap_or_default()),
+        ObjectKind::String => Value::String(String::from_object(object).unwrap_or_default()),
+        ObjectKind::Array => Value::Array(
+            Array::from_object(object)
+                .map(|array| array.into_iter().map(object_to_json).collect())
+                .unwrap_or_default(),
+        ),
+        ObjectKind::Dictionary => Dictionary::from_object(object)
+            .map(dictionary_to_json)
+            .unwrap_or_default(),
+        _ => Value::Null,
+    }
+}
+
+/// Converts event data into a JSON object
+pub fn dictionary_to_json(dictionary: Dictionary) -> Value {
+    Value::Object(
+        dictionary
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), object_to_json(value)))
+            .collect(),
+    )
+}
@@ -0,0 +1,24 @@
+use crate::{apc::transcript::Message, nvim::parse};
+use agent_client_protocol::Result;
+use nvim_oxi::Dictionary;
+
+pub fn message_event(message: Message) -> Result<Dictionary> {
+    let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    data.insert("role", message.role.to_string());
+    data.insert("text", message.text());
+
+    let content = message
+        .content
+        .into_iter()
+        .map(|block| {
+            let content_type = parse::content_type(&block)?;
+            parse::communication(block).map(|(mut dict, _)| {
+                dict.insert("type", content_type);
+                dict
+            })
+        })
+        .collect::<Result<Vec<Dictionary>>>()?;
+    data.insert("content", nvim_oxi::Array::from_iter(content));
+
+    Ok(data)
+}
@@ -1,18 +1,37 @@
 pub mod annotations;
+pub mod auth_method;
 pub mod available_commands;
 pub mod communication;
 pub mod config_option;
 pub mod current_mode;
+pub mod initialize;
+pub mod json;
+pub mod message;
+pub mod permission;
 pub mod plan;
+pub mod session_record;
+pub mod status;
 pub mod tool_call;
 pub mod tool_call_content;
+pub mod tool_call_location;
 pub mod tool_call_update;
+pub mod unknown;
 
+pub use auth_method::*;
 pub use available_commands::*;
 pub use communication::*;
-pub use communication::{image_event, resource_event, resource_link_event, text_event};
+pub use communication::{
+    audio_event, image_event, resource_event, resource_link_event, text_event,
+};
 pub use config_option::*;
 pub use current_mode::*;
+pub use initialize::*;
+pub use json::*;
+pub use message::*;
+pub use permission::*;
 pub use plan::*;
+pub use session_record::*;
+pub use status::*;
 pub use tool_call::*;
 pub use tool_call_update::*;
+pub use unknown::*;
@@ -0,0 +1,30 @@
+use crate::nvim::parse::{json::enum_name, tool_call_update::tool_call_update_event};
+use agent_client_protocol::{
+    RequestPermissionOutcome, RequestPermissionRequest, RequestPermissionResponse, Result,
+};
+use nvim_oxi::Dictionary;
+
+/// How a permission request was answered, with the tool call it was asked for
+pub fn permission_event(
+    request: RequestPermissionRequest,
+    response: &RequestPermissionResponse,
+) -> Result<Dictionary> {
+    let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    match &response.outcome {
+        RequestPermissionOutcome::Selected(selected) => {
+            data.insert("outcome", "selected");
+            data.insert("optionId", selected.option_id.to_string());
+            if let Some(option) = request
+                .options
+                .iter()
+                .find(|option| option.option_id == selected.option_id)
+            {
+                data.insert("name", option.name.as_str());
+                data.insert("kind", enum_name(&option.kind)?);
+            }
+        }
+        _ => data.insert("outcome", "cancelled"),
+    }
+    data.insert("toolCall", tool_call_update_event(request.tool_call)?);
+    Ok(data)
+}
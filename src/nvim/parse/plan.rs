@@ -1,20 +1,39 @@
-use agent_client_protocol::{Plan, Result};
+use crate::nvim::parse::json::{enum_name, meta_to_dictionary};
+use agent_client_protocol::{Plan, PlanEntryStatus, Result};
 use nvim_oxi::Dictionary;
 
 pub fn plan_event(plan: Plan) -> Result<Dictionary> {
     let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
-    let entries = plan.entries.into_iter().map(|entry| {
-        let mut dict = nvim_oxi::Dictionary::new();
-        dict.insert("content", entry.content.to_string());
-        dict.insert("priority", format!("{:?}", entry.priority));
-        // dict.insert("meta", entry.meta);
-        dict
-    });
+    let total = plan.entries.len();
+    let completed = plan
+        .entries
+        .iter()
+        .filter(|entry| entry.status == PlanEntryStatus::Completed)
+        .count();
+    let entries = plan
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let mut dict = nvim_oxi::Dictionary::new();
+            dict.insert("content", entry.content.to_string());
+            dict.insert("priority", enum_name(&entry.priority)?);
+            dict.insert("status", enum_name(&entry.status)?);
+            if let Some(meta) = entry.meta {
+                dict.insert("meta", meta_to_dictionary(meta));
+            }
+            Ok(dict)
+        })
+        .collect::<Result<Vec<Dictionary>>>()?;
 
     data.insert("entries", nvim_oxi::Array::from_iter(entries));
 
+    let mut progress = nvim_oxi::Dictionary::new();
+    progress.insert("completed", completed as i64);
+    progress.insert("total", total as i64);
+    data.insert("progress", progress);
+
     if let Some(meta) = plan.meta {
-        data.insert("meta", format!("{:?}", meta));
+        data.insert("meta", meta_to_dictionary(meta));
     }
 
     Ok(data)
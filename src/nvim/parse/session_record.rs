@@ -0,0 +1,17 @@
+use crate::apc::history::SessionRecord;
+use nvim_oxi::Dictionary;
+
+pub fn session_record(record: SessionRecord) -> Dictionary {
+    let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    data.insert("sessionId", record.session_id.to_string());
+    data.insert("agent", record.agent);
+    if let Some(cwd) = record.cwd {
+        data.insert("cwd", cwd);
+    }
+    if let Some(first_prompt) = record.first_prompt {
+        data.insert("firstPrompt", first_prompt);
+    }
+    data.insert("timestamp", record.timestamp as i64);
+    data.insert("updated", record.updated as i64);
+    data
+}
@@ -0,0 +1,43 @@
+use crate::{apc::status::AgentStatus, nvim::parse::json::json_to_object};
+use agent_client_protocol::{Error, Result, SessionId, SessionModeId};
+use nvim_oxi::{Array, Dictionary};
+
+/// Converts a connection's status, `mode` looks up the current mode of each of its sessions
+pub fn agent_status(
+    status: AgentStatus,
+    mode: impl Fn(&SessionId) -> Option<SessionModeId>,
+) -> Result<Dictionary> {
+    let mut data = Dictionary::new();
+    data.insert("agent", status.agent.to_string());
+    if let Some(label) = &status.label {
+        data.insert("label", label.as_str());
+    }
+    data.insert("state", status.state.to_string());
+    data.insert("protocol", status.protocol.to_string());
+    if let Some(pid) = status.pid {
+        data.insert("pid", i64::from(pid));
+    }
+    if let Some(version) = status.protocol_version {
+        let version = serde_json::to_value(version).map_err(Error::into_internal_error)?;
+        data.insert("protocolVersion", json_to_object(version));
+    }
+    if let Some(capabilities) = status.capabilities {
+        let capabilities =
+            serde_json::to_value(capabilities).map_err(Error::into_internal_error)?;
+        data.insert("capabilities", json_to_object(capabilities));
+    }
+    let sessions = status.sessions.iter().map(|session_id| {
+        let mut session = Dictionary::new();
+        session.insert("sessionId", session_id.to_string());
+        if let Some(mode) = mode(session_id) {
+            session.insert("mode", mode.to_string());
+        }
+        session.insert("prompting", status.prompting.contains(session_id));
+        session
+    });
+    data.insert("sessions", Array::from_iter(sessions));
+    if let Some(error) = status.last_error {
+        data.insert("lastError", error);
+    }
+    Ok(data)
+}
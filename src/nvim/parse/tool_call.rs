@@ -1,9 +1,11 @@
-use std::fs;
-
-use agent_client_protocol::{Error, Result, ToolCall};
+use agent_client_protocol::{Result, ToolCall};
 use nvim_oxi::Dictionary;
 
-use crate::nvim::parse::tool_call_content::parse_tool_call_content;
+use crate::nvim::parse::{
+    json::{enum_name, json_to_object, meta_to_dictionary},
+    tool_call_content::parse_tool_call_content,
+    tool_call_location::parse_tool_call_location,
+};
 
 pub fn tool_call_event(tool_call: ToolCall) -> Result<Dictionary> {
     let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
@@ -17,33 +19,24 @@ pub fn tool_call_event(tool_call: ToolCall) -> Result<Dictionary> {
     data.insert("content", nvim_oxi::Array::from_iter(tool_call_content));
     data.insert("id", tool_call.tool_call_id.to_string());
     data.insert("title", tool_call.title);
-    data.insert("kind", format!("{:?}", tool_call.kind));
-    data.insert("status", format!("{:?}", tool_call.status));
+    data.insert("kind", enum_name(&tool_call.kind)?);
+    data.insert("status", enum_name(&tool_call.status)?);
 
     let locations = tool_call
         .locations
-        .iter()
-        .map(|location| {
-            fs::read_to_string(location.path.clone())
-                .map(|path| {
-                    let mut dict = Dictionary::new();
-                    dict.insert("path", path);
-                    if let Some(line) = location.line {
-                        dict.insert("line", line);
-                    }
-                    dict
-                })
-                .map_err(Error::into_internal_error)
-        })
-        .collect::<Result<Vec<Dictionary>>>()?;
+        .into_iter()
+        .map(parse_tool_call_location);
 
     data.insert("locations", nvim_oxi::Array::from_iter(locations));
 
     if let Some(input) = tool_call.raw_input {
-        data.insert("input", input.to_string());
+        data.insert("input", json_to_object(input));
     }
     if let Some(output) = tool_call.raw_output {
-        data.insert("output", output.to_string());
+        data.insert("output", json_to_object(output));
+    }
+    if let Some(meta) = tool_call.meta {
+        data.insert("meta", meta_to_dictionary(meta));
     }
 
     Ok(data)
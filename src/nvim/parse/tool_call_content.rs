@@ -1,13 +1,13 @@
-use crate::nvim::parse;
-use agent_client_protocol::{Error, Result, ToolCallContent};
+use crate::nvim::parse::{self, json::meta_to_dictionary, unknown::unknown_event};
+use agent_client_protocol::{Result, ToolCallContent};
 use nvim_oxi::Dictionary;
-use std::fs;
 
 pub fn parse_tool_call_content(content: ToolCallContent) -> Result<Dictionary> {
     match content {
         ToolCallContent::Content(container) => {
-            parse::communication(container.content.clone()).map(|(mut dict, content_type)| {
-                dict.insert("type", content_type.to_lowercase());
+            let content_type = parse::content_type(&container.content)?;
+            parse::communication(container.content).map(|(mut dict, _)| {
+                dict.insert("type", content_type);
                 dict
             })
         }
@@ -17,18 +17,22 @@ pub fn parse_tool_call_content(content: ToolCallContent) -> Result<Dictionary> {
             dict.insert("type", "terminal");
             Ok(dict)
         }
-        ToolCallContent::Diff(diff) => fs::read_to_string(diff.path.clone())
-            .map_err(Error::into_internal_error)
-            .map(|path| {
-                let mut dict = Dictionary::new();
-                dict.insert("type", "diff");
-                dict.insert("path", path);
-                dict.insert("new_text", diff.new_text.clone());
-                if let Some(old_text) = diff.old_text.clone() {
-                    dict.insert("old_text", old_text);
-                }
-                dict
-            }),
-        _ => Err(Error::method_not_found()),
+        ToolCallContent::Diff(diff) => {
+            let mut dict = Dictionary::new();
+            dict.insert("type", "diff");
+            dict.insert("path", diff.path.to_string_lossy().to_string());
+            dict.insert("newText", diff.new_text);
+            if let Some(old_text) = diff.old_text {
+                dict.insert("oldText", old_text);
+            }
+            if let Some(meta) = diff.meta {
+                dict.insert("meta", meta_to_dictionary(meta));
+            }
+            Ok(dict)
+        }
+        other => unknown_event("tool_call_content", &other).map(|mut dict| {
+            dict.insert("type", "unknown");
+            dict
+        }),
     }
 }
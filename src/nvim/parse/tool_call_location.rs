@@ -0,0 +1,15 @@
+use crate::nvim::parse::json::meta_to_dictionary;
+use agent_client_protocol::ToolCallLocation;
+use nvim_oxi::Dictionary;
+
+pub fn parse_tool_call_location(location: ToolCallLocation) -> Dictionary {
+    let mut dict = Dictionary::new();
+    dict.insert("path", location.path.to_string_lossy().to_string());
+    if let Some(line) = location.line {
+        dict.insert("line", line);
+    }
+    if let Some(meta) = location.meta {
+        dict.insert("meta", meta_to_dictionary(meta));
+    }
+    dict
+}
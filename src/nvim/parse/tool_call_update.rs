@@ -1,16 +1,31 @@
 use agent_client_protocol::{Result, ToolCallUpdate};
 use nvim_oxi::Dictionary;
 
-use crate::nvim::parse::tool_call_content::parse_tool_call_content;
+use crate::nvim::parse::{
+    json::{enum_name, json_to_object, meta_to_dictionary},
+    tool_call_content::parse_tool_call_content,
+    tool_call_location::parse_tool_call_location,
+};
 
+/// Only the fields present in the update are included so they can be merged into the tool call
 pub fn tool_call_update_event(update: ToolCallUpdate) -> Result<Dictionary> {
     let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    let fields = update.fields;
 
     data.insert("id", update.tool_call_id.to_string());
 
-    if let Some(content) = update.fields.content {
+    if let Some(title) = fields.title {
+        data.insert("title", title);
+    }
+    if let Some(kind) = fields.kind {
+        data.insert("kind", enum_name(&kind)?);
+    }
+    if let Some(status) = fields.status {
+        data.insert("status", enum_name(&status)?);
+    }
+    if let Some(content) = fields.content {
         data.insert(
-            "fields",
+            "content",
             nvim_oxi::Array::from_iter(
                 content
                     .into_iter()
@@ -19,8 +34,20 @@ pub fn tool_call_update_event(update: ToolCallUpdate) -> Result<Dictionary> {
             ),
         );
     }
+    if let Some(locations) = fields.locations {
+        data.insert(
+            "locations",
+            nvim_oxi::Array::from_iter(locations.into_iter().map(parse_tool_call_location)),
+        );
+    }
+    if let Some(input) = fields.raw_input {
+        data.insert("input", json_to_object(input));
+    }
+    if let Some(output) = fields.raw_output {
+        data.insert("output", json_to_object(output));
+    }
     if let Some(meta) = update.meta {
-        data.insert("meta", format!("{:?}", meta));
+        data.insert("meta", meta_to_dictionary(meta));
     }
 
     Ok(data)
@@ -0,0 +1,26 @@
+use crate::apc::connection::unknown::UnknownUpdate;
+use crate::nvim::parse::json::json_to_object;
+use agent_client_protocol::{Error, Result};
+use nvim_oxi::Dictionary;
+use serde::Serialize;
+
+/// Content type reported for content blocks this version of Hermes does not understand
+pub const UNKNOWN: &str = "Unknown";
+
+/// Describes an update Hermes does not understand so it can be forwarded as is
+pub fn unknown_event<T: Serialize>(kind: &str, value: &T) -> Result<Dictionary> {
+    let value = serde_json::to_value(value).map_err(Error::into_internal_error)?;
+    let mut data: nvim_oxi::Dictionary = nvim_oxi::Dictionary::new();
+    data.insert("kind", kind);
+    data.insert("value", json_to_object(value));
+    Ok(data)
+}
+
+/// Describes an update the protocol library couldn't decode, see [`UnknownUpdate`]
+pub fn unknown_update_event(update: UnknownUpdate) -> Result<Dictionary> {
+    let mut data = unknown_event(update.kind, &update.value)?;
+    if let Some(tool_call_id) = update.tool_call_id {
+        data.insert("toolCallId", tool_call_id);
+    }
+    Ok(data)
+}
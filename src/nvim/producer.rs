@@ -1,32 +1,443 @@
-use crate::nvim::parse;
+use crate::{
+    apc::{
+        commands::CommandRegistry,
+        config_options::ConfigOptionTracker,
+        connection::{
+            ConnectionInfo, Protocol,
+            unknown::{UNKNOWN_UPDATE_METHOD, UnknownUpdate},
+        },
+        history::{History, HistoryEntry},
+        modes::ModeTracker,
+        tool_calls::ToolCallTracker,
+        transcript::{Message, MessageRole, Transcript},
+    },
+    nvim::{buffers, parse},
+};
 use agent_client_protocol::{
-    Client, CreateTerminalRequest, CreateTerminalResponse, Error as AcpError, ReadTextFileRequest,
+    AuthMethod, Client, ContentBlock, CreateTerminalRequest, CreateTerminalResponse,
+    Error as AcpError, ExtNotification, InitializeResponse, ReadTextFileRequest,
     ReadTextFileResponse, ReleaseTerminalRequest, ReleaseTerminalResponse,
-    RequestPermissionRequest, RequestPermissionResponse, Result, SessionNotification,
-    SessionUpdate, TerminalOutputRequest, TerminalOutputResponse, WaitForTerminalExitRequest,
+    RequestPermissionRequest, RequestPermissionResponse, Result, SessionConfigOption, SessionId,
+    SessionModeState, SessionNotification, SessionUpdate, StopReason, TerminalOutputRequest,
+    TerminalOutputResponse, ToolCall, ToolCallContent, ToolCallId, WaitForTerminalExitRequest,
     WaitForTerminalExitResponse, WriteTextFileRequest, WriteTextFileResponse,
 };
-use nvim_oxi::api::opts::ExecAutocmdsOpts;
+use nvim_oxi::{Dictionary, api::opts::ExecAutocmdsOpts};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+fn locked<T, R>(state: &Mutex<T>, apply: impl FnOnce(&mut T) -> R) -> Result<R> {
+    let mut state = state.lock().map_err(AcpError::into_internal_error)?;
+    Ok(apply(&mut state))
+}
+
+fn agent_data(connection: &ConnectionInfo) -> Dictionary {
+    let mut data = Dictionary::from_iter([
+        ("agent", connection.agent.to_string()),
+        ("connection", connection.id.to_string()),
+    ]);
+    if let Some(label) = &connection.label {
+        data.insert("label", label.as_str());
+    }
+    data
+}
+
+fn session_data(connection: &ConnectionInfo, cwd: &Path) -> Dictionary {
+    let mut data = agent_data(connection);
+    data.insert("cwd", cwd.to_string_lossy().to_string());
+    data
+}
+
+/// The events written to a session's history, enough to rebuild the conversation
+pub const RECORDED_EVENTS: [&str; 10] = [
+    "SessionCreated",
+    "SessionLoaded",
+    "UserMessageComplete",
+    "AgentMessageComplete",
+    "AgentThoughtComplete",
+    "AgentToolCall",
+    "AgentToolCallUpdate",
+    "AgentPlan",
+    "PermissionDecided",
+    "PromptFinished",
+];
+
+fn completion_event(role: MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "UserMessageComplete",
+        MessageRole::Agent => "AgentMessageComplete",
+        MessageRole::Thought => "AgentThoughtComplete",
+    }
+}
+
+/// Receives each event with its payload
+pub type EventSink = Arc<dyn Fn(&str, Dictionary) -> Result<()> + Send + Sync>;
+
+/// Fires events as autocommands in `group`
+fn autocommands(group: String) -> EventSink {
+    Arc::new(move |command, data| {
+        let opts = ExecAutocmdsOpts::builder()
+            .data(data)
+            .group(group.as_str())
+            .build();
+
+        nvim_oxi::api::exec_autocmds([command], &opts).map_err(AcpError::into_internal_error)
+    })
+}
 
 #[derive(Clone)]
 pub struct EventHandler {
-    group: String,
+    sink: EventSink,
+    commands: Arc<Mutex<CommandRegistry>>,
+    config_options: Arc<Mutex<ConfigOptionTracker>>,
+    modes: Arc<Mutex<ModeTracker>>,
+    tool_calls: Arc<Mutex<ToolCallTracker>>,
+    transcript: Arc<Mutex<Transcript>>,
+    history: Option<History>,
+    /// Sessions whose history the agent is streaming back while they are loaded
+    replaying: Arc<Mutex<Vec<SessionId>>>,
 }
 
 impl EventHandler {
     pub fn new(group: String) -> Self {
-        Self { group }
+        Self::with_sink(autocommands(group))
+    }
+
+    /// Hands events to `sink` rather than firing autocommands, e.g. to collect them
+    pub fn with_sink(sink: EventSink) -> Self {
+        Self {
+            sink,
+            commands: Arc::new(Mutex::new(CommandRegistry::new())),
+            config_options: Arc::new(Mutex::new(ConfigOptionTracker::new())),
+            modes: Arc::new(Mutex::new(ModeTracker::new())),
+            tool_calls: Arc::new(Mutex::new(ToolCallTracker::new())),
+            transcript: Arc::new(Mutex::new(Transcript::new())),
+            history: None,
+            replaying: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Records the session events in [`RECORDED_EVENTS`] to disk as they are fired
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    pub fn commands(&self) -> Arc<Mutex<CommandRegistry>> {
+        self.commands.clone()
+    }
+
+    pub fn config_options(&self) -> Arc<Mutex<ConfigOptionTracker>> {
+        self.config_options.clone()
+    }
+
+    pub fn modes(&self) -> Arc<Mutex<ModeTracker>> {
+        self.modes.clone()
+    }
+
+    pub fn tool_calls(&self) -> Arc<Mutex<ToolCallTracker>> {
+        self.tool_calls.clone()
+    }
+
+    pub fn transcript(&self) -> Arc<Mutex<Transcript>> {
+        self.transcript.clone()
+    }
+
+    fn emit_message(
+        &self,
+        session_id: &SessionId,
+        message: Message,
+        stop_reason: Option<StopReason>,
+    ) -> Result<()> {
+        let command = completion_event(message.role);
+        let mut data = parse::message_event(message)?;
+        if let Some(stop_reason) = stop_reason {
+            data.insert("stopReason", parse::enum_name(&stop_reason)?);
+        }
+        self.emit(command, session_id, data)
+    }
+
+    /// Records a prompt sent by the user in the session transcript
+    ///
+    /// The prompt is complete as sent, so it fires `UserMessageComplete` right away, after the
+    /// message it ends if one was still streamed.
+    pub fn record_prompt(&self, session_id: &SessionId, content: Vec<ContentBlock>) -> Result<()> {
+        let message = Message::new(MessageRole::User, content);
+        if let Some(completed) = locked(&self.transcript, |transcript| {
+            transcript.record(session_id, message.clone())
+        })? {
+            self.emit_message(session_id, completed, None)?;
+        }
+        self.emit_message(session_id, message, None)
+    }
+
+    /// Flags the session's events with `replay = true` until [`Self::finish_replay`]
+    ///
+    /// Replayed events are already in the session's history, so they aren't recorded again.
+    pub fn start_replay(&self, session_id: &SessionId) -> Result<()> {
+        locked(&self.replaying, |replaying| {
+            if !replaying.contains(session_id) {
+                replaying.push(session_id.clone());
+            }
+        })
+    }
+
+    /// Completes the last replayed message, then stops flagging the session's events
+    pub fn finish_replay(&self, session_id: &SessionId) -> Result<()> {
+        let completed = locked(&self.transcript, |transcript| {
+            transcript.complete(session_id)
+        })?;
+        let result = match completed {
+            Some(message) => self.emit_message(session_id, message, None),
+            None => Ok(()),
+        };
+        locked(&self.replaying, |replaying| {
+            replaying.retain(|replaying| replaying != session_id)
+        })?;
+        result
+    }
+
+    /// Records the modes a session reported when it was created or loaded
+    pub fn record_modes(&self, session_id: &SessionId, modes: SessionModeState) -> Result<()> {
+        locked(&self.modes, |tracker| tracker.record(session_id, modes))
+    }
+
+    /// Records the configuration options a session reported, replacing the previous ones
+    pub fn record_config_options(
+        &self,
+        session_id: &SessionId,
+        options: Vec<SessionConfigOption>,
+    ) -> Result<()> {
+        locked(&self.config_options, |tracker| {
+            tracker.update(session_id, options)
+        })
+    }
+
+    /// Drops what was tracked for a session that can't be used anymore, its transcript is kept
+    pub fn forget_session(&self, session_id: &SessionId) -> Result<()> {
+        locked(&self.commands, |registry| registry.clear(session_id))?;
+        locked(&self.modes, |tracker| tracker.clear(session_id))?;
+        locked(&self.config_options, |tracker| tracker.clear(session_id))?;
+        locked(&self.tool_calls, |tracker| tracker.clear(session_id))
+    }
+
+    /// Fired before Hermes launches an agent
+    pub fn agent_connecting(&self, connection: &ConnectionInfo, protocol: &Protocol) -> Result<()> {
+        let mut data = agent_data(connection);
+        data.insert("protocol", protocol.to_string());
+        self.exec("AgentConnecting", data)
+    }
+
+    /// Fired once the agent answered `initialize`
+    pub fn agent_connected(
+        &self,
+        connection: &ConnectionInfo,
+        response: InitializeResponse,
+    ) -> Result<()> {
+        let mut data = parse::initialize_response(response)?;
+        for (key, value) in agent_data(connection) {
+            data.insert(key, value);
+        }
+        self.exec("AgentConnected", data)
+    }
+
+    pub fn agent_connection_failed(&self, connection: &ConnectionInfo, error: &str) -> Result<()> {
+        let mut data = agent_data(connection);
+        data.insert("error", error);
+        self.exec("AgentConnectionFailed", data)
+    }
+
+    /// Fired after the connection closed, along with the sessions that can't be used anymore
+    pub fn agent_disconnected(
+        &self,
+        connection: &ConnectionInfo,
+        sessions: &[SessionId],
+    ) -> Result<()> {
+        let mut data = agent_data(connection);
+        data.insert(
+            "sessions",
+            nvim_oxi::Array::from_iter(sessions.iter().map(ToString::to_string)),
+        );
+        self.exec("AgentDisconnected", data)
+    }
+
+    pub fn session_created(
+        &self,
+        connection: &ConnectionInfo,
+        session_id: &SessionId,
+        cwd: &Path,
+    ) -> Result<()> {
+        self.emit("SessionCreated", session_id, session_data(connection, cwd))
+    }
+
+    pub fn session_loaded(
+        &self,
+        connection: &ConnectionInfo,
+        session_id: &SessionId,
+        cwd: &Path,
+    ) -> Result<()> {
+        self.emit("SessionLoaded", session_id, session_data(connection, cwd))
+    }
+
+    pub fn prompt_started(
+        &self,
+        connection: &ConnectionInfo,
+        session_id: &SessionId,
+    ) -> Result<()> {
+        self.emit("PromptStarted", session_id, agent_data(connection))
+    }
+
+    /// Fired when a turn ends, with the stop reason or the error the prompt failed with
+    pub fn prompt_finished(
+        &self,
+        connection: &ConnectionInfo,
+        session_id: &SessionId,
+        outcome: std::result::Result<StopReason, &str>,
+    ) -> Result<()> {
+        let mut data = agent_data(connection);
+        match outcome {
+            Ok(stop_reason) => data.insert("stopReason", parse::enum_name(&stop_reason)?),
+            Err(error) => data.insert("error", error),
+        }
+        self.emit("PromptFinished", session_id, data)
+    }
+
+    /// Lets the user know an agent needs them to log in with one of its authentication methods
+    pub fn auth_required(
+        &self,
+        connection: &ConnectionInfo,
+        session_id: Option<&SessionId>,
+        methods: Vec<AuthMethod>,
+    ) -> Result<()> {
+        let mut data = agent_data(connection);
+        data.insert(
+            "methods",
+            nvim_oxi::Array::from_iter(
+                methods
+                    .into_iter()
+                    .map(parse::auth_method)
+                    .collect::<Result<Vec<Dictionary>>>()?,
+            ),
+        );
+        match session_id {
+            Some(session_id) => self.emit("AgentAuthRequired", session_id, data),
+            None => self.exec("AgentAuthRequired", data),
+        }
+    }
+
+    /// Fired once a permission request was answered, by the user or the permission policy
+    pub fn permission_decided(
+        &self,
+        request: &RequestPermissionRequest,
+        response: &RequestPermissionResponse,
+    ) -> Result<()> {
+        let session_id = request.session_id.clone();
+        let data = parse::permission_event(request.clone(), response)?;
+        self.emit("PermissionDecided", &session_id, data)
+    }
+
+    /// Completes the message streamed during a turn once the agent reports why it stopped
+    pub fn complete_turn(&self, session_id: &SessionId, stop_reason: StopReason) -> Result<()> {
+        match locked(&self.transcript, |transcript| {
+            transcript.complete(session_id)
+        })? {
+            Some(message) => self.emit_message(session_id, message, Some(stop_reason)),
+            None => Ok(()),
+        }
+    }
+
+    fn transcribe(
+        &self,
+        session_id: &SessionId,
+        role: MessageRole,
+        block: &ContentBlock,
+    ) -> Result<Option<Message>> {
+        locked(&self.transcript, |transcript| {
+            transcript.push(session_id, role, block.clone())
+        })
+    }
+
+    fn emit(&self, command: &str, session_id: &SessionId, mut data: Dictionary) -> Result<()> {
+        data.insert("sessionId", session_id.to_string());
+        if locked(&self.replaying, |replaying| replaying.contains(session_id))? {
+            data.insert("replay", true);
+        } else if let Some(history) = &self.history
+            && RECORDED_EVENTS.contains(&command)
+        {
+            let entry = HistoryEntry::new(command, parse::dictionary_to_json(data.clone()));
+            history
+                .record(session_id, &entry)
+                .map_err(AcpError::into_internal_error)?;
+        }
+        self.exec(command, data)
+    }
+
+    fn exec(&self, command: &str, data: Dictionary) -> Result<()> {
+        (self.sink)(command, data)
+    }
+
+    fn track<F>(&self, apply: F) -> Result<Option<ToolCall>>
+    where
+        F: FnOnce(&mut ToolCallTracker) -> Option<ToolCall>,
+    {
+        locked(&self.tool_calls, apply)
     }
 }
 
 impl Default for EventHandler {
     fn default() -> Self {
-        Self {
-            group: "Hermes".to_string(),
-        }
+        Self::new("Hermes".to_string())
     }
 }
 
+/// Fired for updates Hermes does not understand so the notification still succeeds
+const UNKNOWN_UPDATE: &str = "AgentUnknownUpdate";
+
+fn chunk_event(source: &str, content_type: &str, kind: &str) -> String {
+    if content_type == parse::UNKNOWN {
+        UNKNOWN_UPDATE.to_string()
+    } else {
+        format!("{}{}{}", source, content_type, kind)
+    }
+}
+
+fn unknown_tool_call_content(
+    tool_call_id: &ToolCallId,
+    content: &[ToolCallContent],
+) -> Result<Vec<Dictionary>> {
+    content
+        .iter()
+        .filter(|content| {
+            !matches!(
+                content,
+                ToolCallContent::Content(_)
+                    | ToolCallContent::Diff(_)
+                    | ToolCallContent::Terminal(_)
+            )
+        })
+        .map(|content| {
+            parse::unknown_event("tool_call_content", content).map(|mut dict| {
+                dict.insert("toolCallId", tool_call_id.to_string());
+                dict
+            })
+        })
+        .collect()
+}
+
+/// Parses a tool call into event data, resolving the buffers of any open files
+pub fn tool_call_data(tool_call: ToolCall) -> Result<Dictionary> {
+    parse::tool_call_event(tool_call).map(|mut dict| {
+        buffers::attach_buffer_numbers(&mut dict);
+        dict
+    })
+}
+
 #[async_trait::async_trait(?Send)]
 impl Client for EventHandler {
     async fn request_permission(
@@ -37,40 +448,103 @@ impl Client for EventHandler {
     }
 
     async fn session_notification(&self, args: SessionNotification) -> Result<()> {
-        let group = self.group.to_string();
-
-        let (mut data, command) =
-            match args.update {
-                SessionUpdate::UserMessageChunk(chunk) => parse::communication(chunk.content)
-                    .map(|(dict, t)| (dict, format!("User{}Message", t))),
-                SessionUpdate::AgentMessageChunk(chunk) => parse::communication(chunk.content)
-                    .map(|(dict, t)| (dict, format!("Agent{}Message", t))),
-                SessionUpdate::AgentThoughtChunk(chunk) => parse::communication(chunk.content)
-                    .map(|(dict, t)| (dict, format!("Agent{}Thought", t))),
-                SessionUpdate::ToolCall(tool_call) => parse::tool_call_event(tool_call)
-                    .map(|dict| (dict, "AgentToolCall".to_string())),
-                SessionUpdate::ToolCallUpdate(update) => parse::tool_call_update_event(update)
-                    .map(|dict| (dict, "AgentToolCallUpdate".to_string())),
-                SessionUpdate::Plan(plan) => {
-                    parse::plan_event(plan).map(|dict| (dict, "AgentPlan".to_string()))
-                }
-                SessionUpdate::AvailableCommandsUpdate(update) => {
-                    parse::available_commands_event(update)
-                        .map(|dict| (dict, "AgentAvailableCommands".to_string()))
+        let session_id = args.session_id;
+        let mut changed = None;
+        let mut completed = None;
+        let mut unknown = Vec::new();
+
+        let (data, command) = match args.update {
+            SessionUpdate::UserMessageChunk(chunk) => {
+                completed = self.transcribe(&session_id, MessageRole::User, &chunk.content)?;
+                parse::communication(chunk.content)
+                    .map(|(dict, t)| (dict, chunk_event("User", &t, "Message")))
+            }
+            SessionUpdate::AgentMessageChunk(chunk) => {
+                completed = self.transcribe(&session_id, MessageRole::Agent, &chunk.content)?;
+                parse::communication(chunk.content)
+                    .map(|(dict, t)| (dict, chunk_event("Agent", &t, "Message")))
+            }
+            SessionUpdate::AgentThoughtChunk(chunk) => {
+                completed = self.transcribe(&session_id, MessageRole::Thought, &chunk.content)?;
+                parse::communication(chunk.content)
+                    .map(|(dict, t)| (dict, chunk_event("Agent", &t, "Thought")))
+            }
+            SessionUpdate::ToolCall(tool_call) => {
+                unknown = unknown_tool_call_content(&tool_call.tool_call_id, &tool_call.content)?;
+                changed =
+                    self.track(|tracker| Some(tracker.record(&session_id, tool_call.clone())))?;
+                tool_call_data(tool_call).map(|dict| (dict, "AgentToolCall".to_string()))
+            }
+            SessionUpdate::ToolCallUpdate(update) => {
+                if let Some(content) = &update.fields.content {
+                    unknown = unknown_tool_call_content(&update.tool_call_id, content)?;
                 }
-                SessionUpdate::CurrentModeUpdate(update) => parse::current_mode_event(update)
-                    .map(|dict| (dict, "AgentCurrentMode".to_string())),
-                SessionUpdate::ConfigOptionUpdate(update) => parse::config_option_event(update)
-                    .map(|dict| (dict, "AgentConfigOption".to_string())),
-                _ => return Err(AcpError::method_not_found()),
-            }?;
+                changed = self.track(|tracker| tracker.apply(&session_id, update.clone()))?;
+                parse::tool_call_update_event(update).map(|mut dict| {
+                    buffers::attach_buffer_numbers(&mut dict);
+                    (dict, "AgentToolCallUpdate".to_string())
+                })
+            }
+            SessionUpdate::Plan(plan) => {
+                parse::plan_event(plan).map(|dict| (dict, "AgentPlan".to_string()))
+            }
+            SessionUpdate::AvailableCommandsUpdate(update) => {
+                locked(&self.commands, |commands| {
+                    commands.update(&session_id, update.available_commands.clone())
+                })?;
+                parse::available_commands_event(update)
+                    .map(|dict| (dict, "AgentAvailableCommands".to_string()))
+            }
+            SessionUpdate::CurrentModeUpdate(update) => {
+                locked(&self.modes, |modes| {
+                    modes.set_current(&session_id, update.current_mode_id.clone())
+                })?;
+                parse::current_mode_event(update).map(|dict| (dict, "AgentCurrentMode".to_string()))
+            }
+            SessionUpdate::ConfigOptionUpdate(update) => {
+                self.record_config_options(&session_id, update.config_options.clone())?;
+                parse::config_option_event(update)
+                    .map(|dict| (dict, "AgentConfigOption".to_string()))
+            }
+            // Updates the library can't decode at all arrive through `ext_notification`
+            other => parse::unknown_event("session_update", &other)
+                .map(|dict| (dict, UNKNOWN_UPDATE.to_string())),
+        }?;
 
-        data.insert("sessionId", args.session_id.to_string());
+        if let Some(message) = completed {
+            self.emit_message(&session_id, message, None)?;
+        }
+
+        self.emit(&command, &session_id, data)?;
 
-        let opts = ExecAutocmdsOpts::builder().data(data).group(group).build();
+        for data in unknown {
+            self.emit(UNKNOWN_UPDATE, &session_id, data)?;
+        }
 
-        nvim_oxi::api::exec_autocmds([command.as_str()], &opts)
-            .map_err(AcpError::into_internal_error)
+        match changed {
+            Some(tool_call) => self.emit(
+                "AgentToolCallChanged",
+                &session_id,
+                tool_call_data(tool_call)?,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Fires `AgentUnknownUpdate` for the updates passed on since they failed to decode
+    async fn ext_notification(&self, args: ExtNotification) -> Result<()> {
+        if *args.method != *UNKNOWN_UPDATE_METHOD {
+            return Ok(());
+        }
+        let params =
+            serde_json::from_str(args.params.get()).map_err(AcpError::into_internal_error)?;
+        let update = UnknownUpdate::from_params(&params).ok_or_else(AcpError::invalid_params)?;
+        let session_id = update.session_id.clone();
+        self.emit(
+            UNKNOWN_UPDATE,
+            &session_id,
+            parse::unknown_update_event(update)?,
+        )
     }
 
     async fn write_text_file(&self, _args: WriteTextFileRequest) -> Result<WriteTextFileResponse> {
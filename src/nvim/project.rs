@@ -0,0 +1,205 @@
+use crate::nvim::{parse::json_to_object, setup::Setup};
+use nvim_oxi::{Dictionary, Object, conversion::FromObject, lua::Error};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Project configuration files looked up in the workspace root, in order of preference
+pub const FILES: [&str; 2] = [".hermes.json", ".hermes.toml"];
+
+/// The nearest ancestor of `start` holding a `.git` directory or a project configuration file,
+/// falling back to `start` itself
+pub fn workspace_root(start: &Path) -> PathBuf {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists() || find(dir).is_some())
+        .unwrap_or(start)
+        .to_path_buf()
+}
+
+/// The project configuration file in `root`, if there is one
+pub fn find(root: &Path) -> Option<PathBuf> {
+    FILES
+        .iter()
+        .map(|file| root.join(file))
+        .find(|path| path.is_file())
+}
+
+/// Parses a project configuration, TOML when the file ends in `.toml` and JSON otherwise
+pub fn parse(path: &Path, contents: &str) -> Result<Dictionary, Error> {
+    let value: serde_json::Value = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str(contents).map_err(|e| invalid(path, e.to_string()))?
+    } else {
+        serde_json::from_str(contents).map_err(|e| invalid(path, e.to_string()))?
+    };
+    match json_to_object(value) {
+        object if object.is_nil() => Ok(Dictionary::new()),
+        object => Dictionary::from_object(object)
+            .map_err(|_| invalid(path, "expected a table at the top level".to_string())),
+    }
+}
+
+/// Reads and parses a project configuration file
+pub fn load(path: &Path) -> Result<Dictionary, Error> {
+    let contents = std::fs::read_to_string(path).map_err(|e| invalid(path, e.to_string()))?;
+    parse(path, &contents)
+}
+
+fn invalid(path: &Path, message: String) -> Error {
+    Error::RuntimeError(format!(
+        "Invalid project config {}: {}",
+        path.display(),
+        message
+    ))
+}
+
+/// The SHA-256 of a project configuration, in hex
+pub fn content_hash(contents: &str) -> String {
+    Sha256::digest(contents.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// A workspace and the hash of the configuration the user trusted or ignored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Decided {
+    root: PathBuf,
+    hash: String,
+}
+
+impl Decided {
+    fn is(&self, root: &Path, hash: &str) -> bool {
+        self.root == root && self.hash == hash
+    }
+}
+
+/// Workspaces the user has been asked about, so each project is only prompted for once
+///
+/// Trust is given to or withheld from a configuration rather than a workspace, the user is asked
+/// again when it changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    #[serde(default)]
+    trusted: Vec<Decided>,
+    #[serde(default)]
+    ignored: Vec<Decided>,
+}
+
+impl TrustStore {
+    /// Loads the store, a missing file is an empty store
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                serde_json::from_str(&contents).map_err(|e| invalid(path, e.to_string()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(invalid(path, e.to_string())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| invalid(path, e.to_string()))?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|e| invalid(path, e.to_string()))?;
+        std::fs::write(path, contents).map_err(|e| invalid(path, e.to_string()))
+    }
+
+    /// Whether the workspace's configuration with the given hash is trusted, `None` when the
+    /// user hasn't decided yet or decided on a different configuration
+    pub fn decision(&self, root: &Path, hash: &str) -> Option<bool> {
+        if self.trusted.iter().any(|trusted| trusted.is(root, hash)) {
+            Some(true)
+        } else if self.ignored.iter().any(|ignored| ignored.is(root, hash)) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a different configuration of the workspace was trusted before
+    pub fn changed(&self, root: &Path, hash: &str) -> bool {
+        self.trusted
+            .iter()
+            .any(|trusted| trusted.root == root && trusted.hash != hash)
+    }
+
+    /// Records a decision, replacing any earlier one for the workspace
+    pub fn record(&mut self, root: &Path, hash: &str, trusted: bool) {
+        self.trusted.retain(|trusted| trusted.root != root);
+        self.ignored.retain(|ignored| ignored.root != root);
+        let decided = Decided {
+            root: root.to_path_buf(),
+            hash: hash.to_string(),
+        };
+        if trusted {
+            self.trusted.push(decided);
+        } else {
+            self.ignored.push(decided);
+        }
+    }
+}
+
+fn trust_store_path() -> Result<PathBuf, Error> {
+    let data: String = nvim_oxi::api::call_function("stdpath", ("data",))
+        .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    Ok(PathBuf::from(data).join("hermes").join("trust.json"))
+}
+
+fn ask_trust(path: &Path, changed: bool) -> Result<bool, Error> {
+    let found = if changed {
+        format!(
+            "The project config at {} changed since it was trusted",
+            path.display()
+        )
+    } else {
+        format!("Hermes found a project config at {}", path.display())
+    };
+    let message = format!(
+        "{}.\nIt can define agent commands that will be run. Trust it?",
+        found
+    );
+    let choice: i64 =
+        nvim_oxi::api::call_function("confirm", (message, "&Trust\n&Ignore", Object::from(2)))
+            .map_err(|e| Error::RuntimeError(e.to_string()))?;
+    Ok(choice == 1)
+}
+
+/// Merges the configuration of the project containing the current directory over `setup`
+///
+/// The user is asked whether to trust a workspace the first time its configuration is found, and
+/// again whenever a trusted configuration changed, and the answer is remembered. Returns the file
+/// that was applied, if any.
+pub fn apply(setup: &mut Setup) -> Result<Option<PathBuf>, Error> {
+    let cwd = std::env::current_dir().map_err(|e| Error::RuntimeError(e.to_string()))?;
+    let root = workspace_root(&cwd);
+    let Some(path) = find(&root) else {
+        return Ok(None);
+    };
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| invalid(&path, e.to_string()))?;
+    let hash = content_hash(&contents);
+    let store_path = trust_store_path()?;
+    let mut store = TrustStore::load(&store_path)?;
+    let trusted = match store.decision(&root, &hash) {
+        Some(trusted) => trusted,
+        None => {
+            let trusted = ask_trust(&path, store.changed(&root, &hash))?;
+            store.record(&root, &hash, trusted);
+            store.save(&store_path)?;
+            trusted
+        }
+    };
+    if !trusted {
+        return Ok(None);
+    }
+
+    let options = parse(&path, &contents)?;
+    setup.merge_project(options, &root).map_err(|e| match e {
+        Error::RuntimeError(message) => invalid(&path, message),
+        other => other,
+    })?;
+    Ok(Some(path))
+}
@@ -0,0 +1,131 @@
+use agent_client_protocol::{
+    AudioContent, ContentBlock, EmbeddedResource, EmbeddedResourceResource, ResourceLink,
+    TextContent, TextResourceContents,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use nvim_oxi::{Dictionary, Object, ObjectKind, conversion::FromObject, lua::Error};
+use std::{fs, path::Path};
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::RuntimeError(message.into())
+}
+
+fn string_field(table: &Dictionary, key: &str) -> Result<Option<String>, Error> {
+    table
+        .get(key)
+        .map(|value: &Object| {
+            String::from_object(value.clone())
+                .map_err(|_| invalid(format!("Invalid input for \"{}\", must be a string", key)))
+        })
+        .transpose()
+}
+
+fn required_field(table: &Dictionary, key: &str, block_type: &str) -> Result<String, Error> {
+    string_field(table, key)?.ok_or_else(|| {
+        invalid(format!(
+            "Missing \"{}\" for content of type \"{}\"",
+            key, block_type
+        ))
+    })
+}
+
+/// Guesses the mime type of an audio file from its extension
+pub fn audio_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "wav" => Some("audio/wav"),
+        "mp3" => Some("audio/mpeg"),
+        "ogg" | "oga" => Some("audio/ogg"),
+        "flac" => Some("audio/flac"),
+        "m4a" => Some("audio/mp4"),
+        "aac" => Some("audio/aac"),
+        "webm" => Some("audio/webm"),
+        _ => None,
+    }
+}
+
+/// Reads an audio file from disk and encodes it as an audio content block
+pub fn audio_file(path: &Path, mime_type: Option<String>) -> Result<AudioContent, Error> {
+    let mime_type = mime_type
+        .or_else(|| audio_mime_type(path).map(str::to_string))
+        .ok_or_else(|| {
+            invalid(format!(
+                "Unable to determine the audio format of \"{}\", provide a \"mimeType\"",
+                path.display()
+            ))
+        })?;
+    let bytes = fs::read(path)
+        .map_err(|e| invalid(format!("Unable to read \"{}\": {}", path.display(), e)))?;
+    Ok(AudioContent::new(STANDARD.encode(bytes), mime_type))
+}
+
+fn audio_block(table: &Dictionary) -> Result<ContentBlock, Error> {
+    let mime_type = string_field(table, "mimeType")?;
+    let audio = match (string_field(table, "path")?, string_field(table, "data")?) {
+        (Some(path), _) => audio_file(Path::new(&path), mime_type)?,
+        (None, Some(data)) => AudioContent::new(
+            data,
+            mime_type
+                .ok_or_else(|| invalid("Missing \"mimeType\" for content of type \"audio\""))?,
+        ),
+        (None, None) => {
+            return Err(invalid(
+                "Content of type \"audio\" requires either a \"path\" or \"data\"",
+            ));
+        }
+    };
+    Ok(ContentBlock::Audio(audio))
+}
+
+fn resource_block(table: &Dictionary) -> Result<ContentBlock, Error> {
+    let contents = TextResourceContents::new(
+        required_field(table, "text", "resource")?,
+        required_field(table, "uri", "resource")?,
+    )
+    .mime_type(string_field(table, "mimeType")?);
+    Ok(ContentBlock::Resource(EmbeddedResource::new(
+        EmbeddedResourceResource::TextResourceContents(contents),
+    )))
+}
+
+fn resource_link_block(table: &Dictionary) -> Result<ContentBlock, Error> {
+    let uri = required_field(table, "uri", "resource_link")?;
+    let name = string_field(table, "name")?.unwrap_or_else(|| uri.clone());
+    Ok(ContentBlock::ResourceLink(
+        ResourceLink::new(name, uri)
+            .description(string_field(table, "description")?)
+            .mime_type(string_field(table, "mimeType")?),
+    ))
+}
+
+/// Converts a single Lua prompt item into a content block
+///
+/// Plain strings are sent as text, tables are dispatched on their `type` field.
+pub fn content_block(item: Object) -> Result<ContentBlock, Error> {
+    match item.kind() {
+        ObjectKind::String => Ok(ContentBlock::Text(TextContent::new(
+            String::from_object(item).map_err(|e| invalid(e.to_string()))?,
+        ))),
+        ObjectKind::Dictionary => {
+            let table = Dictionary::from_object(item).map_err(|e| invalid(e.to_string()))?;
+            let block_type = string_field(&table, "type")?.unwrap_or_else(|| "text".to_string());
+            match block_type.as_str() {
+                "text" => Ok(ContentBlock::Text(TextContent::new(required_field(
+                    &table, "text", "text",
+                )?))),
+                "audio" => audio_block(&table),
+                "resource" => resource_block(&table),
+                "resource_link" => resource_link_block(&table),
+                other => Err(invalid(format!("Unsupported content type \"{}\"", other))),
+            }
+        }
+        _ => Err(invalid(
+            "Invalid prompt content, must be a string or a table",
+        )),
+    }
+}
+
+/// Converts a list of Lua prompt items into content blocks
+pub fn content_blocks(items: Vec<Object>) -> Result<Vec<ContentBlock>, Error> {
+    items.into_iter().map(content_block).collect()
+}
@@ -0,0 +1,413 @@
+use crate::apc::{
+    client::ClientConfig,
+    connection::{AgentDefinition, Assistant},
+    mcp,
+    permissions::{PermissionPolicy, PermissionRule},
+};
+use agent_client_protocol::{
+    EnvVariable, HttpHeader, McpServer, McpServerHttp, McpServerSse, McpServerStdio, ToolKind,
+};
+use nvim_oxi::{
+    Array, Dictionary, Object,
+    conversion::FromObject,
+    lua::{Error, Poppable, ffi::State},
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+const OPTIONS: [&str; 7] = [
+    "client",
+    "capabilities",
+    "agents",
+    "fs_roots",
+    "permissions",
+    "mcp_servers",
+    "editor_mcp",
+];
+
+/// Projects can't change what the client identifies as or widen its capabilities
+const PROJECT_OPTIONS: [&str; 4] = ["agents", "fs_roots", "permissions", "mcp_servers"];
+
+const MCP_TRANSPORTS: [&str; 3] = ["stdio", "http", "sse"];
+
+const TOOL_KINDS: [(&str, ToolKind); 10] = [
+    ("read", ToolKind::Read),
+    ("edit", ToolKind::Edit),
+    ("delete", ToolKind::Delete),
+    ("move", ToolKind::Move),
+    ("search", ToolKind::Search),
+    ("execute", ToolKind::Execute),
+    ("think", ToolKind::Think),
+    ("fetch", ToolKind::Fetch),
+    ("switch_mode", ToolKind::SwitchMode),
+    ("other", ToolKind::Other),
+];
+
+/// Everything that can be configured through `hermes.setup()`
+#[derive(Debug, Clone, Default)]
+pub struct Setup {
+    pub client: ClientConfig,
+    pub agents: HashMap<Assistant, AgentDefinition>,
+    pub permissions: PermissionPolicy,
+    pub fs_roots: Vec<PathBuf>,
+    /// Passed to every session, sorted by name
+    pub mcp_servers: Vec<McpServer>,
+    /// Whether sessions get the MCP server exposing the editor's state
+    pub editor_mcp: bool,
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::RuntimeError(message.into())
+}
+
+fn field<T: FromObject>(value: &Object, path: &str, expected: &str) -> Result<T, Error> {
+    T::from_object(value.clone()).map_err(|_| {
+        invalid(format!(
+            "Invalid input for \"{}\", must be {}",
+            path, expected
+        ))
+    })
+}
+
+fn table(value: &Object, path: &str) -> Result<Dictionary, Error> {
+    match Array::from_object(value.clone()) {
+        // Lua can't tell an empty table from an empty list
+        Ok(list) if list.is_empty() => Ok(Dictionary::new()),
+        _ => field(value, path, "a table"),
+    }
+}
+
+/// Iterates over the entries of a table, rejecting keys that are not in `allowed`
+fn entries<'a>(
+    table: &'a Dictionary,
+    path: &str,
+    allowed: &[&str],
+) -> Result<Vec<(String, &'a Object)>, Error> {
+    table
+        .iter()
+        .map(|(key, value)| {
+            let key = key.to_string();
+            if allowed.contains(&key.as_str()) {
+                Ok((key, value))
+            } else {
+                Err(invalid(format!(
+                    "Unknown option \"{}{}\", expected one of: {}",
+                    path,
+                    key,
+                    allowed.join(", ")
+                )))
+            }
+        })
+        .collect()
+}
+
+fn client(value: &Object, mut config: ClientConfig) -> Result<ClientConfig, Error> {
+    for (key, value) in entries(&table(value, "client")?, "client.", &["name", "version"])? {
+        let path = format!("client.{}", key);
+        match key.as_str() {
+            "name" => config.name = field(value, &path, "a string")?,
+            _ => config.version = field(value, &path, "a string")?,
+        }
+    }
+    Ok(config)
+}
+
+fn capabilities(value: &Object, mut config: ClientConfig) -> Result<ClientConfig, Error> {
+    let table = table(value, "capabilities")?;
+    for (key, value) in entries(
+        &table,
+        "capabilities.",
+        &["fs_read", "fs_write", "terminal"],
+    )? {
+        let path = format!("capabilities.{}", key);
+        let enabled = field(value, &path, "a boolean")?;
+        match key.as_str() {
+            "fs_read" => config.fs_read_access = enabled,
+            "fs_write" => config.fs_write_access = enabled,
+            _ => config.terminal_access = enabled,
+        }
+    }
+    Ok(config)
+}
+
+fn agent(value: &Object, path: &str) -> Result<AgentDefinition, Error> {
+    let table = table(value, path)?;
+    let prefix = format!("{}.", path);
+    let mut definition = AgentDefinition::default();
+    let mut command = None;
+    for (key, value) in entries(&table, &prefix, &["command", "args", "env"])? {
+        let path = format!("{}{}", prefix, key);
+        match key.as_str() {
+            "command" => command = Some(field::<String>(value, &path, "a string")?),
+            "args" => definition.args = field(value, &path, "a list of strings")?,
+            _ => {
+                definition.env = table_of(value, &path)?
+                    .into_iter()
+                    .map(|(name, value)| {
+                        field(&value, &format!("{}.{}", path, name), "a string")
+                            .map(|value| (name, value))
+                    })
+                    .collect::<Result<HashMap<String, String>, Error>>()?
+            }
+        }
+    }
+    definition.command =
+        command.ok_or_else(|| invalid(format!("Missing \"{}command\"", prefix)))?;
+    Ok(definition)
+}
+
+fn table_of(value: &Object, path: &str) -> Result<Vec<(String, Object)>, Error> {
+    Ok(table(value, path)?
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect())
+}
+
+fn agents(value: &Object) -> Result<HashMap<Assistant, AgentDefinition>, Error> {
+    table_of(value, "agents")?
+        .into_iter()
+        .map(|(name, value)| {
+            agent(&value, &format!("agents.{}", name))
+                .map(|definition| (Assistant::from(name), definition))
+        })
+        .collect()
+}
+
+/// A table of names to string values, sorted by name
+fn string_table(value: &Object, path: &str) -> Result<Vec<(String, String)>, Error> {
+    let mut entries = table_of(value, path)?
+        .into_iter()
+        .map(|(name, value)| {
+            field(&value, &format!("{}.{}", path, name), "a string").map(|value| (name, value))
+        })
+        .collect::<Result<Vec<(String, String)>, Error>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+fn mcp_server(name: String, value: &Object, path: &str) -> Result<McpServer, Error> {
+    let table = table(value, path)?;
+    let prefix = format!("{}.", path);
+    let transport = match table.get("type") {
+        Some(value) => {
+            let transport: String = field(value, &format!("{}type", prefix), "a string")?;
+            if !MCP_TRANSPORTS.contains(&transport.as_str()) {
+                return Err(invalid(format!(
+                    "Invalid input for \"{}type\", must be one of: {}",
+                    prefix,
+                    MCP_TRANSPORTS.join(", ")
+                )));
+            }
+            transport
+        }
+        None => "stdio".to_string(),
+    };
+    let allowed: &[&str] = if transport == "stdio" {
+        &["type", "command", "args", "env"]
+    } else {
+        &["type", "url", "headers"]
+    };
+    let entries = entries(&table, &prefix, allowed)?;
+    let required = |key: &str| {
+        entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .ok_or_else(|| invalid(format!("Missing \"{}{}\"", prefix, key)))
+            .and_then(|(_, value)| {
+                field::<String>(value, &format!("{}{}", prefix, key), "a string")
+            })
+    };
+    let optional = |key: &str| {
+        entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| *value)
+    };
+
+    if transport == "stdio" {
+        let args = optional("args")
+            .map(|value| field(value, &format!("{}args", prefix), "a list of strings"))
+            .transpose()?
+            .unwrap_or_default();
+        let env = optional("env")
+            .map(|value| string_table(value, &format!("{}env", prefix)))
+            .transpose()?
+            .unwrap_or_default();
+        return Ok(McpServer::Stdio(
+            McpServerStdio::new(name, required("command")?)
+                .args(args)
+                .env(
+                    env.into_iter()
+                        .map(|(name, value)| EnvVariable::new(name, value))
+                        .collect(),
+                ),
+        ));
+    }
+    let headers: Vec<HttpHeader> = optional("headers")
+        .map(|value| string_table(value, &format!("{}headers", prefix)))
+        .transpose()?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, value)| HttpHeader::new(name, value))
+        .collect();
+    let url = required("url")?;
+    Ok(if transport == "http" {
+        McpServer::Http(McpServerHttp::new(name, url).headers(headers))
+    } else {
+        McpServer::Sse(McpServerSse::new(name, url).headers(headers))
+    })
+}
+
+/// Parses MCP server definitions keyed by name, sorted by name
+///
+/// Servers are launched by the agent over stdio unless `type` says otherwise, `http` and `sse`
+/// servers are reached at their `url`.
+pub fn mcp_servers(value: &Object, path: &str) -> Result<Vec<McpServer>, Error> {
+    let mut servers = table_of(value, path)?;
+    servers.sort_by(|(a, _), (b, _)| a.cmp(b));
+    servers
+        .into_iter()
+        .map(|(name, value)| {
+            let path = format!("{}.{}", path, name);
+            mcp_server(name, &value, &path)
+        })
+        .collect()
+}
+
+fn rule(value: &Object, path: &str) -> Result<PermissionRule, Error> {
+    let name: String = field(value, path, "a string")?;
+    PermissionRule::from_name(&name).ok_or_else(|| {
+        invalid(format!(
+            "Invalid input for \"{}\", must be one of: {}",
+            path,
+            PermissionRule::NAMES.join(", ")
+        ))
+    })
+}
+
+fn fs_roots(value: &Object, base: &Path) -> Result<Vec<PathBuf>, Error> {
+    let roots: Vec<String> = match Array::from_object(value.clone()) {
+        Ok(list) if list.is_empty() => Vec::new(),
+        _ => field(value, "fs_roots", "a list of strings")?,
+    };
+    Ok(roots.into_iter().map(|root| base.join(root)).collect())
+}
+
+/// The parts of `roots` that lie within `limits`
+///
+/// An empty list of roots allows everything, so having nothing in common is an error rather than
+/// an empty list.
+fn within(roots: &[PathBuf], limits: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    if roots.is_empty() {
+        return Ok(limits.to_vec());
+    }
+    if limits.is_empty() {
+        return Ok(roots.to_vec());
+    }
+    let mut common: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        for limit in limits {
+            let inner = if root.starts_with(limit) {
+                root
+            } else if limit.starts_with(root) {
+                limit
+            } else {
+                continue;
+            };
+            if !common.contains(inner) {
+                common.push(inner.clone());
+            }
+        }
+    }
+    if common.is_empty() {
+        return Err(invalid(
+            "\"fs_roots\" must lie within the ones passed to setup",
+        ));
+    }
+    Ok(common)
+}
+
+fn permissions(value: &Object, base: PermissionPolicy) -> Result<PermissionPolicy, Error> {
+    let table = table(value, "permissions")?;
+    let allowed: Vec<&str> = std::iter::once("default")
+        .chain(TOOL_KINDS.iter().map(|(name, _)| *name))
+        .collect();
+    let entries = entries(&table, "permissions.", &allowed)?;
+    let default = entries
+        .iter()
+        .find(|(key, _)| key == "default")
+        .map(|(_, value)| rule(value, "permissions.default"))
+        .transpose()?;
+    let base = match default {
+        Some(default) => base.with_default(default),
+        None => base,
+    };
+    entries
+        .iter()
+        .filter(|(key, _)| key != "default")
+        .try_fold(base, |policy, (key, value)| {
+            let kind = TOOL_KINDS
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, kind)| *kind)
+                .unwrap_or_default();
+            rule(value, &format!("permissions.{}", key)).map(|rule| policy.rule(kind, rule))
+        })
+}
+
+impl Setup {
+    /// Validates the options passed to `hermes.setup()`, anything not provided keeps its default
+    ///
+    /// Relative filesystem roots are resolved against the current directory.
+    pub fn from_dictionary(options: Dictionary) -> Result<Self, Error> {
+        let cwd = std::env::current_dir().map_err(|e| invalid(e.to_string()))?;
+        let mut setup = Setup::default();
+        setup.merge(options, &OPTIONS, &cwd)?;
+        Ok(setup)
+    }
+
+    /// Layers a project's configuration over this one
+    ///
+    /// Agents and MCP servers are merged by name, with the project's taking precedence. Permission
+    /// rules from the project can only tighten the ones configured before, e.g. turn "allow" into
+    /// "ask" but not the other way around. Filesystem roots from the project are resolved against
+    /// the project root and can only narrow the ones configured before: what lies outside all of
+    /// them is dropped.
+    pub fn merge_project(&mut self, options: Dictionary, root: &Path) -> Result<(), Error> {
+        let user_roots = self.fs_roots.clone();
+        let user_permissions = self.permissions.clone();
+        self.merge(options, &PROJECT_OPTIONS, root)?;
+        self.fs_roots = within(&self.fs_roots, &user_roots)?;
+        self.permissions = user_permissions.tightened_by(&self.permissions);
+        Ok(())
+    }
+
+    fn merge(&mut self, options: Dictionary, allowed: &[&str], base: &Path) -> Result<(), Error> {
+        for (key, value) in entries(&options, "", allowed)? {
+            match key.as_str() {
+                "client" => self.client = client(value, self.client.clone())?,
+                "capabilities" => self.client = capabilities(value, self.client.clone())?,
+                "agents" => self.agents.extend(agents(value)?),
+                "fs_roots" => self.fs_roots = fs_roots(value, base)?,
+                "editor_mcp" => self.editor_mcp = field(value, "editor_mcp", "a boolean")?,
+                "mcp_servers" => {
+                    let mut servers =
+                        mcp::merge(&self.mcp_servers, mcp_servers(value, "mcp_servers")?);
+                    servers.sort_by(|a, b| mcp::name(a).cmp(mcp::name(b)));
+                    self.mcp_servers = servers;
+                }
+                _ => self.permissions = permissions(value, self.permissions.clone())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Poppable for Setup {
+    unsafe fn pop(state: *mut State) -> Result<Self, Error> {
+        let options = unsafe { Dictionary::pop(state)? };
+        Self::from_dictionary(options)
+    }
+}
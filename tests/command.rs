@@ -0,0 +1,209 @@
+use agent_client_protocol::{
+    ContentBlock, SessionConfigValueId, SessionId, SessionModeId, TextContent,
+};
+use hermes::{
+    apc::{
+        connection::Assistant,
+        transcript::{Message, MessageRole},
+    },
+    nvim::command::{self, Completion, Subcommand},
+};
+
+struct Source;
+
+impl Completion for Source {
+    fn agents(&self) -> Vec<String> {
+        vec![
+            "copilot".to_string(),
+            "gemini".to_string(),
+            "opencode".to_string(),
+        ]
+    }
+
+    fn connections(&self) -> Vec<String> {
+        vec![
+            "copilot-1".to_string(),
+            "opencode-2".to_string(),
+            "review".to_string(),
+        ]
+    }
+
+    fn sessions(&self) -> Vec<String> {
+        vec!["sess-1".to_string(), "sess-2".to_string()]
+    }
+
+    fn modes(&self, session_id: Option<&str>) -> Vec<String> {
+        match session_id {
+            Some("sess-2") => vec!["review".to_string()],
+            _ => vec!["ask".to_string(), "code".to_string()],
+        }
+    }
+
+    fn models(&self, _session_id: Option<&str>) -> Vec<String> {
+        vec!["fast".to_string(), "smart".to_string()]
+    }
+}
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(ToString::to_string).collect()
+}
+
+fn parse(line: &str) -> Result<Subcommand, String> {
+    let sessions = [SessionId::new("sess-1"), SessionId::new("sess-2")];
+    Subcommand::parse(&args(line), &sessions).map_err(|e| match e {
+        nvim_oxi::lua::Error::RuntimeError(message) => message,
+        other => other.to_string(),
+    })
+}
+
+#[test]
+fn test_parse_connect() {
+    assert_eq!(parse("connect"), Ok(Subcommand::Connect(None, None)));
+    assert_eq!(
+        parse("connect gemini"),
+        Ok(Subcommand::Connect(
+            Some(Assistant::Custom("gemini".to_string())),
+            None
+        ))
+    );
+    assert_eq!(
+        parse("connect copilot review"),
+        Ok(Subcommand::Connect(
+            Some(Assistant::Copilot),
+            Some("review".to_string())
+        ))
+    );
+    assert_eq!(
+        parse("disconnect copilot-1"),
+        Ok(Subcommand::Disconnect(Some("copilot-1".to_string())))
+    );
+    assert!(parse("connect a b c").is_err());
+    assert!(parse("disconnect a b").is_err());
+}
+
+#[test]
+fn test_parse_prompt_with_and_without_session() {
+    assert_eq!(
+        parse("prompt fix the tests"),
+        Ok(Subcommand::Prompt(None, "fix the tests".to_string()))
+    );
+    assert_eq!(
+        parse("prompt sess-2 fix the tests"),
+        Ok(Subcommand::Prompt(
+            Some(SessionId::new("sess-2")),
+            "fix the tests".to_string()
+        ))
+    );
+    assert_eq!(
+        parse("prompt"),
+        Err("Missing the text to send with \"prompt\"".to_string())
+    );
+}
+
+#[test]
+fn test_parse_mode_and_model() {
+    assert_eq!(parse("mode"), Ok(Subcommand::Mode(None, None)));
+    assert_eq!(
+        parse("mode code"),
+        Ok(Subcommand::Mode(None, Some(SessionModeId::new("code"))))
+    );
+    assert_eq!(
+        parse("mode sess-1 code"),
+        Ok(Subcommand::Mode(
+            Some(SessionId::new("sess-1")),
+            Some(SessionModeId::new("code"))
+        ))
+    );
+    assert_eq!(
+        parse("model sess-1 fast"),
+        Ok(Subcommand::Model(
+            Some(SessionId::new("sess-1")),
+            Some(SessionConfigValueId::new("fast"))
+        ))
+    );
+    assert!(parse("mode code review").is_err());
+}
+
+#[test]
+fn test_parse_session_commands() {
+    assert_eq!(parse("cancel"), Ok(Subcommand::Cancel(None)));
+    assert_eq!(
+        parse("log sess-1"),
+        Ok(Subcommand::Log(Some(SessionId::new("sess-1"))))
+    );
+    assert_eq!(
+        parse("log sess-9"),
+        Err("Unknown session sess-9".to_string())
+    );
+    assert_eq!(parse("sessions"), Ok(Subcommand::Sessions));
+    assert_eq!(parse("status"), Ok(Subcommand::Status));
+}
+
+#[test]
+fn test_parse_unknown_subcommand() {
+    let usage =
+        "Usage: :Hermes {connect|disconnect|status|prompt|cancel|mode|model|sessions|log} [args]";
+    assert_eq!(parse("launch"), Err(usage.to_string()));
+    assert_eq!(parse(""), Err(usage.to_string()));
+}
+
+#[test]
+fn test_complete_subcommands() {
+    assert_eq!(command::complete("", "Hermes ", &Source).len(), 9);
+    assert_eq!(
+        command::complete("s", "Hermes s", &Source),
+        vec!["status", "sessions"]
+    );
+}
+
+#[test]
+fn test_complete_agents_and_sessions() {
+    assert_eq!(
+        command::complete("", "Hermes connect ", &Source),
+        vec!["copilot", "gemini", "opencode"]
+    );
+    assert_eq!(
+        command::complete("o", "Hermes disconnect o", &Source),
+        vec!["opencode-2"]
+    );
+    assert_eq!(
+        command::complete("", "Hermes log ", &Source),
+        vec!["sess-1", "sess-2"]
+    );
+    assert!(command::complete("", "Hermes connect copilot ", &Source).is_empty());
+}
+
+#[test]
+fn test_complete_modes() {
+    assert_eq!(
+        command::complete("", "Hermes mode ", &Source),
+        vec!["sess-1", "sess-2", "ask", "code"]
+    );
+    assert_eq!(
+        command::complete("", "Hermes mode sess-2 ", &Source),
+        vec!["review"]
+    );
+    assert_eq!(
+        command::complete("sm", "Hermes model sess-1 sm", &Source),
+        vec!["smart"]
+    );
+    assert!(command::complete("", "Hermes mode code ", &Source).is_empty());
+}
+
+#[test]
+fn test_log_lines() {
+    let messages = vec![
+        Message::new(
+            MessageRole::User,
+            vec![ContentBlock::Text(TextContent::new("Hello"))],
+        ),
+        Message::new(
+            MessageRole::Agent,
+            vec![ContentBlock::Text(TextContent::new("Hi\nthere"))],
+        ),
+    ];
+    assert_eq!(
+        command::log_lines(&messages),
+        vec!["## user", "Hello", "", "## agent", "Hi", "there", ""]
+    );
+}
@@ -0,0 +1,89 @@
+use agent_client_protocol::{
+    AvailableCommand, AvailableCommandInput, SessionId, UnstructuredCommandInput,
+};
+use hermes::apc::commands::{CommandRegistry, invocation};
+
+fn session() -> SessionId {
+    SessionId::new("session_1")
+}
+
+fn review() -> AvailableCommand {
+    AvailableCommand::new("review", "Review the current changes").input(
+        AvailableCommandInput::Unstructured(UnstructuredCommandInput::new("focus area")),
+    )
+}
+
+#[test]
+fn test_commands_for_unknown_session_is_empty() {
+    let registry = CommandRegistry::new();
+    assert!(registry.commands(&session()).is_empty());
+}
+
+#[test]
+fn test_update_records_commands() {
+    let mut registry = CommandRegistry::new();
+    registry.update(&session(), vec![review()]);
+
+    let commands = registry.commands(&session());
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].name, "review");
+}
+
+#[test]
+fn test_update_replaces_previous_commands() {
+    let mut registry = CommandRegistry::new();
+    registry.update(&session(), vec![review()]);
+    registry.update(
+        &session(),
+        vec![AvailableCommand::new("plan", "Create a plan")],
+    );
+
+    let commands = registry.commands(&session());
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].name, "plan");
+}
+
+#[test]
+fn test_commands_are_kept_per_session() {
+    let mut registry = CommandRegistry::new();
+    registry.update(&session(), vec![review()]);
+
+    assert!(registry.commands(&SessionId::new("session_2")).is_empty());
+}
+
+#[test]
+fn test_get_by_name() {
+    let mut registry = CommandRegistry::new();
+    registry.update(&session(), vec![review()]);
+
+    assert!(registry.get(&session(), "review").is_some());
+    assert!(registry.get(&session(), "/review").is_some());
+    assert!(registry.get(&session(), "plan").is_none());
+}
+
+#[test]
+fn test_clear_removes_commands() {
+    let mut registry = CommandRegistry::new();
+    registry.update(&session(), vec![review()]);
+    registry.clear(&session());
+
+    assert!(registry.commands(&session()).is_empty());
+}
+
+#[test]
+fn test_invocation_without_input() {
+    assert_eq!(invocation(&review(), None), "/review");
+}
+
+#[test]
+fn test_invocation_with_input() {
+    assert_eq!(
+        invocation(&review(), Some("error handling")),
+        "/review error handling"
+    );
+}
+
+#[test]
+fn test_invocation_ignores_blank_input() {
+    assert_eq!(invocation(&review(), Some("  ")), "/review");
+}
@@ -0,0 +1,109 @@
+use agent_client_protocol::{
+    SessionConfigId, SessionConfigOption, SessionConfigSelectGroup, SessionConfigSelectOption,
+    SessionConfigValueId, SessionId,
+};
+use hermes::apc::config_options::{ConfigOptionTracker, values};
+
+fn session() -> SessionId {
+    SessionId::new("session_1")
+}
+
+fn model() -> SessionConfigOption {
+    SessionConfigOption::select(
+        SessionConfigId::new("model"),
+        "Model",
+        SessionConfigValueId::new("fast"),
+        vec![
+            SessionConfigSelectOption::new(SessionConfigValueId::new("fast"), "Fast"),
+            SessionConfigSelectOption::new(SessionConfigValueId::new("smart"), "Smart"),
+        ],
+    )
+}
+
+#[test]
+fn test_options_for_unknown_session_is_empty() {
+    let tracker = ConfigOptionTracker::new();
+    assert!(tracker.options(&session()).is_empty());
+}
+
+#[test]
+fn test_update_records_options() {
+    let mut tracker = ConfigOptionTracker::new();
+    tracker.update(&session(), vec![model()]);
+
+    assert_eq!(tracker.options(&session()), vec![model()]);
+    assert!(
+        tracker
+            .get(&session(), &SessionConfigId::new("model"))
+            .is_some()
+    );
+    assert!(
+        tracker
+            .get(&session(), &SessionConfigId::new("effort"))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_update_replaces_previous_options() {
+    let mut tracker = ConfigOptionTracker::new();
+    tracker.update(&session(), vec![model()]);
+    tracker.update(&session(), vec![]);
+
+    assert!(tracker.options(&session()).is_empty());
+}
+
+#[test]
+fn test_clear_removes_options() {
+    let mut tracker = ConfigOptionTracker::new();
+    tracker.update(&session(), vec![model()]);
+    tracker.clear(&session());
+
+    assert!(tracker.options(&session()).is_empty());
+}
+
+#[test]
+fn test_values_of_ungrouped_select() {
+    let option = model();
+    assert_eq!(
+        values(&option),
+        vec![
+            &SessionConfigValueId::new("fast"),
+            &SessionConfigValueId::new("smart"),
+        ]
+    );
+}
+
+#[test]
+fn test_values_of_grouped_select() {
+    let option = SessionConfigOption::select(
+        SessionConfigId::new("model"),
+        "Model",
+        SessionConfigValueId::new("gpt"),
+        vec![
+            SessionConfigSelectGroup::new(
+                "openai",
+                "OpenAI",
+                vec![SessionConfigSelectOption::new(
+                    SessionConfigValueId::new("gpt"),
+                    "GPT",
+                )],
+            ),
+            SessionConfigSelectGroup::new(
+                "anthropic",
+                "Anthropic",
+                vec![SessionConfigSelectOption::new(
+                    SessionConfigValueId::new("sonnet"),
+                    "Sonnet",
+                )],
+            ),
+        ],
+    );
+    assert_eq!(
+        values(&option),
+        vec![
+            &SessionConfigValueId::new("gpt"),
+            &SessionConfigValueId::new("sonnet"),
+        ]
+    );
+}
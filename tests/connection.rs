@@ -0,0 +1,170 @@
+use agent_client_protocol::{
+    AgentNotification, Client, ClientSide, RequestPermissionRequest, RequestPermissionResponse,
+    SessionId, SessionNotification, Side,
+};
+use async_trait::async_trait;
+use hermes::apc::{
+    connection::{
+        AgentDefinition, Assistant, ConnectionDetails, ConnectionId, ConnectionInfo,
+        ConnectionManager, Protocol,
+        unknown::{self, UnknownUpdate},
+    },
+    error::Error,
+};
+use hermes::{ApcClient, ClientConfig};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+#[test]
+fn test_builtin_agents() {
+    assert_eq!(Assistant::from("copilot"), Assistant::Copilot);
+    assert_eq!(Assistant::from("OpenCode"), Assistant::Opencode);
+}
+
+#[test]
+fn test_custom_agent() {
+    let agent = Assistant::from("gemini");
+    assert_eq!(agent, Assistant::Custom("gemini".to_string()));
+    assert_eq!(agent.to_string(), "gemini");
+}
+
+#[test]
+fn test_builtin_definitions() {
+    assert_eq!(
+        AgentDefinition::builtin(&Assistant::Copilot),
+        Some(AgentDefinition::new(
+            "node",
+            ["copilot-language-server", "--acp"]
+        ))
+    );
+    assert_eq!(
+        AgentDefinition::builtin(&Assistant::Opencode),
+        Some(AgentDefinition::new("opencode", ["apc"]))
+    );
+    assert_eq!(
+        AgentDefinition::builtin(&Assistant::Custom("gemini".to_string())),
+        None
+    );
+}
+
+#[test]
+fn test_generated_connection_ids_are_unique() {
+    let first = ConnectionId::generate(&Assistant::Copilot);
+    let second = ConnectionId::generate(&Assistant::Copilot);
+    assert_ne!(first, second);
+    assert!(first.to_string().starts_with("copilot-"));
+    assert!(second.to_string().starts_with("copilot-"));
+}
+
+#[test]
+fn test_connection_matches_id_label_or_agent() {
+    let connection = ConnectionInfo {
+        id: ConnectionId::from("copilot-4"),
+        agent: Assistant::Copilot,
+        label: Some("review".to_string()),
+    };
+    assert!(connection.matches("copilot-4"));
+    assert!(connection.matches("review"));
+    assert!(connection.matches("copilot"));
+    assert!(!connection.matches("opencode"));
+    assert!(!connection.matches("copilot-5"));
+}
+
+#[test]
+fn test_unknown_session_update_is_passed_on_as_an_extension() {
+    let line = r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"session-1","update":{"sessionUpdate":"usage_update","used":10}}}"#;
+
+    let rewritten: Value = serde_json::from_str(&unknown::rewrite(line).unwrap()).unwrap();
+    assert_eq!(rewritten["method"], json!("_hermes/unknown_update"));
+    let params = serde_json::value::to_raw_value(&rewritten["params"]).unwrap();
+    match ClientSide::decode_notification("_hermes/unknown_update", Some(&params)).unwrap() {
+        AgentNotification::ExtNotification(notification) => {
+            assert_eq!(&*notification.method, unknown::UNKNOWN_UPDATE_METHOD);
+        }
+        other => panic!("Unexpected notification {:?}", other),
+    }
+    assert_eq!(
+        UnknownUpdate::from_params(&rewritten["params"]),
+        Some(UnknownUpdate {
+            session_id: SessionId::new("session-1"),
+            kind: "session_update",
+            value: json!({ "sessionUpdate": "usage_update", "used": 10 }),
+            tool_call_id: None,
+        })
+    );
+}
+
+#[test]
+fn test_unknown_content_block_is_passed_on_as_an_extension() {
+    let line = r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"session-1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"video","uri":"file:///tmp/demo.mp4"}}}}"#;
+
+    let rewritten: Value = serde_json::from_str(&unknown::rewrite(line).unwrap()).unwrap();
+    let update = UnknownUpdate::from_params(&rewritten["params"]).unwrap();
+    assert_eq!(update.kind, "content_block");
+    assert_eq!(
+        update.value,
+        json!({ "type": "video", "uri": "file:///tmp/demo.mp4" })
+    );
+}
+
+#[test]
+fn test_known_messages_are_passed_on_as_is() {
+    let update = r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"session-1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"Hello"}}}}"#;
+    let response = r#"{"jsonrpc":"2.0","id":1,"result":{"stopReason":"end_turn"}}"#;
+
+    assert_eq!(unknown::rewrite(update), None);
+    assert_eq!(unknown::rewrite(response), None);
+    assert_eq!(unknown::rewrite("not json"), None);
+}
+
+/// Never reached, no agent gets connected
+struct Unused;
+
+#[async_trait(?Send)]
+impl Client for Unused {
+    async fn request_permission(
+        &self,
+        _args: RequestPermissionRequest,
+    ) -> agent_client_protocol::Result<RequestPermissionResponse> {
+        Err(agent_client_protocol::Error::method_not_found())
+    }
+
+    async fn session_notification(
+        &self,
+        _args: SessionNotification,
+    ) -> agent_client_protocol::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+#[allow(clippy::arc_with_non_send_sync)]
+fn test_unsupported_protocols_fail_to_connect() {
+    let mut manager =
+        ConnectionManager::new(Arc::new(ApcClient::new(ClientConfig::default(), Unused))).unwrap();
+
+    for protocol in [Protocol::Http, Protocol::Socket] {
+        let result = manager.connect(ConnectionDetails {
+            agent: Assistant::Opencode,
+            protocol: protocol.clone(),
+            label: None,
+        });
+
+        match result {
+            Err(Error::Connection(message)) => assert_eq!(
+                message,
+                format!("{} connections are not supported", protocol)
+            ),
+            other => panic!("expected a connection error, got {:?}", other),
+        }
+    }
+    assert!(manager.connections().is_empty());
+    let status = manager.status();
+    let tried = status.lock().unwrap().connections();
+    assert_eq!(tried.len(), 1);
+    assert_eq!(tried[0].1.protocol, Protocol::Socket);
+    assert_eq!(
+        tried[0].1.last_error.as_deref(),
+        Some("Connection error: socket connections are not supported")
+    );
+}
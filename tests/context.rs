@@ -0,0 +1,194 @@
+use agent_client_protocol::{ContentBlock, EmbeddedResourceResource, TextContent};
+use hermes::apc::context::{ContextRegistry, Mention, mentions};
+use hermes::nvim::context::{
+    diagnostics_text, diff_args, quickfix_text, symbol_links, text_resource,
+};
+use serde_json::json;
+use std::path::Path;
+
+fn text(text: &str) -> ContentBlock {
+    ContentBlock::Text(TextContent::new(text))
+}
+
+fn resource_text(block: &ContentBlock) -> &str {
+    match block {
+        ContentBlock::Resource(resource) => match &resource.resource {
+            EmbeddedResourceResource::TextResourceContents(contents) => &contents.text,
+            _ => panic!("expected text contents"),
+        },
+        _ => panic!("expected a resource block"),
+    }
+}
+
+fn echo(argument: Option<&str>, _: &Path) -> Result<Vec<ContentBlock>, String> {
+    Ok(vec![text_resource(
+        "test://echo",
+        argument.unwrap_or("nothing"),
+        "text/plain",
+    )])
+}
+
+fn registry() -> ContextRegistry {
+    let mut registry = ContextRegistry::new();
+    registry.register("echo", echo);
+    registry
+}
+
+#[test]
+fn test_mentions_with_and_without_argument() {
+    assert_eq!(
+        mentions("Fix @diagnostics in @buffer:src/main.rs please"),
+        vec![
+            Mention {
+                provider: "diagnostics".to_string(),
+                argument: None,
+            },
+            Mention {
+                provider: "buffer".to_string(),
+                argument: Some("src/main.rs".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_mentions_ignore_addresses_and_bare_at() {
+    assert!(mentions("Mail user@example.com about @ and @!").is_empty());
+}
+
+#[test]
+fn test_mentions_empty_argument_is_none() {
+    assert_eq!(mentions("@diff:")[0].argument, None);
+}
+
+#[test]
+fn test_register_replaces_by_name() {
+    let mut registry = registry();
+    registry.register("echo", |_: Option<&str>, _: &Path| Ok(Vec::new()));
+    registry.register("diff", echo);
+
+    assert_eq!(registry.names(), vec!["diff", "echo"]);
+    assert_eq!(
+        registry
+            .expand(vec![text("@echo")], Path::new("/"))
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_unregister() {
+    let mut registry = registry();
+
+    assert!(registry.unregister("echo"));
+    assert!(!registry.unregister("echo"));
+    assert!(!registry.contains("echo"));
+}
+
+#[test]
+fn test_expand_appends_content_after_text() {
+    let expanded = registry()
+        .expand(
+            vec![text("Look at @echo:this"), text("and @echo")],
+            Path::new("/"),
+        )
+        .unwrap();
+
+    assert_eq!(expanded.len(), 4);
+    assert_eq!(expanded[0], text("Look at @echo:this"));
+    assert_eq!(resource_text(&expanded[1]), "this");
+    assert_eq!(expanded[2], text("and @echo"));
+    assert_eq!(resource_text(&expanded[3]), "nothing");
+}
+
+#[test]
+fn test_expand_skips_unknown_and_repeated_mentions() {
+    let expanded = registry()
+        .expand(vec![text("@echo @someone @echo")], Path::new("/"))
+        .unwrap();
+
+    assert_eq!(expanded.len(), 2);
+}
+
+#[test]
+fn test_expand_reports_provider_errors() {
+    let mut registry = registry();
+    registry.register("broken", |_: Option<&str>, _: &Path| {
+        Err("no LSP client".to_string())
+    });
+
+    let error = registry
+        .expand(vec![text("@broken")], Path::new("/"))
+        .unwrap_err();
+    assert!(error.to_string().contains("@broken failed: no LSP client"));
+}
+
+#[test]
+fn test_expand_passes_the_working_directory() {
+    let mut registry = ContextRegistry::new();
+    registry.register("cwd", |_: Option<&str>, cwd: &Path| {
+        Ok(vec![text(&cwd.to_string_lossy())])
+    });
+
+    let expanded = registry
+        .expand(vec![text("@cwd")], Path::new("/project"))
+        .unwrap();
+    assert_eq!(expanded[1], text("/project"));
+}
+
+#[test]
+fn test_diff_args_tell_paths_from_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("main.rs"), "").unwrap();
+
+    assert_eq!(diff_args(None, dir.path()).unwrap(), ["diff", "--no-color"]);
+    assert_eq!(
+        diff_args(Some("main.rs"), dir.path()).unwrap(),
+        ["diff", "--no-color", "--", "main.rs"]
+    );
+    assert_eq!(
+        diff_args(Some("HEAD~1"), dir.path()).unwrap(),
+        ["diff", "--no-color", "HEAD~1", "--"]
+    );
+}
+
+#[test]
+fn test_diff_args_refuse_options() {
+    let error = diff_args(Some("--output=/tmp/owned"), Path::new("/")).unwrap_err();
+    assert!(error.contains("is not a revision or path"), "{}", error);
+}
+
+#[test]
+fn test_diagnostics_text() {
+    let diagnostics = json!([
+        {"path": "/src/main.rs", "line": 3, "column": 5, "severity": "ERROR", "message": "unused", "source": "rustc"},
+        {"path": "/src/lib.rs", "line": 1, "column": 1, "severity": "WARN", "message": "missing docs"},
+    ]);
+
+    assert_eq!(
+        diagnostics_text(&diagnostics),
+        "/src/main.rs:3:5: ERROR: unused [rustc]\n/src/lib.rs:1:1: WARN: missing docs"
+    );
+}
+
+#[test]
+fn test_quickfix_text() {
+    let items = json!([{"path": "/src/main.rs", "line": 7, "column": 2, "text": "expected `;`"}]);
+
+    assert_eq!(quickfix_text(&items), "/src/main.rs:7:2: expected `;`");
+}
+
+#[test]
+fn test_symbol_links() {
+    let symbols = json!([{"name": "Setup", "kind": "Struct", "path": "/src/setup.rs", "line": 12}]);
+
+    match &symbol_links(&symbols)[..] {
+        [ContentBlock::ResourceLink(link)] => {
+            assert_eq!(link.name, "Setup");
+            assert_eq!(link.uri, "file:///src/setup.rs");
+            assert_eq!(link.description.as_deref(), Some("Struct at line 12"));
+        }
+        _ => panic!("expected a single resource link"),
+    }
+}
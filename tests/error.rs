@@ -0,0 +1,13 @@
+use hermes::apc::error::Error;
+
+#[test]
+fn test_auth_required_error_is_detected() {
+    let error = Error::from(agent_client_protocol::Error::auth_required());
+    assert!(matches!(error, Error::AuthRequired(_)));
+}
+
+#[test]
+fn test_other_errors_are_internal() {
+    let error = Error::from(agent_client_protocol::Error::method_not_found());
+    assert!(matches!(error, Error::Internal(_)));
+}
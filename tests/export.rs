@@ -0,0 +1,119 @@
+use agent_client_protocol::SessionId;
+use hermes::apc::history::HistoryEntry;
+use hermes::nvim::export::{Format, format_timestamp, json, markdown};
+use serde_json::{Value, json};
+
+fn entry(timestamp: u64, event: &str, data: Value) -> HistoryEntry {
+    HistoryEntry {
+        timestamp,
+        event: event.to_string(),
+        data,
+    }
+}
+
+fn session() -> Vec<HistoryEntry> {
+    vec![
+        entry(
+            1_760_862_600,
+            "SessionCreated",
+            json!({"agent": "copilot", "connection": "copilot-1", "cwd": "/work"}),
+        ),
+        entry(1, "UserMessageComplete", json!({"text": "Fix the build"})),
+        entry(
+            2,
+            "AgentThoughtComplete",
+            json!({"text": "Look at main.rs\n\nthen fix"}),
+        ),
+        entry(
+            3,
+            "AgentPlan",
+            json!({"entries": [
+                {"content": "Read the error", "status": "completed"},
+                {"content": "Edit main.rs", "status": "in_progress"},
+            ]}),
+        ),
+        entry(
+            4,
+            "AgentToolCall",
+            json!({"id": "call_1", "title": "Edit", "kind": "edit", "status": "pending",
+                   "locations": [{"path": "/work/main.rs", "line": 3}]}),
+        ),
+        entry(
+            5,
+            "PermissionDecided",
+            json!({"outcome": "selected", "name": "Allow once", "kind": "allow_once",
+                   "toolCall": {"id": "call_1", "title": "Edit main.rs"}}),
+        ),
+        entry(
+            6,
+            "AgentToolCallUpdate",
+            json!({"id": "call_1", "title": "Edit main.rs", "status": "completed"}),
+        ),
+        entry(7, "AgentMessageComplete", json!({"text": "Fixed."})),
+        entry(8, "PromptFinished", json!({"stopReason": "end_turn"})),
+    ]
+}
+
+#[test]
+fn test_format_from_name() {
+    assert_eq!(Format::from_name("markdown"), Some(Format::Markdown));
+    assert_eq!(Format::from_name("json"), Some(Format::Json));
+    assert_eq!(Format::from_name("html"), None);
+}
+
+#[test]
+fn test_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(format_timestamp(1_760_862_600), "2025-10-19 08:30:00 UTC");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+}
+
+#[test]
+fn test_markdown() {
+    let expected = "\
+# Session session-1
+
+- Agent: copilot (copilot-1)
+- Directory: /work
+- Started: 2025-10-19 08:30:00 UTC
+
+## User
+
+Fix the build
+
+### Thought
+
+> Look at main.rs
+>
+> then fix
+
+### Plan
+
+- [x] Read the error
+- [ ] Edit main.rs (in_progress)
+
+### Tool call: Edit main.rs
+
+- Kind: edit
+- Status: completed
+- Location: /work/main.rs:3
+
+**Permission:** Allow once (allow_once) for Edit main.rs
+
+## Agent
+
+Fixed.
+
+_Turn ended: end_turn_
+";
+
+    assert_eq!(markdown(&SessionId::new("session-1"), &session()), expected);
+}
+
+#[test]
+fn test_json_round_trips() {
+    let entries = session();
+    let exported: Vec<HistoryEntry> = serde_json::from_str(&json(&entries)).unwrap();
+
+    assert_eq!(exported, entries);
+}
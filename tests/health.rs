@@ -0,0 +1,201 @@
+use agent_client_protocol::{Implementation, InitializeResponse, ProtocolVersion};
+use hermes::{
+    apc::{
+        connection::{AgentDefinition, Assistant, ConnectionId, Protocol},
+        status::AgentStatus,
+    },
+    nvim::health::{self, Level},
+};
+use std::{ffi::OsString, path::Path, time::Duration};
+
+fn executable(dir: &Path, name: &str) {
+    let path = dir.join(name);
+    std::fs::write(&path, "#!/bin/sh\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+fn path(dir: &Path) -> OsString {
+    std::env::join_paths([dir]).unwrap()
+}
+
+#[test]
+fn test_find_executable_on_path() {
+    let dir = tempfile::tempdir().unwrap();
+    executable(dir.path(), "opencode");
+    let path = path(dir.path());
+
+    assert_eq!(
+        health::find_executable("opencode", Some(&path)),
+        Some(dir.path().join("opencode"))
+    );
+    assert_eq!(health::find_executable("gemini", Some(&path)), None);
+    assert_eq!(health::find_executable("opencode", None), None);
+}
+
+#[test]
+fn test_find_executable_by_path() {
+    let dir = tempfile::tempdir().unwrap();
+    executable(dir.path(), "agent");
+    let agent = dir.path().join("agent");
+
+    assert_eq!(
+        health::find_executable(agent.to_str().unwrap(), None),
+        Some(agent)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_skips_files_that_cannot_run() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("opencode"), "").unwrap();
+
+    assert_eq!(
+        health::find_executable("opencode", Some(&path(dir.path()))),
+        None
+    );
+}
+
+#[test]
+fn test_interpreted_agent_checks_its_script() {
+    let dir = tempfile::tempdir().unwrap();
+    executable(dir.path(), "node");
+    let path = path(dir.path());
+    let definition = AgentDefinition::builtin(&Assistant::Copilot).unwrap();
+
+    let reports = health::agent_reports(&Assistant::Copilot, &definition, false, Some(&path));
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].level, Level::Ok);
+    assert_eq!(reports[1].level, Level::Warn);
+    assert_eq!(
+        reports[1].message,
+        "copilot: `copilot-language-server` not found on PATH"
+    );
+
+    executable(dir.path(), "copilot-language-server");
+    let reports = health::agent_reports(&Assistant::Copilot, &definition, false, Some(&path));
+    assert!(reports.iter().all(|report| report.level == Level::Ok));
+}
+
+#[test]
+fn test_missing_configured_agent_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let definition = AgentDefinition::new("gemini", ["--experimental-acp"]);
+    let agent = Assistant::from("gemini");
+
+    let reports = health::agent_reports(&agent, &definition, true, Some(&path(dir.path())));
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].level, Level::Error);
+    assert_eq!(
+        reports[0].advice,
+        vec!["Install it or set agents.gemini.command in hermes.setup()"]
+    );
+}
+
+#[test]
+fn test_protocol_report() {
+    let response = InitializeResponse::new(ProtocolVersion::LATEST)
+        .agent_info(Implementation::new("opencode", "0.9.0"));
+    let report = health::protocol_report(&ConnectionId::from("opencode-1"), &response);
+    assert_eq!(report.level, Level::Ok);
+    assert_eq!(
+        report.message,
+        "opencode-1 (opencode 0.9.0): protocol version 1"
+    );
+
+    let report = health::protocol_report(
+        &ConnectionId::from("opencode-1"),
+        &InitializeResponse::new(ProtocolVersion::V0),
+    );
+    assert_eq!(report.level, Level::Warn);
+    assert_eq!(
+        report.message,
+        "opencode-1: negotiated protocol version 0, Hermes speaks 1"
+    );
+}
+
+#[test]
+fn test_transport_report() {
+    let connection = ConnectionId::from("opencode-1");
+    let status = AgentStatus {
+        agent: Assistant::Opencode,
+        ..AgentStatus::default()
+    };
+    assert_eq!(health::transport_report(&connection, &status), None);
+
+    let status = AgentStatus {
+        protocol: Protocol::Http,
+        ..status
+    };
+    let report = health::transport_report(&connection, &status).unwrap();
+    assert_eq!(report.level, Level::Error);
+    assert_eq!(
+        report.message,
+        "opencode-1: http connections are not supported"
+    );
+}
+
+#[test]
+fn test_neovim_and_augroup_reports() {
+    assert_eq!(health::neovim_report(true).level, Level::Ok);
+    assert_eq!(health::neovim_report(false).level, Level::Error);
+    assert_eq!(
+        health::augroup_report(true).message,
+        "Autocommand group \"hermes\" exists"
+    );
+    assert_eq!(health::augroup_report(false).level, Level::Error);
+}
+
+#[test]
+fn test_url_address() {
+    assert_eq!(
+        health::url_address("https://mcp.example.com/sse"),
+        Some("mcp.example.com:443".to_string())
+    );
+    assert_eq!(
+        health::url_address("http://user@localhost:8080/mcp?x=1"),
+        Some("localhost:8080".to_string())
+    );
+    assert_eq!(
+        health::url_address("http://[::1]/mcp"),
+        Some("[::1]:80".to_string())
+    );
+    assert_eq!(health::url_address("localhost:8080"), None);
+    assert_eq!(health::url_address("ftp://example.com"), None);
+}
+
+#[test]
+fn test_url_report() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+    let report = health::url_report("docs", &url, Duration::from_secs(2));
+    assert_eq!(report.level, Level::Ok);
+
+    drop(listener);
+    let report = health::url_report("docs", &url, Duration::from_secs(2));
+    assert_eq!(report.level, Level::Error);
+    assert!(
+        report.message.starts_with("docs: can't connect to"),
+        "{}",
+        report.message
+    );
+
+    let report = health::url_report("docs", "localhost:8080", Duration::from_secs(2));
+    assert_eq!(report.level, Level::Error);
+}
+
+#[test]
+fn test_socket_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("hermes.sock");
+    let report = health::socket_report("editor MCP server", &socket, Duration::from_secs(2));
+    assert_eq!(report.level, Level::Error);
+
+    let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    let report = health::socket_report("editor MCP server", &socket, Duration::from_secs(2));
+    assert_eq!(report.level, Level::Ok);
+}
@@ -0,0 +1,160 @@
+use agent_client_protocol::SessionId;
+use hermes::apc::history::{History, HistoryEntry, SessionRecord};
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_record_and_read_entries() {
+    let dir = TempDir::new().unwrap();
+    let history = History::new(dir.path().join("sessions"));
+    let session_id = SessionId::new("session-1");
+    let first = HistoryEntry::new("UserMessageComplete", json!({"text": "Hello"}));
+    let second = HistoryEntry::new("AgentMessageComplete", json!({"text": "Hi"}));
+
+    history.record(&session_id, &first).unwrap();
+    history.record(&session_id, &second).unwrap();
+
+    assert_eq!(history.entries(&session_id).unwrap(), vec![first, second]);
+}
+
+#[test]
+fn test_path_encodes_unsafe_characters() {
+    let history = History::new("/state/hermes/sessions");
+
+    assert_eq!(
+        history.path(&SessionId::new("../a b/c")),
+        history.dir().join("..%2Fa%20b%2Fc.jsonl")
+    );
+}
+
+#[test]
+fn test_paths_of_different_sessions_differ() {
+    let history = History::new("/state/hermes/sessions");
+
+    assert_ne!(
+        history.path(&SessionId::new("a/b")),
+        history.path(&SessionId::new("a:b"))
+    );
+    assert_ne!(
+        history.path(&SessionId::new("a/b")),
+        history.path(&SessionId::new("a%2Fb"))
+    );
+}
+
+#[test]
+fn test_entries_leave_out_buffer_numbers() {
+    let entry = HistoryEntry::new(
+        "AgentToolCall",
+        json!({
+            "locations": [{ "path": "/src/main.rs", "bufnr": 3 }],
+            "content": [{ "type": "diff", "path": "/src/main.rs", "bufnr": 3 }, "text"],
+        }),
+    );
+
+    assert_eq!(
+        entry.data,
+        json!({
+            "locations": [{ "path": "/src/main.rs" }],
+            "content": [{ "type": "diff", "path": "/src/main.rs" }, "text"],
+        })
+    );
+}
+
+#[test]
+fn test_entries_of_unknown_session() {
+    let dir = TempDir::new().unwrap();
+    let history = History::new(dir.path());
+
+    assert!(history.entries(&SessionId::new("missing")).is_err());
+}
+
+#[test]
+fn test_entries_reports_invalid_line() {
+    let dir = TempDir::new().unwrap();
+    let history = History::new(dir.path());
+    let session_id = SessionId::new("session-1");
+    fs::write(
+        history.path(&session_id),
+        "{\"timestamp\":1,\"event\":\"AgentPlan\",\"data\":{}}\n\nnot json\n",
+    )
+    .unwrap();
+
+    let error = history.entries(&session_id).unwrap_err().to_string();
+    assert!(error.contains("line 3"));
+}
+
+fn started(session_id: &str, agent: &str, timestamp: u64) -> HistoryEntry {
+    HistoryEntry {
+        timestamp,
+        event: "SessionCreated".to_string(),
+        data: json!({"sessionId": session_id, "agent": agent, "connection": "copilot-1", "cwd": "/work"}),
+    }
+}
+
+fn prompt(text: &str, timestamp: u64) -> HistoryEntry {
+    HistoryEntry {
+        timestamp,
+        event: "UserMessageComplete".to_string(),
+        data: json!({"text": text}),
+    }
+}
+
+#[test]
+fn test_session_record_from_entries() {
+    let entries = vec![
+        started("session-1", "copilot", 10),
+        prompt("Fix the build", 11),
+        prompt("Thanks", 20),
+    ];
+
+    assert_eq!(
+        SessionRecord::from_entries(&entries),
+        Some(SessionRecord {
+            session_id: SessionId::new("session-1"),
+            agent: "copilot".to_string(),
+            cwd: Some("/work".to_string()),
+            first_prompt: Some("Fix the build".to_string()),
+            timestamp: 10,
+            updated: 20,
+        })
+    );
+}
+
+#[test]
+fn test_session_record_needs_start() {
+    assert_eq!(SessionRecord::from_entries(&[prompt("Hello", 1)]), None);
+}
+
+#[test]
+fn test_sessions_most_recent_first() {
+    let dir = TempDir::new().unwrap();
+    let history = History::new(dir.path());
+    let older = SessionId::new("older");
+    let newer = SessionId::new("newer");
+    history
+        .record(&older, &started("older", "copilot", 1))
+        .unwrap();
+    history
+        .record(&newer, &started("newer", "gemini", 2))
+        .unwrap();
+    history.record(&older, &prompt("Still going", 3)).unwrap();
+    fs::write(dir.path().join("broken.jsonl"), "not json\n").unwrap();
+
+    let sessions: Vec<SessionId> = history
+        .sessions()
+        .unwrap()
+        .into_iter()
+        .map(|record| record.session_id)
+        .collect();
+    assert_eq!(sessions, vec![older.clone(), newer]);
+    assert_eq!(history.session(&older).unwrap().agent, "copilot");
+}
+
+#[test]
+fn test_sessions_without_recordings() {
+    let dir = TempDir::new().unwrap();
+    let history = History::new(dir.path().join("missing"));
+
+    assert!(history.sessions().unwrap().is_empty());
+}
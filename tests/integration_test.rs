@@ -4,11 +4,12 @@
 //! with the agent-client-protocol library.
 
 use agent_client_protocol::{
-    Client, ContentBlock, ContentChunk, RequestPermissionRequest, RequestPermissionResponse,
-    SessionId, SessionNotification, SessionUpdate, TextContent,
+    Client, ContentBlock, ContentChunk, ReadTextFileRequest, RequestPermissionRequest,
+    RequestPermissionResponse, SessionId, SessionNotification, SessionUpdate, TextContent,
 };
 use async_trait::async_trait;
 use hermes::{ApcClient, ClientConfig};
+use std::path::{Path, PathBuf};
 
 #[derive(Clone)]
 struct MockHandler;
@@ -202,3 +203,32 @@ async fn test_client_cloneable() {
     let result2: agent_client_protocol::Result<()> = client_clone.session_notification(notif).await;
     assert!(result2.is_ok());
 }
+
+/// Tests that filesystem roots limit which paths the agent may touch
+#[test]
+fn test_fs_roots_limit_paths() {
+    let client = ApcClient::new(ClientConfig::default(), MockHandler);
+    assert!(client.allows_path(Path::new("/etc/passwd")));
+
+    let client = client.with_fs_roots(vec![PathBuf::from("/work/project")]);
+    assert!(client.allows_path(Path::new("/work/project/src/main.rs")));
+    assert!(!client.allows_path(Path::new("/work/other/main.rs")));
+    assert!(!client.allows_path(Path::new("/work/project/../other/main.rs")));
+    assert!(!client.allows_path(Path::new("src/main.rs")));
+}
+
+/// Tests that reads outside the filesystem roots are rejected before reaching the handler
+#[tokio::test]
+async fn test_read_outside_fs_roots_is_rejected() {
+    let client = ApcClient::new(ClientConfig::default(), MockHandler)
+        .with_fs_roots(vec![PathBuf::from("/work/project")]);
+
+    let result = client
+        .read_text_file(ReadTextFileRequest::new(
+            SessionId::new("test"),
+            "/etc/passwd",
+        ))
+        .await;
+    let error = result.unwrap_err();
+    assert_eq!(error.code, agent_client_protocol::ErrorCode::InvalidParams);
+}
@@ -0,0 +1,61 @@
+use agent_client_protocol::{
+    McpCapabilities, McpServer, McpServerHttp, McpServerSse, McpServerStdio,
+};
+use hermes::apc::mcp;
+
+fn stdio(name: &str, command: &str) -> McpServer {
+    McpServer::Stdio(McpServerStdio::new(name, command))
+}
+
+#[test]
+fn test_name_and_transport() {
+    let http = McpServer::Http(McpServerHttp::new("docs", "https://example.com/mcp"));
+    assert_eq!(mcp::name(&http), "docs");
+    assert_eq!(mcp::transport(&http), "http");
+    assert_eq!(mcp::transport(&stdio("tools", "mcp-tools")), "stdio");
+}
+
+#[test]
+fn test_merge_replaces_servers_by_name() {
+    let base = vec![stdio("tools", "mcp-tools"), stdio("search", "mcp-search")];
+    let merged = mcp::merge(&base, vec![stdio("tools", "./bin/tools")]);
+
+    assert_eq!(
+        merged,
+        vec![stdio("search", "mcp-search"), stdio("tools", "./bin/tools")]
+    );
+}
+
+#[test]
+fn test_stdio_is_always_supported() {
+    assert!(
+        mcp::check(
+            &[stdio("tools", "mcp-tools")],
+            &McpCapabilities::new(),
+            "copilot-1"
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_check_rejects_unsupported_transports() {
+    let servers = vec![
+        McpServer::Http(McpServerHttp::new("docs", "https://example.com/mcp")),
+        McpServer::Sse(McpServerSse::new("events", "https://example.com/sse")),
+    ];
+
+    let error = mcp::check(&servers, &McpCapabilities::new().http(true), "copilot-1").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Internal error: MCP server \"events\" uses sse, which copilot-1 doesn't support"
+    );
+    assert!(
+        mcp::check(
+            &servers,
+            &McpCapabilities::new().http(true).sse(true),
+            "copilot-1"
+        )
+        .is_ok()
+    );
+}
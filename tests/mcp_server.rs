@@ -0,0 +1,232 @@
+use agent_client_protocol::McpServer;
+use hermes::nvim::mcp::{self, Editor, Position};
+use serde_json::{Value, json};
+use std::{
+    cell::RefCell,
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+};
+
+#[derive(Default)]
+struct FakeEditor {
+    positions: RefCell<Vec<Position>>,
+}
+
+impl Editor for FakeEditor {
+    fn diagnostics(&self, path: Option<&str>) -> Result<Value, String> {
+        Ok(json!([{ "path": path.unwrap_or("all"), "message": "unused variable" }]))
+    }
+
+    fn definitions(&self, position: &Position) -> Result<Value, String> {
+        self.positions.borrow_mut().push(position.clone());
+        Ok(json!([{ "path": "src/lib.rs", "line": 3, "column": 8 }]))
+    }
+
+    fn references(&self, _position: &Position) -> Result<Value, String> {
+        Err("No LSP client attached".to_string())
+    }
+
+    fn buffers(&self) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn quickfix(&self) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn selection(&self) -> Result<Value, String> {
+        Ok(Value::Null)
+    }
+
+    fn cursor(&self) -> Result<Value, String> {
+        Ok(json!({ "path": "src/main.rs", "line": 1, "column": 1 }))
+    }
+}
+
+fn request(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+}
+
+#[test]
+fn test_initialize() {
+    let response = mcp::handle(&FakeEditor::default(), &request("initialize", json!({}))).unwrap();
+    assert_eq!(response["id"], json!(1));
+    assert_eq!(
+        response["result"]["protocolVersion"],
+        json!(mcp::PROTOCOL_VERSION)
+    );
+    assert_eq!(response["result"]["serverInfo"]["name"], json!("neovim"));
+    assert!(response["result"]["capabilities"]["tools"].is_object());
+}
+
+#[test]
+fn test_notifications_are_not_answered() {
+    let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    assert!(mcp::handle(&FakeEditor::default(), &notification).is_none());
+}
+
+#[test]
+fn test_tools_list() {
+    let response = mcp::handle(&FakeEditor::default(), &request("tools/list", json!({}))).unwrap();
+    let names: Vec<&str> = response["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "diagnostics",
+            "lsp_definitions",
+            "lsp_references",
+            "buffers",
+            "quickfix",
+            "selection",
+            "cursor"
+        ]
+    );
+}
+
+#[test]
+fn test_call_tool() {
+    let editor = FakeEditor::default();
+    let response = mcp::handle(
+        &editor,
+        &request(
+            "tools/call",
+            json!({ "name": "lsp_definitions", "arguments": { "path": "src/main.rs", "line": 4, "column": 12 } }),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(response["result"]["isError"], json!(false));
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(text).unwrap(),
+        json!([{ "path": "src/lib.rs", "line": 3, "column": 8 }])
+    );
+    assert_eq!(
+        editor.positions.borrow()[0],
+        Position {
+            path: Some("src/main.rs".to_string()),
+            line: 4,
+            column: 12
+        }
+    );
+}
+
+#[test]
+fn test_tool_failures_are_reported_in_the_result() {
+    let response = mcp::handle(
+        &FakeEditor::default(),
+        &request(
+            "tools/call",
+            json!({ "name": "lsp_references", "arguments": { "line": 1, "column": 1 } }),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(response["result"]["isError"], json!(true));
+    assert_eq!(
+        response["result"]["content"][0]["text"],
+        json!("No LSP client attached")
+    );
+}
+
+#[test]
+fn test_invalid_calls_are_errors() {
+    let editor = FakeEditor::default();
+    let unknown =
+        mcp::handle(&editor, &request("tools/call", json!({ "name": "format" }))).unwrap();
+    assert_eq!(unknown["error"]["code"], json!(-32602));
+    assert_eq!(
+        unknown["error"]["message"],
+        json!("Unknown tool \"format\"")
+    );
+
+    let missing_line = mcp::handle(
+        &editor,
+        &request(
+            "tools/call",
+            json!({ "name": "lsp_definitions", "arguments": { "column": 1 } }),
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        missing_line["error"]["message"],
+        json!("\"line\" must be a positive integer")
+    );
+
+    let method = mcp::handle(&editor, &request("resources/list", json!({}))).unwrap();
+    assert_eq!(method["error"]["code"], json!(-32601));
+}
+
+#[test]
+fn test_handle_line() {
+    let editor = FakeEditor::default();
+    let response =
+        mcp::handle_line(&editor, r#"{"jsonrpc":"2.0","id":7,"method":"ping"}"#).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(&response).unwrap(),
+        json!({ "jsonrpc": "2.0", "id": 7, "result": {} })
+    );
+
+    let invalid = mcp::handle_line(&editor, "not json").unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(&invalid).unwrap()["error"]["code"],
+        json!(-32700)
+    );
+}
+
+#[test]
+fn test_stdio_server_runs_the_bridge() {
+    let server = mcp::stdio_server(
+        PathBuf::from("/usr/bin/nvim"),
+        PathBuf::from("/plugin/lua/hermes/mcp_bridge.lua"),
+        PathBuf::from("/run/hermes-1.sock"),
+    );
+    match server {
+        McpServer::Stdio(server) => {
+            assert_eq!(server.name, "neovim");
+            assert_eq!(server.command, PathBuf::from("/usr/bin/nvim"));
+            assert_eq!(
+                server.args,
+                vec![
+                    "--clean",
+                    "--headless",
+                    "-l",
+                    "/plugin/lua/hermes/mcp_bridge.lua",
+                    "/run/hermes-1.sock"
+                ]
+            );
+        }
+        other => panic!("expected a stdio server, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_server_answers_once_woken() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("hermes.sock");
+    let (server, mut woken) = mcp::Server::bind(&socket).unwrap();
+
+    let agent = std::thread::spawn(move || {
+        let mut stream = UnixStream::connect(socket).unwrap();
+        writeln!(
+            stream,
+            "{}",
+            request("tools/call", json!({ "name": "cursor" }))
+        )
+        .unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        serde_json::from_str::<Value>(&line).unwrap()
+    });
+    woken.read_exact(&mut [0]).unwrap();
+    server.answer(&FakeEditor::default());
+
+    let response = agent.join().unwrap();
+    assert_eq!(response["result"]["isError"], json!(false));
+}
@@ -1,2 +1,25 @@
+#![allow(clippy::bool_assert_comparison)]
+
+mod command;
+mod commands;
+mod config_options;
+mod connection;
+mod context;
+mod error;
+mod export;
+mod health;
+mod history;
 mod integration_test;
+mod mcp;
+mod mcp_server;
+mod modes;
 mod parse;
+mod permissions;
+mod producer;
+mod project;
+mod prompt;
+mod setup;
+mod status;
+mod tool_calls;
+mod transcript;
+mod turn;
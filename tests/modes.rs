@@ -0,0 +1,79 @@
+use agent_client_protocol::{SessionId, SessionMode, SessionModeId, SessionModeState};
+use hermes::apc::modes::ModeTracker;
+
+fn session() -> SessionId {
+    SessionId::new("session_1")
+}
+
+fn modes() -> SessionModeState {
+    SessionModeState::new(
+        "ask",
+        vec![
+            SessionMode::new("ask", "Ask"),
+            SessionMode::new("code", "Code"),
+        ],
+    )
+}
+
+#[test]
+fn test_modes_for_unknown_session_is_none() {
+    let tracker = ModeTracker::new();
+    assert!(tracker.modes(&session()).is_none());
+    assert!(tracker.current(&session()).is_none());
+}
+
+#[test]
+fn test_record_modes() {
+    let mut tracker = ModeTracker::new();
+    tracker.record(&session(), modes());
+
+    assert_eq!(tracker.modes(&session()), Some(modes()));
+    assert_eq!(
+        tracker.current(&session()),
+        Some(&SessionModeId::new("ask"))
+    );
+}
+
+#[test]
+fn test_set_current_mode() {
+    let mut tracker = ModeTracker::new();
+    tracker.record(&session(), modes());
+
+    assert!(tracker.set_current(&session(), SessionModeId::new("code")));
+    assert_eq!(
+        tracker.current(&session()),
+        Some(&SessionModeId::new("code"))
+    );
+}
+
+#[test]
+fn test_set_current_mode_without_modes() {
+    let mut tracker = ModeTracker::new();
+
+    assert!(!tracker.set_current(&session(), SessionModeId::new("code")));
+    assert!(tracker.current(&session()).is_none());
+}
+
+#[test]
+fn test_supports_available_modes_only() {
+    let mut tracker = ModeTracker::new();
+    tracker.record(&session(), modes());
+
+    assert!(tracker.supports(&session(), &SessionModeId::new("code")));
+    assert!(!tracker.supports(&session(), &SessionModeId::new("architect")));
+}
+
+#[test]
+fn test_supports_no_mode_without_mode_state() {
+    let tracker = ModeTracker::new();
+    assert!(!tracker.supports(&session(), &SessionModeId::new("code")));
+}
+
+#[test]
+fn test_clear_removes_modes() {
+    let mut tracker = ModeTracker::new();
+    tracker.record(&session(), modes());
+    tracker.clear(&session());
+
+    assert!(tracker.modes(&session()).is_none());
+}
@@ -0,0 +1,40 @@
+use agent_client_protocol::{AuthMethod, AuthMethodId};
+use hermes::nvim::parse::auth_method;
+
+#[test]
+fn test_auth_method_ok() {
+    let method = AuthMethod::new(AuthMethodId::new("github"), "GitHub");
+
+    let result = auth_method(method);
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn test_auth_method_id_and_name() {
+    let method = AuthMethod::new(AuthMethodId::new("github"), "GitHub");
+
+    let result = auth_method(method).unwrap();
+
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("id", "github");
+    expected.insert("name", "GitHub");
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_auth_method_with_description() {
+    let method = AuthMethod::new(AuthMethodId::new("github"), "GitHub")
+        .description("Sign in with GitHub".to_string());
+
+    let result = auth_method(method).unwrap();
+    let description = result.get("description").unwrap();
+    assert_eq!(*description, nvim_oxi::Object::from("Sign in with GitHub"));
+}
+
+#[test]
+fn test_auth_method_without_meta() {
+    let method = AuthMethod::new(AuthMethodId::new("github"), "GitHub");
+
+    let result = auth_method(method).unwrap();
+    assert_eq!(result.get("meta").is_some(), false);
+}
@@ -1,7 +1,7 @@
 use agent_client_protocol::{
     AvailableCommand, AvailableCommandInput, AvailableCommandsUpdate, UnstructuredCommandInput,
 };
-use hermes::nvim::parse::available_commands_event;
+use hermes::nvim::parse::{available_command, available_commands_event};
 
 #[test]
 fn test_available_commands_event_ok() {
@@ -93,6 +93,7 @@ fn test_available_commands_event_with_input() {
     expected_cmd.insert("description", "Search for text");
 
     let mut input_dict = nvim_oxi::Dictionary::new();
+    input_dict.insert("type", "unstructured");
     input_dict.insert("hint", "Enter search query...");
     expected_cmd.insert("input", input_dict);
 
@@ -122,3 +123,51 @@ fn test_available_commands_event_with_meta() {
     let result = available_commands_event(update).unwrap();
     assert_eq!(result.get("meta").is_some(), true);
 }
+
+#[test]
+fn test_available_commands_event_with_input_meta() {
+    let meta: serde_json::Map<String, serde_json::Value> = serde_json::json!({"optional": true})
+        .as_object()
+        .unwrap()
+        .clone();
+    let cmd = AvailableCommand::new("search", "Search for text").input(
+        AvailableCommandInput::Unstructured(UnstructuredCommandInput::new("query").meta(meta)),
+    );
+    let update = AvailableCommandsUpdate::new(vec![cmd]);
+
+    let result = available_commands_event(update).unwrap();
+    let commands = result.get("commands").unwrap();
+
+    let mut meta_dict = nvim_oxi::Dictionary::new();
+    meta_dict.insert("optional", true);
+    let mut input_dict = nvim_oxi::Dictionary::new();
+    input_dict.insert("type", "unstructured");
+    input_dict.insert("hint", "query");
+    input_dict.insert("meta", meta_dict);
+    let mut expected_cmd = nvim_oxi::Dictionary::new();
+    expected_cmd.insert("name", "search");
+    expected_cmd.insert("description", "Search for text");
+    expected_cmd.insert("input", input_dict);
+    let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_cmd)]);
+
+    assert_eq!(*commands, nvim_oxi::Object::from(expected));
+}
+
+#[test]
+fn test_available_command_with_meta() {
+    let meta: serde_json::Map<String, serde_json::Value> = serde_json::json!({"source": "agent"})
+        .as_object()
+        .unwrap()
+        .clone();
+    let cmd = AvailableCommand::new("review", "Review changes").meta(meta);
+
+    let result = available_command(cmd).unwrap();
+
+    let mut meta_dict = nvim_oxi::Dictionary::new();
+    meta_dict.insert("source", "agent");
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("name", "review");
+    expected.insert("description", "Review changes");
+    expected.insert("meta", meta_dict);
+    assert_eq!(result, expected);
+}
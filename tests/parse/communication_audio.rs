@@ -0,0 +1,87 @@
+use agent_client_protocol::{AudioContent, ContentBlock};
+use hermes::nvim::parse::{audio_event, communication};
+
+#[test]
+fn test_audio_event_ok() {
+    let audio = AudioContent::new("base64data", "audio/wav");
+    let result = audio_event(audio);
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn test_audio_event_data_value() {
+    let audio = AudioContent::new("base64encoded", "audio/wav");
+    let (dict, _content_type) = audio_event(audio).unwrap();
+
+    let data = dict.get("data").unwrap();
+    assert_eq!(*data, nvim_oxi::Object::from("base64encoded"));
+}
+
+#[test]
+fn test_audio_event_mime_type_value() {
+    let audio = AudioContent::new("data", "audio/mpeg");
+    let (dict, _content_type) = audio_event(audio).unwrap();
+
+    let mime_type = dict.get("mimeType").unwrap();
+    assert_eq!(*mime_type, nvim_oxi::Object::from("audio/mpeg"));
+}
+
+#[test]
+fn test_audio_event_content_type() {
+    let audio = AudioContent::new("data", "audio/wav");
+    let (_dict, content_type) = audio_event(audio).unwrap();
+
+    assert_eq!(content_type, "Audio");
+}
+
+#[test]
+fn test_audio_event_without_annotations() {
+    let audio = AudioContent::new("data", "audio/wav");
+    let (dict, _) = audio_event(audio).unwrap();
+
+    assert_eq!(dict.get("annotations").is_some(), false);
+}
+
+#[test]
+fn test_audio_event_with_annotations() {
+    use agent_client_protocol::Annotations;
+
+    let audio = AudioContent::new("data", "audio/wav").annotations(Annotations::new());
+    let (dict, _) = audio_event(audio).unwrap();
+
+    let annotations_value = dict.get("annotations").unwrap();
+    let expected_dict = nvim_oxi::Dictionary::new();
+    assert_eq!(*annotations_value, nvim_oxi::Object::from(expected_dict));
+}
+
+#[test]
+fn test_audio_event_without_meta() {
+    let audio = AudioContent::new("data", "audio/wav");
+    let (dict, _) = audio_event(audio).unwrap();
+
+    assert_eq!(dict.get("meta").is_some(), false);
+}
+
+#[test]
+fn test_audio_event_with_meta() {
+    let meta: serde_json::Map<String, serde_json::Value> = serde_json::json!({"source": "test"})
+        .as_object()
+        .unwrap()
+        .clone();
+    let audio = AudioContent::new("data", "audio/wav").meta(meta);
+    let (dict, _) = audio_event(audio).unwrap();
+
+    assert_eq!(dict.get("meta").is_some(), true);
+}
+
+#[test]
+fn test_communication_maps_audio_block() {
+    let block = ContentBlock::Audio(AudioContent::new("data", "audio/ogg"));
+    let (dict, content_type) = communication(block).unwrap();
+
+    assert_eq!(content_type, "Audio");
+    assert_eq!(
+        *dict.get("mimeType").unwrap(),
+        nvim_oxi::Object::from("audio/ogg")
+    );
+}
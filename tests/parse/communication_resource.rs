@@ -114,7 +114,7 @@ fn test_resource_event_with_annotations_audience() {
         let mut d = nvim_oxi::Dictionary::new();
         d.insert(
             "audience",
-            nvim_oxi::Array::from_iter([nvim_oxi::Object::from("User")]),
+            nvim_oxi::Array::from_iter([nvim_oxi::Object::from("user")]),
         );
         d
     };
@@ -63,7 +63,8 @@ fn test_text_event_with_meta() {
     let text = TextContent::new("With meta").meta(meta);
     let (dict, _) = text_event(text).unwrap();
 
-    assert_eq!(dict.get("meta").is_some(), true);
+    let expected = nvim_oxi::Dictionary::from_iter([("source", "test")]);
+    assert_eq!(*dict.get("meta").unwrap(), nvim_oxi::Object::from(expected));
 }
 
 #[test]
@@ -3,7 +3,8 @@ use agent_client_protocol::{
     SessionConfigOptionCategory, SessionConfigSelectGroup, SessionConfigSelectOption,
     SessionConfigSelectOptions, SessionConfigValueId,
 };
-use hermes::nvim::parse::config_option_event;
+use hermes::nvim::parse::{config_option, config_option_event};
+use nvim_oxi::{Array, Dictionary, Object, conversion::FromObject};
 
 #[test]
 fn test_config_option_event_ok() {
@@ -123,7 +124,7 @@ fn test_config_option_event_with_category() {
     let mut expected_option = nvim_oxi::Dictionary::new();
     expected_option.insert("id", "option_3");
     expected_option.insert("name", "Option Three");
-    expected_option.insert("category", "Model");
+    expected_option.insert("category", "model");
 
     let mut select_dict = nvim_oxi::Dictionary::new();
     select_dict.insert("currentValue", "default");
@@ -320,3 +321,78 @@ fn test_config_option_event_with_meta() {
     let result = config_option_event(update).unwrap();
     assert_eq!(result.get("meta").is_some(), true);
 }
+
+#[test]
+fn test_config_option_meta() {
+    let meta = |source: &str| {
+        serde_json::json!({ "source": source })
+            .as_object()
+            .unwrap()
+            .clone()
+    };
+    let option = SessionConfigOption::select(
+        SessionConfigId::new("option_9"),
+        "Option Nine",
+        SessionConfigValueId::new("value_a"),
+        SessionConfigSelectOptions::Grouped(vec![
+            SessionConfigSelectGroup::new(
+                SessionConfigGroupId::new("group_1"),
+                "Group 1",
+                vec![
+                    SessionConfigSelectOption::new(SessionConfigValueId::new("value_a"), "Value A")
+                        .meta(meta("value")),
+                ],
+            )
+            .meta(meta("group")),
+        ]),
+    )
+    .meta(meta("option"));
+
+    let result = config_option(option).unwrap();
+
+    let source = |dict: &Dictionary| {
+        let meta = Dictionary::from_object(dict.get("meta").unwrap().clone()).unwrap();
+        meta.get("source").unwrap().clone()
+    };
+    assert_eq!(source(&result), Object::from("option"));
+    let kind = Dictionary::from_object(result.get("kind").unwrap().clone()).unwrap();
+    let groups = Array::from_object(kind.get("options").unwrap().clone()).unwrap();
+    let group = Dictionary::from_object(groups[0].clone()).unwrap();
+    assert_eq!(source(&group), Object::from("group"));
+    let options = Array::from_object(group.get("options").unwrap().clone()).unwrap();
+    let value = Dictionary::from_object(options[0].clone()).unwrap();
+    assert_eq!(source(&value), Object::from("value"));
+}
+
+#[test]
+fn test_config_option() {
+    let option = SessionConfigOption::select(
+        SessionConfigId::new("model"),
+        "Model",
+        SessionConfigValueId::new("fast"),
+        vec![SessionConfigSelectOption::new(
+            SessionConfigValueId::new("fast"),
+            "Fast",
+        )],
+    )
+    .category(SessionConfigOptionCategory::Model);
+
+    let result = config_option(option).unwrap();
+
+    let mut opt = nvim_oxi::Dictionary::new();
+    opt.insert("value", "fast");
+    opt.insert("name", "Fast");
+    opt.insert("type", "ungrouped");
+    let mut select_dict = nvim_oxi::Dictionary::new();
+    select_dict.insert("currentValue", "fast");
+    select_dict.insert(
+        "options",
+        nvim_oxi::Array::from_iter([nvim_oxi::Object::from(opt)]),
+    );
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("id", "model");
+    expected.insert("name", "Model");
+    expected.insert("category", "model");
+    expected.insert("kind", select_dict);
+    assert_eq!(result, expected);
+}
@@ -1,5 +1,5 @@
-use agent_client_protocol::{CurrentModeUpdate, SessionModeId};
-use hermes::nvim::parse::current_mode_event;
+use agent_client_protocol::{CurrentModeUpdate, SessionMode, SessionModeId, SessionModeState};
+use hermes::nvim::parse::{current_mode_event, session_modes};
 
 #[test]
 fn test_current_mode_event_ok() {
@@ -37,3 +37,49 @@ fn test_current_mode_event_with_meta() {
     let result = current_mode_event(update).unwrap();
     assert_eq!(result.get("meta").is_some(), true);
 }
+
+#[test]
+fn test_session_modes() {
+    let modes = SessionModeState::new(
+        "ask",
+        vec![
+            SessionMode::new("ask", "Ask").description("Answer questions".to_string()),
+            SessionMode::new("code", "Code"),
+        ],
+    );
+
+    let result = session_modes(modes).unwrap();
+
+    let mut ask = nvim_oxi::Dictionary::new();
+    ask.insert("id", "ask");
+    ask.insert("name", "Ask");
+    ask.insert("description", "Answer questions");
+    let mut code = nvim_oxi::Dictionary::new();
+    code.insert("id", "code");
+    code.insert("name", "Code");
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("currentModeId", "ask");
+    expected.insert(
+        "availableModes",
+        nvim_oxi::Array::from_iter([nvim_oxi::Object::from(ask), nvim_oxi::Object::from(code)]),
+    );
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_session_modes_with_meta() {
+    let meta: serde_json::Map<String, serde_json::Value> = serde_json::json!({"source": "agent"})
+        .as_object()
+        .unwrap()
+        .clone();
+    let modes = SessionModeState::new("ask", vec![SessionMode::new("ask", "Ask")]).meta(meta);
+
+    let result = session_modes(modes).unwrap();
+
+    let mut expected_meta = nvim_oxi::Dictionary::new();
+    expected_meta.insert("source", "agent");
+    assert_eq!(
+        *result.get("meta").unwrap(),
+        nvim_oxi::Object::from(expected_meta)
+    );
+}
@@ -0,0 +1,39 @@
+use agent_client_protocol::{
+    AgentCapabilities, AuthMethod, AuthMethodId, Implementation, InitializeResponse,
+    ProtocolVersion,
+};
+use hermes::nvim::parse::initialize_response;
+use nvim_oxi::{Array, Dictionary, Object, conversion::FromObject};
+
+#[test]
+fn test_initialize_response_ok() {
+    let result = initialize_response(InitializeResponse::new(ProtocolVersion::LATEST));
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn test_initialize_response_fields() {
+    let response = InitializeResponse::new(ProtocolVersion::LATEST)
+        .agent_capabilities(AgentCapabilities::new().load_session(true))
+        .auth_methods(vec![AuthMethod::new(AuthMethodId::new("github"), "GitHub")])
+        .agent_info(Implementation::new("opencode", "0.9.0"));
+
+    let result = initialize_response(response).unwrap();
+
+    assert_eq!(result.get("protocolVersion"), Some(&Object::from(1)));
+    let capabilities =
+        Dictionary::from_object(result.get("agentCapabilities").unwrap().clone()).unwrap();
+    assert_eq!(capabilities.get("loadSession"), Some(&Object::from(true)));
+    let methods = Array::from_object(result.get("authMethods").unwrap().clone()).unwrap();
+    assert_eq!(methods.len(), 1);
+    let info = Dictionary::from_object(result.get("agentInfo").unwrap().clone()).unwrap();
+    assert_eq!(info.get("name"), Some(&Object::from("opencode")));
+    assert_eq!(info.get("version"), Some(&Object::from("0.9.0")));
+}
+
+#[test]
+fn test_initialize_response_without_agent_info() {
+    let result = initialize_response(InitializeResponse::new(ProtocolVersion::LATEST)).unwrap();
+    assert_eq!(result.get("agentInfo").is_none(), true);
+    assert_eq!(result.get("meta").is_none(), true);
+}
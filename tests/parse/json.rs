@@ -0,0 +1,176 @@
+use agent_client_protocol::{
+    PlanEntryPriority, PlanEntryStatus, Role, SessionConfigOptionCategory, StopReason,
+    ToolCallStatus, ToolKind,
+};
+use hermes::nvim::parse::{
+    dictionary_to_json, enum_name, json_to_object, meta_to_dictionary, object_to_json,
+};
+use nvim_oxi::{Array, Dictionary, Object};
+use serde_json::json;
+
+#[test]
+fn test_json_to_object_null() {
+    assert_eq!(json_to_object(json!(null)), Object::nil());
+}
+
+#[test]
+fn test_json_to_object_bool() {
+    assert_eq!(json_to_object(json!(true)), Object::from(true));
+}
+
+#[test]
+fn test_json_to_object_integer() {
+    assert_eq!(json_to_object(json!(42)), Object::from(42));
+}
+
+#[test]
+fn test_json_to_object_negative_integer() {
+    assert_eq!(json_to_object(json!(-7)), Object::from(-7));
+}
+
+#[test]
+fn test_json_to_object_float() {
+    assert_eq!(json_to_object(json!(1.5)), Object::from(1.5));
+}
+
+#[test]
+fn test_json_to_object_string() {
+    assert_eq!(json_to_object(json!("hello")), Object::from("hello"));
+}
+
+#[test]
+fn test_json_to_object_array() {
+    let expected = Array::from_iter([Object::from(1), Object::from("two")]);
+    assert_eq!(json_to_object(json!([1, "two"])), Object::from(expected));
+}
+
+#[test]
+fn test_json_to_object_nested_object() {
+    let value = json!({"file": {"path": "/tmp/a.rs", "lines": [1, 2]}});
+
+    let mut file = Dictionary::new();
+    file.insert("lines", Array::from_iter([1, 2]));
+    file.insert("path", "/tmp/a.rs");
+    let expected = Dictionary::from_iter([("file", Object::from(file))]);
+
+    assert_eq!(json_to_object(value), Object::from(expected));
+}
+
+#[test]
+fn test_meta_to_dictionary_empty() {
+    let meta = serde_json::Map::new();
+    assert_eq!(meta_to_dictionary(meta), Dictionary::new());
+}
+
+#[test]
+fn test_meta_to_dictionary_values() {
+    let meta = json!({"source": "test", "count": 3})
+        .as_object()
+        .unwrap()
+        .clone();
+
+    let result = meta_to_dictionary(meta);
+    assert_eq!(*result.get("source").unwrap(), Object::from("test"));
+    assert_eq!(*result.get("count").unwrap(), Object::from(3));
+}
+
+#[test]
+fn test_enum_name_tool_kind() {
+    assert_eq!(enum_name(&ToolKind::Read).unwrap(), "read");
+    assert_eq!(enum_name(&ToolKind::Edit).unwrap(), "edit");
+    assert_eq!(enum_name(&ToolKind::Delete).unwrap(), "delete");
+    assert_eq!(enum_name(&ToolKind::Move).unwrap(), "move");
+    assert_eq!(enum_name(&ToolKind::Search).unwrap(), "search");
+    assert_eq!(enum_name(&ToolKind::Execute).unwrap(), "execute");
+    assert_eq!(enum_name(&ToolKind::Think).unwrap(), "think");
+    assert_eq!(enum_name(&ToolKind::Fetch).unwrap(), "fetch");
+    assert_eq!(enum_name(&ToolKind::SwitchMode).unwrap(), "switch_mode");
+    assert_eq!(enum_name(&ToolKind::Other).unwrap(), "other");
+}
+
+#[test]
+fn test_enum_name_tool_call_status() {
+    assert_eq!(enum_name(&ToolCallStatus::Pending).unwrap(), "pending");
+    assert_eq!(
+        enum_name(&ToolCallStatus::InProgress).unwrap(),
+        "in_progress"
+    );
+    assert_eq!(enum_name(&ToolCallStatus::Completed).unwrap(), "completed");
+    assert_eq!(enum_name(&ToolCallStatus::Failed).unwrap(), "failed");
+}
+
+#[test]
+fn test_enum_name_plan_entry() {
+    assert_eq!(enum_name(&PlanEntryPriority::High).unwrap(), "high");
+    assert_eq!(enum_name(&PlanEntryPriority::Medium).unwrap(), "medium");
+    assert_eq!(enum_name(&PlanEntryPriority::Low).unwrap(), "low");
+    assert_eq!(enum_name(&PlanEntryStatus::Pending).unwrap(), "pending");
+    assert_eq!(
+        enum_name(&PlanEntryStatus::InProgress).unwrap(),
+        "in_progress"
+    );
+    assert_eq!(enum_name(&PlanEntryStatus::Completed).unwrap(), "completed");
+}
+
+#[test]
+fn test_enum_name_config_category() {
+    assert_eq!(
+        enum_name(&SessionConfigOptionCategory::Mode).unwrap(),
+        "mode"
+    );
+    assert_eq!(
+        enum_name(&SessionConfigOptionCategory::Model).unwrap(),
+        "model"
+    );
+    assert_eq!(
+        enum_name(&SessionConfigOptionCategory::ThoughtLevel).unwrap(),
+        "thought_level"
+    );
+    assert_eq!(
+        enum_name(&SessionConfigOptionCategory::Other("_custom".to_string())).unwrap(),
+        "_custom"
+    );
+}
+
+#[test]
+fn test_enum_name_role() {
+    assert_eq!(enum_name(&Role::User).unwrap(), "user");
+    assert_eq!(enum_name(&Role::Assistant).unwrap(), "assistant");
+}
+
+#[test]
+fn test_enum_name_stop_reason() {
+    assert_eq!(enum_name(&StopReason::EndTurn).unwrap(), "end_turn");
+    assert_eq!(enum_name(&StopReason::MaxTokens).unwrap(), "max_tokens");
+    assert_eq!(
+        enum_name(&StopReason::MaxTurnRequests).unwrap(),
+        "max_turn_requests"
+    );
+    assert_eq!(enum_name(&StopReason::Refusal).unwrap(), "refusal");
+    assert_eq!(enum_name(&StopReason::Cancelled).unwrap(), "cancelled");
+}
+
+#[test]
+fn test_enum_name_rejects_non_string() {
+    assert_eq!(enum_name(&42).is_err(), true);
+}
+
+#[test]
+fn test_object_to_json_round_trip() {
+    let value = json!({
+        "name": "hermes",
+        "count": 3,
+        "ratio": 0.5,
+        "enabled": false,
+        "items": [1, "two", {"three": 3}],
+    });
+
+    assert_eq!(object_to_json(json_to_object(value.clone())), value);
+}
+
+#[test]
+fn test_dictionary_to_json() {
+    let dict = Dictionary::from_iter([("id", Object::from("call_1")), ("line", Object::from(4))]);
+
+    assert_eq!(dictionary_to_json(dict), json!({"id": "call_1", "line": 4}));
+}
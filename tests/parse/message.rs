@@ -0,0 +1,80 @@
+use agent_client_protocol::{ContentBlock, ImageContent, ResourceLink, TextContent};
+use hermes::{
+    apc::transcript::{Message, MessageRole},
+    nvim::parse::message_event,
+};
+use nvim_oxi::conversion::FromObject;
+
+#[test]
+fn test_message_event_ok() {
+    let message = Message::new(MessageRole::Agent, vec![]);
+    let result = message_event(message);
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn test_message_event_role() {
+    let message = Message::new(MessageRole::Thought, vec![]);
+    let result = message_event(message).unwrap();
+
+    let role = result.get("role").unwrap();
+    assert_eq!(*role, nvim_oxi::Object::from("thought"));
+}
+
+#[test]
+fn test_message_event_text() {
+    let message = Message::new(
+        MessageRole::Agent,
+        vec![ContentBlock::Text(TextContent::new("Hello"))],
+    );
+    let result = message_event(message).unwrap();
+
+    let text = result.get("text").unwrap();
+    assert_eq!(*text, nvim_oxi::Object::from("Hello"));
+}
+
+#[test]
+fn test_message_event_content() {
+    let message = Message::new(
+        MessageRole::Agent,
+        vec![
+            ContentBlock::Text(TextContent::new("Hello")),
+            ContentBlock::Image(ImageContent::new("data", "image/png")),
+        ],
+    );
+    let result = message_event(message).unwrap();
+
+    let mut text = nvim_oxi::Dictionary::new();
+    text.insert("text", "Hello");
+    text.insert("type", "text");
+    let mut image = nvim_oxi::Dictionary::new();
+    image.insert("data", "data");
+    image.insert("mimeType", "image/png");
+    image.insert("type", "image");
+    let expected =
+        nvim_oxi::Array::from_iter([nvim_oxi::Object::from(text), nvim_oxi::Object::from(image)]);
+
+    assert_eq!(
+        *result.get("content").unwrap(),
+        nvim_oxi::Object::from(expected)
+    );
+}
+
+#[test]
+fn test_message_event_content_type_is_the_protocol_name() {
+    let message = Message::new(
+        MessageRole::User,
+        vec![ContentBlock::ResourceLink(ResourceLink::new(
+            "log.txt",
+            "file:///tmp/log.txt",
+        ))],
+    );
+    let result = message_event(message).unwrap();
+
+    let content = nvim_oxi::Array::from_object(result.get("content").unwrap().clone()).unwrap();
+    let link = nvim_oxi::Dictionary::from_object(content[0].clone()).unwrap();
+    assert_eq!(
+        *link.get("type").unwrap(),
+        nvim_oxi::Object::from("resource_link")
+    );
+}
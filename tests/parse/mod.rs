@@ -1,12 +1,21 @@
 pub mod annotations;
+pub mod auth_method;
 pub mod available_commands;
+pub mod communication_audio;
 pub mod communication_image;
 pub mod communication_resource;
 pub mod communication_resource_link;
 pub mod communication_text;
 pub mod config_option;
 pub mod current_mode;
+pub mod initialize;
+pub mod json;
+pub mod message;
+pub mod permission;
 pub mod plan;
+pub mod session_record;
+pub mod status;
 pub mod tool_call;
 pub mod tool_call_content;
 pub mod tool_call_update;
+pub mod unknown;
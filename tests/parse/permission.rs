@@ -0,0 +1,44 @@
+use agent_client_protocol::{
+    PermissionOption, PermissionOptionKind, RequestPermissionOutcome, RequestPermissionRequest,
+    RequestPermissionResponse, SelectedPermissionOutcome, ToolCallUpdate, ToolCallUpdateFields,
+};
+use hermes::nvim::parse::{dictionary_to_json, permission_event};
+use serde_json::json;
+
+fn request() -> RequestPermissionRequest {
+    RequestPermissionRequest::new(
+        "session-1",
+        ToolCallUpdate::new("call_1", ToolCallUpdateFields::new().title("Edit main.rs")),
+        vec![
+            PermissionOption::new("allow", "Allow once", PermissionOptionKind::AllowOnce),
+            PermissionOption::new("reject", "Reject", PermissionOptionKind::RejectOnce),
+        ],
+    )
+}
+
+#[test]
+fn test_permission_event_selected() {
+    let response = RequestPermissionResponse::new(RequestPermissionOutcome::Selected(
+        SelectedPermissionOutcome::new("reject"),
+    ));
+
+    assert_eq!(
+        dictionary_to_json(permission_event(request(), &response).unwrap()),
+        json!({
+            "outcome": "selected",
+            "optionId": "reject",
+            "name": "Reject",
+            "kind": "reject_once",
+            "toolCall": {"id": "call_1", "title": "Edit main.rs"},
+        })
+    );
+}
+
+#[test]
+fn test_permission_event_cancelled() {
+    let response = RequestPermissionResponse::new(RequestPermissionOutcome::Cancelled);
+    let data = dictionary_to_json(permission_event(request(), &response).unwrap());
+
+    assert_eq!(data["outcome"], "cancelled");
+    assert_eq!(data.get("optionId"), None);
+}
@@ -37,7 +37,8 @@ fn test_plan_event_single_entry() {
 
     let mut expected_entry = nvim_oxi::Dictionary::new();
     expected_entry.insert("content", "Analyze codebase");
-    expected_entry.insert("priority", "High");
+    expected_entry.insert("priority", "high");
+    expected_entry.insert("status", "pending");
     let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
     assert_eq!(*entries, nvim_oxi::Object::from(expected));
 }
@@ -61,11 +62,13 @@ fn test_plan_event_multiple_entries() {
 
     let mut expected_entry1 = nvim_oxi::Dictionary::new();
     expected_entry1.insert("content", "First task");
-    expected_entry1.insert("priority", "High");
+    expected_entry1.insert("priority", "high");
+    expected_entry1.insert("status", "pending");
 
     let mut expected_entry2 = nvim_oxi::Dictionary::new();
     expected_entry2.insert("content", "Second task");
-    expected_entry2.insert("priority", "Low");
+    expected_entry2.insert("priority", "low");
+    expected_entry2.insert("status", "in_progress");
 
     let expected = nvim_oxi::Array::from_iter([
         nvim_oxi::Object::from(expected_entry1),
@@ -88,7 +91,8 @@ fn test_plan_event_entry_content_value() {
 
     let mut expected_entry = nvim_oxi::Dictionary::new();
     expected_entry.insert("content", "My task content");
-    expected_entry.insert("priority", "Medium");
+    expected_entry.insert("priority", "medium");
+    expected_entry.insert("status", "pending");
     let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
     assert_eq!(*entries, nvim_oxi::Object::from(expected));
 }
@@ -103,7 +107,8 @@ fn test_plan_event_entry_priority_high() {
 
     let mut expected_entry = nvim_oxi::Dictionary::new();
     expected_entry.insert("content", "Task");
-    expected_entry.insert("priority", "High");
+    expected_entry.insert("priority", "high");
+    expected_entry.insert("status", "pending");
     let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
     assert_eq!(*entries, nvim_oxi::Object::from(expected));
 }
@@ -118,7 +123,8 @@ fn test_plan_event_entry_priority_medium() {
 
     let mut expected_entry = nvim_oxi::Dictionary::new();
     expected_entry.insert("content", "Task");
-    expected_entry.insert("priority", "Medium");
+    expected_entry.insert("priority", "medium");
+    expected_entry.insert("status", "pending");
     let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
     assert_eq!(*entries, nvim_oxi::Object::from(expected));
 }
@@ -133,7 +139,8 @@ fn test_plan_event_entry_priority_low() {
 
     let mut expected_entry = nvim_oxi::Dictionary::new();
     expected_entry.insert("content", "Task");
-    expected_entry.insert("priority", "Low");
+    expected_entry.insert("priority", "low");
+    expected_entry.insert("status", "pending");
     let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
     assert_eq!(*entries, nvim_oxi::Object::from(expected));
 }
@@ -159,3 +166,90 @@ fn test_plan_event_with_meta() {
     let result = plan_event(plan).unwrap();
     assert_eq!(result.get("meta").is_some(), true);
 }
+
+#[test]
+fn test_plan_event_entry_status_in_progress() {
+    let entry = PlanEntry::new("Task", PlanEntryPriority::Low, PlanEntryStatus::InProgress);
+    let plan = Plan::new(vec![entry]);
+
+    let result = plan_event(plan).unwrap();
+    let entries = result.get("entries").unwrap();
+
+    let mut expected_entry = nvim_oxi::Dictionary::new();
+    expected_entry.insert("content", "Task");
+    expected_entry.insert("priority", "low");
+    expected_entry.insert("status", "in_progress");
+    let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
+    assert_eq!(*entries, nvim_oxi::Object::from(expected));
+}
+
+#[test]
+fn test_plan_event_entry_status_completed() {
+    let entry = PlanEntry::new("Task", PlanEntryPriority::Low, PlanEntryStatus::Completed);
+    let plan = Plan::new(vec![entry]);
+
+    let result = plan_event(plan).unwrap();
+    let entries = result.get("entries").unwrap();
+
+    let mut expected_entry = nvim_oxi::Dictionary::new();
+    expected_entry.insert("content", "Task");
+    expected_entry.insert("priority", "low");
+    expected_entry.insert("status", "completed");
+    let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
+    assert_eq!(*entries, nvim_oxi::Object::from(expected));
+}
+
+#[test]
+fn test_plan_event_entry_with_meta() {
+    let meta: serde_json::Map<String, serde_json::Value> =
+        serde_json::json!({"step": 1}).as_object().unwrap().clone();
+    let entry = PlanEntry::new("Task", PlanEntryPriority::Low, PlanEntryStatus::Pending).meta(meta);
+    let plan = Plan::new(vec![entry]);
+
+    let result = plan_event(plan).unwrap();
+    let entries = result.get("entries").unwrap();
+
+    let mut expected_meta = nvim_oxi::Dictionary::new();
+    expected_meta.insert("step", 1i64);
+    let mut expected_entry = nvim_oxi::Dictionary::new();
+    expected_entry.insert("content", "Task");
+    expected_entry.insert("priority", "low");
+    expected_entry.insert("status", "pending");
+    expected_entry.insert("meta", expected_meta);
+    let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_entry)]);
+    assert_eq!(*entries, nvim_oxi::Object::from(expected));
+}
+
+#[test]
+fn test_plan_event_progress() {
+    let plan = Plan::new(vec![
+        PlanEntry::new("a", PlanEntryPriority::Low, PlanEntryStatus::Completed),
+        PlanEntry::new("b", PlanEntryPriority::Low, PlanEntryStatus::InProgress),
+        PlanEntry::new("c", PlanEntryPriority::Low, PlanEntryStatus::Completed),
+    ]);
+
+    let result = plan_event(plan).unwrap();
+
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("completed", 2i64);
+    expected.insert("total", 3i64);
+    assert_eq!(
+        *result.get("progress").unwrap(),
+        nvim_oxi::Object::from(expected)
+    );
+}
+
+#[test]
+fn test_plan_event_progress_empty() {
+    let plan = Plan::new(vec![]);
+
+    let result = plan_event(plan).unwrap();
+
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("completed", 0i64);
+    expected.insert("total", 0i64);
+    assert_eq!(
+        *result.get("progress").unwrap(),
+        nvim_oxi::Object::from(expected)
+    );
+}
@@ -0,0 +1,27 @@
+use agent_client_protocol::SessionId;
+use hermes::apc::history::SessionRecord;
+use hermes::nvim::parse::{dictionary_to_json, session_record};
+use serde_json::json;
+
+#[test]
+fn test_session_record() {
+    let record = SessionRecord {
+        session_id: SessionId::new("session-1"),
+        agent: "copilot".to_string(),
+        cwd: Some("/work".to_string()),
+        first_prompt: None,
+        timestamp: 10,
+        updated: 20,
+    };
+
+    assert_eq!(
+        dictionary_to_json(session_record(record)),
+        json!({
+            "sessionId": "session-1",
+            "agent": "copilot",
+            "cwd": "/work",
+            "timestamp": 10,
+            "updated": 20,
+        })
+    );
+}
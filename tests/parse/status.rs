@@ -0,0 +1,81 @@
+use agent_client_protocol::{
+    AgentCapabilities, InitializeResponse, ProtocolVersion, SessionId, SessionModeId,
+};
+use hermes::{
+    apc::{
+        connection::{Assistant, ConnectionId, ConnectionInfo, Protocol},
+        status::StatusTracker,
+    },
+    nvim::parse::agent_status,
+};
+use nvim_oxi::{Array, Dictionary, Object, conversion::FromObject};
+
+fn tracked() -> StatusTracker {
+    let mut tracker = StatusTracker::new();
+    let connection = ConnectionInfo {
+        id: ConnectionId::from("copilot-1"),
+        agent: Assistant::Copilot,
+        label: Some("review".to_string()),
+    };
+    let id = &connection.id;
+    tracker.connecting(&connection, Protocol::Stdio);
+    tracker.initialized(
+        id,
+        Some(42),
+        &InitializeResponse::new(ProtocolVersion::LATEST)
+            .agent_capabilities(AgentCapabilities::new().load_session(true)),
+    );
+    tracker.session_created(id, &SessionId::new("sess-1"));
+    tracker.turn_started(id, &SessionId::new("sess-1"));
+    tracker
+}
+
+#[test]
+fn test_agent_status_fields() {
+    let status = tracked()
+        .get(&ConnectionId::from("copilot-1"))
+        .unwrap()
+        .clone();
+    let result = agent_status(status, |_| None).unwrap();
+
+    assert_eq!(result.get("agent"), Some(&Object::from("copilot")));
+    assert_eq!(result.get("label"), Some(&Object::from("review")));
+    assert_eq!(result.get("state"), Some(&Object::from("initialized")));
+    assert_eq!(result.get("protocol"), Some(&Object::from("stdio")));
+    assert_eq!(result.get("pid"), Some(&Object::from(42)));
+    assert_eq!(result.get("protocolVersion"), Some(&Object::from(1)));
+    assert_eq!(result.get("lastError").is_none(), true);
+
+    let capabilities =
+        Dictionary::from_object(result.get("capabilities").unwrap().clone()).unwrap();
+    assert_eq!(capabilities.get("loadSession"), Some(&Object::from(true)));
+}
+
+#[test]
+fn test_agent_status_sessions() {
+    let status = tracked()
+        .get(&ConnectionId::from("copilot-1"))
+        .unwrap()
+        .clone();
+    let result = agent_status(status, |_| Some(SessionModeId::new("code"))).unwrap();
+
+    let sessions = Array::from_object(result.get("sessions").unwrap().clone()).unwrap();
+    assert_eq!(sessions.len(), 1);
+    let session = Dictionary::from_object(sessions.into_iter().next().unwrap()).unwrap();
+    assert_eq!(session.get("sessionId"), Some(&Object::from("sess-1")));
+    assert_eq!(session.get("mode"), Some(&Object::from("code")));
+    assert_eq!(session.get("prompting"), Some(&Object::from(true)));
+}
+
+#[test]
+fn test_agent_status_last_error() {
+    let mut tracker = tracked();
+    tracker.failed(&ConnectionId::from("copilot-1"), "boom");
+    let status = tracker
+        .get(&ConnectionId::from("copilot-1"))
+        .unwrap()
+        .clone();
+
+    let result = agent_status(status, |_| None).unwrap();
+    assert_eq!(result.get("lastError"), Some(&Object::from("boom")));
+}
@@ -3,8 +3,6 @@ use agent_client_protocol::{
     ToolKind,
 };
 use hermes::nvim::parse::tool_call_event;
-use std::io::Write;
-use tempfile::NamedTempFile;
 
 #[test]
 fn test_tool_call_event_ok() {
@@ -58,7 +56,7 @@ fn test_tool_call_event_kind_read() {
 
     let result = tool_call_event(tool_call).unwrap();
     let kind = result.get("kind").unwrap();
-    assert_eq!(*kind, nvim_oxi::Object::from("Read"));
+    assert_eq!(*kind, nvim_oxi::Object::from("read"));
 }
 
 #[test]
@@ -69,7 +67,7 @@ fn test_tool_call_event_kind_edit() {
 
     let result = tool_call_event(tool_call).unwrap();
     let kind = result.get("kind").unwrap();
-    assert_eq!(*kind, nvim_oxi::Object::from("Edit"));
+    assert_eq!(*kind, nvim_oxi::Object::from("edit"));
 }
 
 #[test]
@@ -80,7 +78,7 @@ fn test_tool_call_event_status_pending() {
 
     let result = tool_call_event(tool_call).unwrap();
     let status = result.get("status").unwrap();
-    assert_eq!(*status, nvim_oxi::Object::from("Pending"));
+    assert_eq!(*status, nvim_oxi::Object::from("pending"));
 }
 
 #[test]
@@ -91,7 +89,7 @@ fn test_tool_call_event_status_in_progress() {
 
     let result = tool_call_event(tool_call).unwrap();
     let status = result.get("status").unwrap();
-    assert_eq!(*status, nvim_oxi::Object::from("InProgress"));
+    assert_eq!(*status, nvim_oxi::Object::from("in_progress"));
 }
 
 #[test]
@@ -102,7 +100,7 @@ fn test_tool_call_event_status_completed() {
 
     let result = tool_call_event(tool_call).unwrap();
     let status = result.get("status").unwrap();
-    assert_eq!(*status, nvim_oxi::Object::from("Completed"));
+    assert_eq!(*status, nvim_oxi::Object::from("completed"));
 }
 
 #[test]
@@ -114,10 +112,8 @@ fn test_tool_call_event_input() {
 
     let result = tool_call_event(tool_call).unwrap();
     let input = result.get("input").unwrap();
-    assert_eq!(
-        *input,
-        nvim_oxi::Object::from(r#"{"file":"/path/to/file.txt"}"#)
-    );
+    let expected = nvim_oxi::Dictionary::from_iter([("file", "/path/to/file.txt")]);
+    assert_eq!(*input, nvim_oxi::Object::from(expected));
 }
 
 #[test]
@@ -129,7 +125,8 @@ fn test_tool_call_event_output() {
 
     let result = tool_call_event(tool_call).unwrap();
     let output = result.get("output").unwrap();
-    assert_eq!(*output, nvim_oxi::Object::from(r#"{"result":"success"}"#));
+    let expected = nvim_oxi::Dictionary::from_iter([("result", "success")]);
+    assert_eq!(*output, nvim_oxi::Object::from(expected));
 }
 
 #[test]
@@ -177,11 +174,7 @@ fn test_tool_call_event_with_text_content() {
 
 #[test]
 fn test_tool_call_event_with_location() {
-    let mut temp_file = NamedTempFile::new().unwrap();
-    temp_file.write_all(b"file contents").unwrap();
-    let path = temp_file.path().to_path_buf();
-
-    let location = ToolCallLocation::new(path);
+    let location = ToolCallLocation::new("/project/src/lib.rs");
     let tool_call = ToolCall::new(ToolCallId::new("call_014"), "Task".to_string())
         .kind(ToolKind::Read)
         .status(agent_client_protocol::ToolCallStatus::Pending)
@@ -191,8 +184,39 @@ fn test_tool_call_event_with_location() {
     let locations = result.get("locations").unwrap();
 
     let mut expected_dict = nvim_oxi::Dictionary::new();
-    expected_dict.insert("path", "file contents");
+    expected_dict.insert("path", "/project/src/lib.rs");
     let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_dict)]);
 
     assert_eq!(*locations, nvim_oxi::Object::from(expected));
 }
+
+#[test]
+fn test_tool_call_event_with_location_line() {
+    let location = ToolCallLocation::new("/project/src/lib.rs").line(42);
+    let tool_call = ToolCall::new(ToolCallId::new("call_015"), "Task".to_string())
+        .kind(ToolKind::Read)
+        .status(agent_client_protocol::ToolCallStatus::Pending)
+        .locations(vec![location]);
+
+    let result = tool_call_event(tool_call).unwrap();
+    let locations = result.get("locations").unwrap();
+
+    let mut expected_dict = nvim_oxi::Dictionary::new();
+    expected_dict.insert("path", "/project/src/lib.rs");
+    expected_dict.insert("line", 42);
+    let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_dict)]);
+
+    assert_eq!(*locations, nvim_oxi::Object::from(expected));
+}
+
+#[test]
+fn test_tool_call_event_with_missing_file_location() {
+    let location = ToolCallLocation::new("/does/not/exist.rs");
+    let tool_call = ToolCall::new(ToolCallId::new("call_016"), "Task".to_string())
+        .kind(ToolKind::Edit)
+        .status(agent_client_protocol::ToolCallStatus::Pending)
+        .locations(vec![location]);
+
+    let result = tool_call_event(tool_call);
+    assert!(result.is_ok());
+}
@@ -2,8 +2,6 @@ use agent_client_protocol::{
     Content, ContentBlock, Diff, Terminal, TerminalId, TextContent, ToolCallContent,
 };
 use hermes::nvim::parse::tool_call_content::parse_tool_call_content;
-use std::io::Write;
-use tempfile::NamedTempFile;
 
 #[test]
 fn test_parse_tool_call_content_ok() {
@@ -38,6 +36,17 @@ fn test_parse_tool_call_content_image_content() {
     assert_eq!(*type_field, nvim_oxi::Object::from("image"));
 }
 
+#[test]
+fn test_parse_tool_call_content_resource_link() {
+    let content = ToolCallContent::Content(Content::new(ContentBlock::ResourceLink(
+        agent_client_protocol::ResourceLink::new("log.txt", "file:///tmp/log.txt"),
+    )));
+    let dict = parse_tool_call_content(content).unwrap();
+
+    let type_field = dict.get("type").unwrap();
+    assert_eq!(*type_field, nvim_oxi::Object::from("resource_link"));
+}
+
 #[test]
 fn test_parse_tool_call_content_terminal() {
     let terminal = Terminal::new(TerminalId::new("term_123"));
@@ -53,11 +62,7 @@ fn test_parse_tool_call_content_terminal() {
 
 #[test]
 fn test_parse_tool_call_content_diff_without_old_text() {
-    let mut temp_file = NamedTempFile::new().unwrap();
-    temp_file.write_all(b"original content").unwrap();
-    let path = temp_file.path().to_path_buf();
-
-    let diff = Diff::new(path, "new content".to_string());
+    let diff = Diff::new("/project/src/main.rs", "new content".to_string());
     let content = ToolCallContent::Diff(diff);
     let dict = parse_tool_call_content(content).unwrap();
 
@@ -65,30 +70,40 @@ fn test_parse_tool_call_content_diff_without_old_text() {
     assert_eq!(*type_field, nvim_oxi::Object::from("diff"));
 
     let path_field = dict.get("path").unwrap();
-    assert_eq!(*path_field, nvim_oxi::Object::from("original content"));
+    assert_eq!(*path_field, nvim_oxi::Object::from("/project/src/main.rs"));
 
-    let new_text = dict.get("new_text").unwrap();
+    let new_text = dict.get("newText").unwrap();
     assert_eq!(*new_text, nvim_oxi::Object::from("new content"));
 
-    assert!(dict.get("old_text").is_none());
+    assert!(dict.get("oldText").is_none());
 }
 
 #[test]
 fn test_parse_tool_call_content_diff_with_old_text() {
-    let mut temp_file = NamedTempFile::new().unwrap();
-    temp_file.write_all(b"old content").unwrap();
-    let path = temp_file.path().to_path_buf();
-
-    let diff = Diff::new(path, "new content".to_string()).old_text(Some("old content".to_string()));
+    let diff = Diff::new("/project/src/main.rs", "new content".to_string())
+        .old_text(Some("old content".to_string()));
     let content = ToolCallContent::Diff(diff);
     let dict = parse_tool_call_content(content).unwrap();
 
     let type_field = dict.get("type").unwrap();
     assert_eq!(*type_field, nvim_oxi::Object::from("diff"));
 
-    let old_text = dict.get("old_text").unwrap();
+    let old_text = dict.get("oldText").unwrap();
     assert_eq!(*old_text, nvim_oxi::Object::from("old content"));
 
-    let new_text = dict.get("new_text").unwrap();
+    let new_text = dict.get("newText").unwrap();
     assert_eq!(*new_text, nvim_oxi::Object::from("new content"));
 }
+
+#[test]
+fn test_parse_tool_call_content_diff_for_new_file() {
+    let diff = Diff::new("/does/not/exist/yet.rs", "fn main() {}".to_string());
+    let content = ToolCallContent::Diff(diff);
+    let dict = parse_tool_call_content(content).unwrap();
+
+    let path_field = dict.get("path").unwrap();
+    assert_eq!(
+        *path_field,
+        nvim_oxi::Object::from("/does/not/exist/yet.rs")
+    );
+}
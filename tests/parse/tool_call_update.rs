@@ -1,6 +1,6 @@
 use agent_client_protocol::{
-    Content, ContentBlock, TextContent, ToolCallContent, ToolCallId, ToolCallUpdate,
-    ToolCallUpdateFields,
+    Content, ContentBlock, TextContent, ToolCallContent, ToolCallId, ToolCallLocation,
+    ToolCallStatus, ToolCallUpdate, ToolCallUpdateFields, ToolKind,
 };
 use hermes::nvim::parse::tool_call_update_event;
 
@@ -39,7 +39,17 @@ fn test_tool_call_update_event_without_fields_content() {
     let update = ToolCallUpdate::new(ToolCallId::new("call_002"), fields);
 
     let result = tool_call_update_event(update).unwrap();
-    assert_eq!(result.get("fields").is_some(), false);
+    assert_eq!(result.get("content").is_some(), false);
+}
+
+#[test]
+fn test_tool_call_update_event_omits_absent_fields() {
+    let fields = ToolCallUpdateFields::new();
+    let update = ToolCallUpdate::new(ToolCallId::new("call_002"), fields);
+
+    let result = tool_call_update_event(update).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result.get("id").is_some(), true);
 }
 
 #[test]
@@ -48,7 +58,7 @@ fn test_tool_call_update_event_with_empty_content() {
     let update = ToolCallUpdate::new(ToolCallId::new("call_003"), fields);
 
     let result = tool_call_update_event(update).unwrap();
-    let fields_arr = result.get("fields").unwrap();
+    let fields_arr = result.get("content").unwrap();
     assert_eq!(*fields_arr, nvim_oxi::Object::from(nvim_oxi::Array::new()));
 }
 
@@ -61,7 +71,7 @@ fn test_tool_call_update_event_with_text_content() {
     let update = ToolCallUpdate::new(ToolCallId::new("call_004"), fields);
 
     let result = tool_call_update_event(update).unwrap();
-    let fields_arr = result.get("fields").unwrap();
+    let fields_arr = result.get("content").unwrap();
 
     let mut expected_dict = nvim_oxi::Dictionary::new();
     expected_dict.insert("text", "Hello, world!");
@@ -92,3 +102,70 @@ fn test_tool_call_update_event_with_meta() {
     let result = tool_call_update_event(update).unwrap();
     assert_eq!(result.get("meta").is_some(), true);
 }
+
+#[test]
+fn test_tool_call_update_event_with_status() {
+    let fields = ToolCallUpdateFields::new().status(ToolCallStatus::InProgress);
+    let update = ToolCallUpdate::new(ToolCallId::new("call_007"), fields);
+
+    let result = tool_call_update_event(update).unwrap();
+    assert_eq!(
+        *result.get("status").unwrap(),
+        nvim_oxi::Object::from("in_progress")
+    );
+    assert_eq!(result.get("title").is_some(), false);
+}
+
+#[test]
+fn test_tool_call_update_event_with_title() {
+    let fields = ToolCallUpdateFields::new().title("Reading main.rs".to_string());
+    let update = ToolCallUpdate::new(ToolCallId::new("call_008"), fields);
+
+    let result = tool_call_update_event(update).unwrap();
+    let title = result.get("title").unwrap();
+    assert_eq!(*title, nvim_oxi::Object::from("Reading main.rs"));
+}
+
+#[test]
+fn test_tool_call_update_event_with_kind() {
+    let fields = ToolCallUpdateFields::new().kind(ToolKind::Edit);
+    let update = ToolCallUpdate::new(ToolCallId::new("call_009"), fields);
+
+    let result = tool_call_update_event(update).unwrap();
+    assert_eq!(*result.get("kind").unwrap(), nvim_oxi::Object::from("edit"));
+}
+
+#[test]
+fn test_tool_call_update_event_with_locations() {
+    let fields = ToolCallUpdateFields::new()
+        .locations(vec![ToolCallLocation::new("/project/src/main.rs").line(3)]);
+    let update = ToolCallUpdate::new(ToolCallId::new("call_010"), fields);
+
+    let result = tool_call_update_event(update).unwrap();
+    let locations = result.get("locations").unwrap();
+
+    let mut expected_dict = nvim_oxi::Dictionary::new();
+    expected_dict.insert("path", "/project/src/main.rs");
+    expected_dict.insert("line", 3);
+    let expected = nvim_oxi::Array::from_iter([nvim_oxi::Object::from(expected_dict)]);
+
+    assert_eq!(*locations, nvim_oxi::Object::from(expected));
+}
+
+#[test]
+fn test_tool_call_update_event_with_raw_input_and_output() {
+    let fields = ToolCallUpdateFields::new()
+        .raw_input(serde_json::json!({"command": "ls"}))
+        .raw_output(serde_json::json!({"exitCode": 0}));
+    let update = ToolCallUpdate::new(ToolCallId::new("call_011"), fields);
+
+    let result = tool_call_update_event(update).unwrap();
+
+    let input = result.get("input").unwrap();
+    let expected_input = nvim_oxi::Dictionary::from_iter([("command", "ls")]);
+    assert_eq!(*input, nvim_oxi::Object::from(expected_input));
+
+    let output = result.get("output").unwrap();
+    let expected_output = nvim_oxi::Dictionary::from_iter([("exitCode", 0)]);
+    assert_eq!(*output, nvim_oxi::Object::from(expected_output));
+}
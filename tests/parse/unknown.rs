@@ -0,0 +1,95 @@
+use agent_client_protocol::{ContentBlock, SessionUpdate, TextContent, ToolCallContent};
+use hermes::apc::connection::unknown::UnknownUpdate;
+use hermes::nvim::parse::{unknown_event, unknown_update_event};
+
+#[test]
+fn test_unknown_event_ok() {
+    let result = unknown_event(
+        "session_update",
+        &serde_json::json!({"sessionUpdate": "new"}),
+    );
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn test_unknown_event_kind() {
+    let result = unknown_event("content_block", &serde_json::json!({})).unwrap();
+
+    let kind = result.get("kind").unwrap();
+    assert_eq!(*kind, nvim_oxi::Object::from("content_block"));
+}
+
+#[test]
+fn test_unknown_event_value_is_table() {
+    let value = serde_json::json!({"sessionUpdate": "usage_update", "used": 10});
+    let result = unknown_event("session_update", &value).unwrap();
+
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("sessionUpdate", "usage_update");
+    expected.insert("used", 10);
+    assert_eq!(
+        *result.get("value").unwrap(),
+        nvim_oxi::Object::from(expected)
+    );
+}
+
+#[test]
+fn test_unknown_event_serializes_session_update() {
+    let update = SessionUpdate::AgentMessageChunk(agent_client_protocol::ContentChunk::new(
+        ContentBlock::Text(TextContent::new("Hello")),
+    ));
+    let result = unknown_event("session_update", &update).unwrap();
+
+    let mut content = nvim_oxi::Dictionary::new();
+    content.insert("text", "Hello");
+    content.insert("type", "text");
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("content", content);
+    expected.insert("sessionUpdate", "agent_message_chunk");
+    assert_eq!(
+        *result.get("value").unwrap(),
+        nvim_oxi::Object::from(expected)
+    );
+}
+
+#[test]
+fn test_unknown_event_serializes_tool_call_content() {
+    let content = ToolCallContent::Content(agent_client_protocol::Content::new(
+        ContentBlock::Text(TextContent::new("Hello")),
+    ));
+    let result = unknown_event("tool_call_content", &content);
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn test_unknown_update_event_from_an_unknown_content_type() {
+    let params = serde_json::json!({
+        "sessionId": "session-1",
+        "update": {
+            "sessionUpdate": "tool_call_update",
+            "toolCallId": "call-1",
+            "content": [
+                { "type": "content", "content": { "type": "text", "text": "Hello" } },
+                { "type": "video", "uri": "file:///tmp/demo.mp4" }
+            ]
+        }
+    });
+    let update = UnknownUpdate::from_params(&params).unwrap();
+    let result = unknown_update_event(update).unwrap();
+
+    let mut expected = nvim_oxi::Dictionary::new();
+    expected.insert("type", "video");
+    expected.insert("uri", "file:///tmp/demo.mp4");
+    assert_eq!(
+        *result.get("kind").unwrap(),
+        nvim_oxi::Object::from("tool_call_content")
+    );
+    assert_eq!(
+        *result.get("value").unwrap(),
+        nvim_oxi::Object::from(expected)
+    );
+    assert_eq!(
+        *result.get("toolCallId").unwrap(),
+        nvim_oxi::Object::from("call-1")
+    );
+}
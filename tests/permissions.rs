@@ -0,0 +1,108 @@
+use agent_client_protocol::{
+    PermissionOption, PermissionOptionKind, RequestPermissionOutcome, RequestPermissionRequest,
+    SelectedPermissionOutcome, SessionId, ToolCallId, ToolCallUpdate, ToolCallUpdateFields,
+    ToolKind,
+};
+use hermes::apc::permissions::{PermissionPolicy, PermissionRule};
+
+fn request(kind: ToolKind, options: Vec<PermissionOption>) -> RequestPermissionRequest {
+    RequestPermissionRequest::new(
+        SessionId::new("session_1"),
+        ToolCallUpdate::new(
+            ToolCallId::new("call_1"),
+            ToolCallUpdateFields::new().kind(kind),
+        ),
+        options,
+    )
+}
+
+fn options() -> Vec<PermissionOption> {
+    vec![
+        PermissionOption::new("always", "Always allow", PermissionOptionKind::AllowAlways),
+        PermissionOption::new("once", "Allow once", PermissionOptionKind::AllowOnce),
+        PermissionOption::new("reject", "Reject", PermissionOptionKind::RejectOnce),
+    ]
+}
+
+fn selected(option_id: &str) -> RequestPermissionOutcome {
+    RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(option_id.to_string()))
+}
+
+#[test]
+fn test_default_policy_asks() {
+    let policy = PermissionPolicy::default();
+    assert!(
+        policy
+            .respond(&request(ToolKind::Read, options()))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_allow_prefers_allowing_once() {
+    let policy = PermissionPolicy::new(PermissionRule::Allow);
+    let response = policy.respond(&request(ToolKind::Read, options())).unwrap();
+    assert_eq!(response.outcome, selected("once"));
+}
+
+#[test]
+fn test_deny_rejects() {
+    let policy = PermissionPolicy::new(PermissionRule::Deny);
+    let response = policy.respond(&request(ToolKind::Read, options())).unwrap();
+    assert_eq!(response.outcome, selected("reject"));
+}
+
+#[test]
+fn test_rule_for_kind_overrides_default() {
+    let policy =
+        PermissionPolicy::new(PermissionRule::Allow).rule(ToolKind::Execute, PermissionRule::Ask);
+
+    assert!(
+        policy
+            .respond(&request(ToolKind::Execute, options()))
+            .is_none()
+    );
+    assert!(
+        policy
+            .respond(&request(ToolKind::Read, options()))
+            .is_some()
+    );
+}
+
+#[test]
+fn test_tightened_by_keeps_the_stricter_rules() {
+    let user =
+        PermissionPolicy::new(PermissionRule::Deny).rule(ToolKind::Read, PermissionRule::Allow);
+    let project = PermissionPolicy::new(PermissionRule::Allow)
+        .rule(ToolKind::Read, PermissionRule::Ask)
+        .rule(ToolKind::Execute, PermissionRule::Allow);
+
+    let policy = user.tightened_by(&project);
+    assert_eq!(policy.rule_for(None), PermissionRule::Deny);
+    assert_eq!(policy.rule_for(Some(ToolKind::Read)), PermissionRule::Ask);
+    assert_eq!(
+        policy.rule_for(Some(ToolKind::Execute)),
+        PermissionRule::Deny
+    );
+}
+
+#[test]
+fn test_later_rule_replaces_earlier_rule() {
+    let policy = PermissionPolicy::default()
+        .rule(ToolKind::Edit, PermissionRule::Deny)
+        .rule(ToolKind::Edit, PermissionRule::Allow);
+
+    assert_eq!(policy.rule_for(Some(ToolKind::Edit)), PermissionRule::Allow);
+}
+
+#[test]
+fn test_asks_when_no_option_matches() {
+    let policy = PermissionPolicy::new(PermissionRule::Deny);
+    let options = vec![PermissionOption::new(
+        "once",
+        "Allow once",
+        PermissionOptionKind::AllowOnce,
+    )];
+
+    assert!(policy.respond(&request(ToolKind::Read, options)).is_none());
+}
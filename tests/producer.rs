@@ -0,0 +1,377 @@
+use agent_client_protocol::{
+    Client, ContentBlock, ExtNotification, Implementation, InitializeResponse, ProtocolVersion,
+    SessionId, StopReason,
+};
+use hermes::apc::{
+    connection::{
+        Assistant, ConnectionId, ConnectionInfo, Protocol, unknown::UNKNOWN_UPDATE_METHOD,
+    },
+    history::History,
+    transcript::MessageRole,
+};
+use hermes::nvim::{parse, producer::EventHandler};
+use serde_json::{Value, json};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use tempfile::TempDir;
+
+type Events = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// A handler recording to `dir` whose events are collected rather than fired
+fn collecting(dir: &TempDir) -> (EventHandler, Events) {
+    let events = Events::default();
+    let collected = events.clone();
+    let handler = EventHandler::with_sink(Arc::new(move |command, data| {
+        collected
+            .lock()
+            .unwrap()
+            .push((command.to_string(), parse::dictionary_to_json(data)));
+        Ok(())
+    }))
+    .with_history(History::new(dir.path()));
+    (handler, events)
+}
+
+fn names(events: &Events) -> Vec<String> {
+    events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(command, _)| command.clone())
+        .collect()
+}
+
+fn review_connection() -> ConnectionInfo {
+    ConnectionInfo {
+        id: ConnectionId::from("copilot-1"),
+        agent: Assistant::Copilot,
+        label: Some("review".to_string()),
+    }
+}
+
+/// The only event fired, with its payload
+fn fired(events: &Events) -> (String, Value) {
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1, "{:?}", events);
+    events[0].clone()
+}
+
+#[test]
+fn test_agent_connecting() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+
+    handler
+        .agent_connecting(&review_connection(), &Protocol::Stdio)
+        .unwrap();
+
+    assert_eq!(
+        fired(&events),
+        (
+            "AgentConnecting".to_string(),
+            json!({
+                "agent": "copilot",
+                "connection": "copilot-1",
+                "label": "review",
+                "protocol": "stdio"
+            })
+        )
+    );
+}
+
+#[test]
+fn test_agent_connected() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+    let response = InitializeResponse::new(ProtocolVersion::LATEST)
+        .agent_info(Implementation::new("copilot", "1.2.0"));
+
+    handler
+        .agent_connected(&review_connection(), response)
+        .unwrap();
+
+    let (command, data) = fired(&events);
+    assert_eq!(command, "AgentConnected");
+    assert_eq!(data["agent"], json!("copilot"));
+    assert_eq!(data["connection"], json!("copilot-1"));
+    assert_eq!(data["label"], json!("review"));
+    assert_eq!(data["protocolVersion"], json!(1));
+    assert_eq!(data["agentInfo"]["name"], json!("copilot"));
+    assert_eq!(data["agentInfo"]["version"], json!("1.2.0"));
+    assert!(data["agentCapabilities"].is_object());
+    assert!(data.get("authMethods").is_some());
+}
+
+#[test]
+fn test_agent_connection_failed() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+
+    handler
+        .agent_connection_failed(&review_connection(), "`copilot` not found")
+        .unwrap();
+
+    assert_eq!(
+        fired(&events),
+        (
+            "AgentConnectionFailed".to_string(),
+            json!({
+                "agent": "copilot",
+                "connection": "copilot-1",
+                "label": "review",
+                "error": "`copilot` not found"
+            })
+        )
+    );
+}
+
+#[test]
+fn test_agent_disconnected() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+
+    handler
+        .agent_disconnected(
+            &review_connection(),
+            &[SessionId::new("session-1"), SessionId::new("session-2")],
+        )
+        .unwrap();
+
+    assert_eq!(
+        fired(&events),
+        (
+            "AgentDisconnected".to_string(),
+            json!({
+                "agent": "copilot",
+                "connection": "copilot-1",
+                "label": "review",
+                "sessions": ["session-1", "session-2"]
+            })
+        )
+    );
+}
+
+#[test]
+fn test_session_created() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+
+    handler
+        .session_created(
+            &review_connection(),
+            &SessionId::new("session-1"),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        fired(&events),
+        (
+            "SessionCreated".to_string(),
+            json!({
+                "agent": "copilot",
+                "connection": "copilot-1",
+                "label": "review",
+                "cwd": "/work/project",
+                "sessionId": "session-1"
+            })
+        )
+    );
+}
+
+#[test]
+fn test_session_loaded() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+
+    handler
+        .session_loaded(
+            &review_connection(),
+            &SessionId::new("session-1"),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        fired(&events),
+        (
+            "SessionLoaded".to_string(),
+            json!({
+                "agent": "copilot",
+                "connection": "copilot-1",
+                "label": "review",
+                "cwd": "/work/project",
+                "sessionId": "session-1"
+            })
+        )
+    );
+}
+
+#[test]
+fn test_prompt_started() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+
+    handler
+        .prompt_started(&review_connection(), &SessionId::new("session-1"))
+        .unwrap();
+
+    assert_eq!(
+        fired(&events),
+        (
+            "PromptStarted".to_string(),
+            json!({
+                "agent": "copilot",
+                "connection": "copilot-1",
+                "label": "review",
+                "sessionId": "session-1"
+            })
+        )
+    );
+}
+
+#[test]
+fn test_prompt_finished() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+    let session_id = SessionId::new("session-1");
+
+    handler
+        .prompt_finished(&review_connection(), &session_id, Ok(StopReason::MaxTokens))
+        .unwrap();
+    handler
+        .prompt_finished(&review_connection(), &session_id, Err("Connection lost"))
+        .unwrap();
+
+    let events = events.lock().unwrap();
+    assert_eq!(
+        *events,
+        vec![
+            (
+                "PromptFinished".to_string(),
+                json!({
+                    "agent": "copilot",
+                    "connection": "copilot-1",
+                    "label": "review",
+                    "stopReason": "max_tokens",
+                    "sessionId": "session-1"
+                })
+            ),
+            (
+                "PromptFinished".to_string(),
+                json!({
+                    "agent": "copilot",
+                    "connection": "copilot-1",
+                    "label": "review",
+                    "error": "Connection lost",
+                    "sessionId": "session-1"
+                })
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_prompt_is_recorded() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+    let session_id = SessionId::new("session-1");
+
+    handler
+        .record_prompt(&session_id, vec!["Fix the build".into()])
+        .unwrap();
+
+    assert_eq!(names(&events), vec!["UserMessageComplete"]);
+    let entries = handler.history().unwrap().entries(&session_id).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].event, "UserMessageComplete");
+    assert_eq!(entries[0].data["text"], json!("Fix the build"));
+    assert_eq!(entries[0].data["sessionId"], json!("session-1"));
+}
+
+#[test]
+fn test_prompt_completes_the_streamed_message_first() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+    let session_id = SessionId::new("session-1");
+    handler.transcript().lock().unwrap().push(
+        &session_id,
+        MessageRole::Agent,
+        ContentBlock::from("Done"),
+    );
+
+    handler
+        .record_prompt(&session_id, vec!["Thanks".into()])
+        .unwrap();
+
+    assert_eq!(
+        names(&events),
+        vec!["AgentMessageComplete", "UserMessageComplete"]
+    );
+    let texts: Vec<Value> = handler
+        .history()
+        .unwrap()
+        .entries(&session_id)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.data["text"].clone())
+        .collect();
+    assert_eq!(texts, vec![json!("Done"), json!("Thanks")]);
+}
+
+#[test]
+fn test_recorded_session_starts_with_the_first_prompt() {
+    let dir = TempDir::new().unwrap();
+    let (handler, _) = collecting(&dir);
+    let connection = ConnectionInfo {
+        id: ConnectionId::from("copilot-1"),
+        agent: Assistant::Copilot,
+        label: None,
+    };
+    let session_id = SessionId::new("session-1");
+
+    handler
+        .session_created(&connection, &session_id, Path::new("/work/project"))
+        .unwrap();
+    handler
+        .record_prompt(&session_id, vec!["Fix the build".into()])
+        .unwrap();
+    handler
+        .record_prompt(&session_id, vec!["Now the tests".into()])
+        .unwrap();
+
+    let record = handler.history().unwrap().session(&session_id).unwrap();
+    assert_eq!(record.agent, "copilot");
+    assert_eq!(record.cwd.as_deref(), Some("/work/project"));
+    assert_eq!(record.first_prompt.as_deref(), Some("Fix the build"));
+}
+
+#[test]
+fn test_undecodable_update_fires_unknown_update() {
+    let dir = TempDir::new().unwrap();
+    let (handler, events) = collecting(&dir);
+    let params = serde_json::value::to_raw_value(&json!({
+        "sessionId": "session-1",
+        "update": { "sessionUpdate": "usage_update", "used": 10 }
+    }))
+    .unwrap();
+
+    tokio_test::block_on(
+        handler.ext_notification(ExtNotification::new(UNKNOWN_UPDATE_METHOD, params.into())),
+    )
+    .unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![(
+            "AgentUnknownUpdate".to_string(),
+            json!({
+                "kind": "session_update",
+                "value": { "sessionUpdate": "usage_update", "used": 10 },
+                "sessionId": "session-1"
+            })
+        )]
+    );
+}
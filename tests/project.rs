@@ -0,0 +1,130 @@
+use hermes::nvim::project::{self, TrustStore};
+use nvim_oxi::{Array, Dictionary, conversion::FromObject};
+use std::path::Path;
+
+#[test]
+fn test_workspace_root_is_nearest_git_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    let nested = dir.path().join("src/nvim");
+    std::fs::create_dir_all(&nested).unwrap();
+
+    assert_eq!(project::workspace_root(&nested), dir.path());
+}
+
+#[test]
+fn test_workspace_root_is_nearest_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    let member = dir.path().join("crates/member");
+    std::fs::create_dir_all(member.join("src")).unwrap();
+    std::fs::write(member.join(".hermes.toml"), "").unwrap();
+
+    assert_eq!(project::workspace_root(&member.join("src")), member);
+}
+
+#[test]
+fn test_find_prefers_json() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(project::find(dir.path()), None);
+
+    std::fs::write(dir.path().join(".hermes.toml"), "").unwrap();
+    assert_eq!(
+        project::find(dir.path()),
+        Some(dir.path().join(".hermes.toml"))
+    );
+
+    std::fs::write(dir.path().join(".hermes.json"), "{}").unwrap();
+    assert_eq!(
+        project::find(dir.path()),
+        Some(dir.path().join(".hermes.json"))
+    );
+}
+
+#[test]
+fn test_parse_json() {
+    let options = project::parse(
+        Path::new(".hermes.json"),
+        r#"{"fs_roots": ["src"], "permissions": {"read": "allow"}}"#,
+    )
+    .unwrap();
+
+    let roots = Array::from_object(options.get("fs_roots").unwrap().clone()).unwrap();
+    assert_eq!(roots.len(), 1);
+    let permissions = Dictionary::from_object(options.get("permissions").unwrap().clone()).unwrap();
+    assert!(permissions.get("read").is_some());
+}
+
+#[test]
+fn test_parse_toml() {
+    let options = project::parse(
+        Path::new(".hermes.toml"),
+        "fs_roots = [\"src\"]\n\n[agents.gemini]\ncommand = \"gemini\"\nargs = [\"--acp\"]\n",
+    )
+    .unwrap();
+
+    let agents = Dictionary::from_object(options.get("agents").unwrap().clone()).unwrap();
+    assert!(agents.get("gemini").is_some());
+    assert!(options.get("fs_roots").is_some());
+}
+
+#[test]
+fn test_parse_error_names_the_file() {
+    match project::parse(Path::new("/work/.hermes.json"), "{") {
+        Err(nvim_oxi::lua::Error::RuntimeError(message)) => {
+            assert!(message.starts_with("Invalid project config /work/.hermes.json: "))
+        }
+        other => panic!("expected a runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_trust_store_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("hermes/trust.json");
+    let hash = project::content_hash("fs_roots = [\"src\"]\n");
+    let mut store = TrustStore::load(&path).unwrap();
+    assert_eq!(store.decision(Path::new("/work/project"), &hash), None);
+
+    store.record(Path::new("/work/project"), &hash, true);
+    store.record(Path::new("/work/other"), &hash, false);
+    store.save(&path).unwrap();
+
+    let mut store = TrustStore::load(&path).unwrap();
+    assert_eq!(
+        store.decision(Path::new("/work/project"), &hash),
+        Some(true)
+    );
+    assert_eq!(store.decision(Path::new("/work/other"), &hash), Some(false));
+
+    store.record(Path::new("/work/other"), &hash, true);
+    assert_eq!(store.decision(Path::new("/work/other"), &hash), Some(true));
+}
+
+#[test]
+fn test_trust_is_asked_again_when_the_config_changes() {
+    let trusted = project::content_hash("fs_roots = [\"src\"]\n");
+    let changed = project::content_hash("[agents.gemini]\ncommand = \"./evil\"\n");
+    let mut store = TrustStore::default();
+    store.record(Path::new("/work/project"), &trusted, true);
+
+    assert_ne!(trusted, changed);
+    assert_eq!(store.decision(Path::new("/work/project"), &changed), None);
+    assert!(store.changed(Path::new("/work/project"), &changed));
+    assert!(!store.changed(Path::new("/work/project"), &trusted));
+    assert!(!store.changed(Path::new("/work/other"), &changed));
+}
+
+#[test]
+fn test_ignored_config_is_asked_about_again_when_it_changes() {
+    let ignored = project::content_hash("fs_roots = [\"src\"]\n");
+    let changed = project::content_hash("fs_roots = [\"lib\"]\n");
+    let mut store = TrustStore::default();
+    store.record(Path::new("/work/project"), &ignored, false);
+
+    assert_eq!(
+        store.decision(Path::new("/work/project"), &ignored),
+        Some(false)
+    );
+    assert_eq!(store.decision(Path::new("/work/project"), &changed), None);
+}
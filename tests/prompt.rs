@@ -0,0 +1,186 @@
+use agent_client_protocol::{ContentBlock, EmbeddedResourceResource};
+use hermes::nvim::prompt::{audio_file, audio_mime_type, content_block, content_blocks};
+use nvim_oxi::{Dictionary, Object};
+use std::{io::Write, path::Path};
+use tempfile::Builder;
+
+#[test]
+fn test_content_block_from_string_is_text() {
+    let block = content_block(Object::from("Hello")).unwrap();
+
+    match block {
+        ContentBlock::Text(text) => assert_eq!(text.text, "Hello"),
+        _ => panic!("expected a text block"),
+    }
+}
+
+#[test]
+fn test_content_block_text_table() {
+    let table = Dictionary::from_iter([("type", "text"), ("text", "Hello")]);
+    let block = content_block(Object::from(table)).unwrap();
+
+    match block {
+        ContentBlock::Text(text) => assert_eq!(text.text, "Hello"),
+        _ => panic!("expected a text block"),
+    }
+}
+
+#[test]
+fn test_content_block_text_table_missing_text() {
+    let table = Dictionary::from_iter([("type", "text")]);
+    let result = content_block(Object::from(table));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_content_block_audio_from_data() {
+    let table = Dictionary::from_iter([
+        ("type", "audio"),
+        ("data", "UklGRg=="),
+        ("mimeType", "audio/wav"),
+    ]);
+    let block = content_block(Object::from(table)).unwrap();
+
+    match block {
+        ContentBlock::Audio(audio) => {
+            assert_eq!(audio.data, "UklGRg==");
+            assert_eq!(audio.mime_type, "audio/wav");
+        }
+        _ => panic!("expected an audio block"),
+    }
+}
+
+#[test]
+fn test_content_block_audio_data_requires_mime_type() {
+    let table = Dictionary::from_iter([("type", "audio"), ("data", "UklGRg==")]);
+    let result = content_block(Object::from(table));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_content_block_audio_requires_path_or_data() {
+    let table = Dictionary::from_iter([("type", "audio")]);
+    let result = content_block(Object::from(table));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_content_block_audio_from_path() {
+    let mut file = Builder::new().suffix(".wav").tempfile().unwrap();
+    file.write_all(b"RIFF").unwrap();
+    let path = file.path().to_string_lossy().to_string();
+
+    let table = Dictionary::from_iter([("type", "audio"), ("path", path.as_str())]);
+    let block = content_block(Object::from(table)).unwrap();
+
+    match block {
+        ContentBlock::Audio(audio) => {
+            assert_eq!(audio.data, "UklGRg==");
+            assert_eq!(audio.mime_type, "audio/wav");
+        }
+        _ => panic!("expected an audio block"),
+    }
+}
+
+#[test]
+fn test_audio_file_missing_file() {
+    let result = audio_file(Path::new("/does/not/exist.wav"), None);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_audio_file_unknown_extension_without_mime_type() {
+    let file = Builder::new().suffix(".bin").tempfile().unwrap();
+    let result = audio_file(file.path(), None);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_audio_file_unknown_extension_with_mime_type() {
+    let file = Builder::new().suffix(".bin").tempfile().unwrap();
+    let audio = audio_file(file.path(), Some("audio/x-custom".to_string())).unwrap();
+
+    assert_eq!(audio.mime_type, "audio/x-custom");
+}
+
+#[test]
+fn test_audio_mime_type() {
+    assert_eq!(audio_mime_type(Path::new("a.wav")), Some("audio/wav"));
+    assert_eq!(audio_mime_type(Path::new("a.MP3")), Some("audio/mpeg"));
+    assert_eq!(audio_mime_type(Path::new("a.ogg")), Some("audio/ogg"));
+    assert_eq!(audio_mime_type(Path::new("a.txt")), None);
+    assert_eq!(audio_mime_type(Path::new("a")), None);
+}
+
+#[test]
+fn test_content_block_unsupported_type() {
+    let table = Dictionary::from_iter([("type", "video")]);
+    let result = content_block(Object::from(table));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_content_blocks_preserves_order() {
+    let audio = Dictionary::from_iter([
+        ("type", "audio"),
+        ("data", "AAAA"),
+        ("mimeType", "audio/ogg"),
+    ]);
+    let blocks =
+        content_blocks(vec![Object::from("Transcribe this"), Object::from(audio)]).unwrap();
+
+    assert_eq!(blocks.len(), 2);
+    assert!(matches!(blocks[0], ContentBlock::Text(_)));
+    assert!(matches!(blocks[1], ContentBlock::Audio(_)));
+}
+
+#[test]
+fn test_content_block_resource() {
+    let table = Dictionary::from_iter([
+        ("type", "resource"),
+        ("uri", "file:///src/main.rs"),
+        ("text", "fn main() {}"),
+        ("mimeType", "text/x-rust"),
+    ]);
+    let block = content_block(Object::from(table)).unwrap();
+
+    match block {
+        ContentBlock::Resource(resource) => match resource.resource {
+            EmbeddedResourceResource::TextResourceContents(contents) => {
+                assert_eq!(contents.uri, "file:///src/main.rs");
+                assert_eq!(contents.text, "fn main() {}");
+                assert_eq!(contents.mime_type.as_deref(), Some("text/x-rust"));
+            }
+            _ => panic!("expected text contents"),
+        },
+        _ => panic!("expected a resource block"),
+    }
+}
+
+#[test]
+fn test_content_block_resource_requires_uri() {
+    let table = Dictionary::from_iter([("type", "resource"), ("text", "fn main() {}")]);
+    let result = content_block(Object::from(table));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_content_block_resource_link_defaults_name_to_uri() {
+    let table = Dictionary::from_iter([("type", "resource_link"), ("uri", "file:///README.md")]);
+    let block = content_block(Object::from(table)).unwrap();
+
+    match block {
+        ContentBlock::ResourceLink(link) => {
+            assert_eq!(link.uri, "file:///README.md");
+            assert_eq!(link.name, "file:///README.md");
+        }
+        _ => panic!("expected a resource link block"),
+    }
+}
@@ -0,0 +1,446 @@
+use agent_client_protocol::{
+    EnvVariable, HttpHeader, McpServer, McpServerHttp, McpServerStdio, ToolKind,
+};
+use hermes::{
+    apc::{
+        connection::{AgentDefinition, Assistant},
+        permissions::PermissionRule,
+    },
+    nvim::setup::Setup,
+};
+use nvim_oxi::{Array, Dictionary, Object};
+use std::path::{Path, PathBuf};
+
+fn options(entries: Vec<(&str, Object)>) -> Dictionary {
+    Dictionary::from_iter(entries)
+}
+
+fn error(options: Dictionary) -> String {
+    match Setup::from_dictionary(options) {
+        Err(nvim_oxi::lua::Error::RuntimeError(message)) => message,
+        other => panic!("expected a runtime error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_empty_options_use_defaults() {
+    let setup = Setup::from_dictionary(Dictionary::new()).unwrap();
+
+    assert_eq!(setup.client.name, "hermes");
+    assert!(setup.client.fs_read_access);
+    assert!(setup.client.fs_write_access);
+    assert!(setup.client.terminal_access);
+    assert!(setup.agents.is_empty());
+    assert_eq!(setup.permissions.rule_for(None), PermissionRule::Ask);
+}
+
+#[test]
+fn test_client_info() {
+    let client = Dictionary::from_iter([("name", "my-editor"), ("version", "1.2.3")]);
+    let setup = Setup::from_dictionary(options(vec![("client", client.into())])).unwrap();
+
+    assert_eq!(setup.client.name, "my-editor");
+    assert_eq!(setup.client.version, "1.2.3");
+}
+
+#[test]
+fn test_capabilities() {
+    let capabilities = Dictionary::from_iter([("fs_write", false), ("terminal", false)]);
+    let setup =
+        Setup::from_dictionary(options(vec![("capabilities", capabilities.into())])).unwrap();
+
+    assert!(setup.client.fs_read_access);
+    assert!(!setup.client.fs_write_access);
+    assert!(!setup.client.terminal_access);
+}
+
+#[test]
+fn test_empty_table_is_accepted() {
+    let setup = Setup::from_dictionary(options(vec![("capabilities", Array::new().into())]));
+    assert!(setup.is_ok());
+}
+
+#[test]
+fn test_agents() {
+    let mut gemini = Dictionary::new();
+    gemini.insert("command", "gemini");
+    gemini.insert("args", Array::from_iter(["--experimental-acp"]));
+    gemini.insert("env", Dictionary::from_iter([("DEBUG", "1")]));
+    let agents = Dictionary::from_iter([("gemini", gemini)]);
+
+    let setup = Setup::from_dictionary(options(vec![("agents", agents.into())])).unwrap();
+
+    let mut expected = AgentDefinition::new("gemini", ["--experimental-acp"]);
+    expected.env.insert("DEBUG".to_string(), "1".to_string());
+    assert_eq!(
+        setup.agents.get(&Assistant::Custom("gemini".to_string())),
+        Some(&expected)
+    );
+}
+
+#[test]
+fn test_agents_override_builtin() {
+    let copilot = Dictionary::from_iter([("command", "copilot-language-server")]);
+    let agents = Dictionary::from_iter([("copilot", copilot)]);
+
+    let setup = Setup::from_dictionary(options(vec![("agents", agents.into())])).unwrap();
+
+    assert_eq!(
+        setup.agents.get(&Assistant::Copilot),
+        Some(&AgentDefinition::new(
+            "copilot-language-server",
+            Vec::<String>::new()
+        ))
+    );
+}
+
+#[test]
+fn test_permissions() {
+    let permissions = Dictionary::from_iter([
+        ("default", "deny"),
+        ("read", "allow"),
+        ("switch_mode", "ask"),
+    ]);
+    let setup = Setup::from_dictionary(options(vec![("permissions", permissions.into())])).unwrap();
+
+    assert_eq!(setup.permissions.rule_for(None), PermissionRule::Deny);
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Read)),
+        PermissionRule::Allow
+    );
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::SwitchMode)),
+        PermissionRule::Ask
+    );
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Edit)),
+        PermissionRule::Deny
+    );
+}
+
+#[test]
+fn test_unknown_option() {
+    assert_eq!(
+        error(options(vec![("colors", true.into())])),
+        "Unknown option \"colors\", expected one of: client, capabilities, agents, fs_roots, permissions, mcp_servers, editor_mcp"
+    );
+}
+
+#[test]
+fn test_unknown_nested_option() {
+    let capabilities = Dictionary::from_iter([("network", true)]);
+    assert_eq!(
+        error(options(vec![("capabilities", capabilities.into())])),
+        "Unknown option \"capabilities.network\", expected one of: fs_read, fs_write, terminal"
+    );
+}
+
+#[test]
+fn test_invalid_capability_type() {
+    let capabilities = Dictionary::from_iter([("terminal", "yes")]);
+    assert_eq!(
+        error(options(vec![("capabilities", capabilities.into())])),
+        "Invalid input for \"capabilities.terminal\", must be a boolean"
+    );
+}
+
+#[test]
+fn test_invalid_table_type() {
+    assert_eq!(
+        error(options(vec![("agents", "copilot".into())])),
+        "Invalid input for \"agents\", must be a table"
+    );
+}
+
+#[test]
+fn test_agent_without_command() {
+    let gemini = Dictionary::from_iter([("args", Array::from_iter(["--acp"]))]);
+    let agents = Dictionary::from_iter([("gemini", gemini)]);
+    assert_eq!(
+        error(options(vec![("agents", agents.into())])),
+        "Missing \"agents.gemini.command\""
+    );
+}
+
+#[test]
+fn test_invalid_agent_args() {
+    let gemini = Dictionary::from_iter([("command", Object::from("gemini")), ("args", 1.into())]);
+    let agents = Dictionary::from_iter([("gemini", gemini)]);
+    assert_eq!(
+        error(options(vec![("agents", agents.into())])),
+        "Invalid input for \"agents.gemini.args\", must be a list of strings"
+    );
+}
+
+#[test]
+fn test_invalid_permission_rule() {
+    let permissions = Dictionary::from_iter([("execute", "sometimes")]);
+    assert_eq!(
+        error(options(vec![("permissions", permissions.into())])),
+        "Invalid input for \"permissions.execute\", must be one of: ask, allow, deny"
+    );
+}
+
+#[test]
+fn test_fs_roots_resolve_relative_paths() {
+    let roots = Array::from_iter(["/work/project", "docs"]);
+    let setup = Setup::from_dictionary(options(vec![("fs_roots", roots.into())])).unwrap();
+
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(
+        setup.fs_roots,
+        vec![PathBuf::from("/work/project"), cwd.join("docs")]
+    );
+}
+
+#[test]
+fn test_project_overrides_user_config() {
+    let user_agents = Dictionary::from_iter([
+        ("gemini", Dictionary::from_iter([("command", "gemini")])),
+        ("claude", Dictionary::from_iter([("command", "claude-acp")])),
+    ]);
+    let user_permissions = Dictionary::from_iter([("default", "deny"), ("read", "allow")]);
+    let mut setup = Setup::from_dictionary(options(vec![
+        ("agents", user_agents.into()),
+        ("permissions", user_permissions.into()),
+        ("fs_roots", Array::from_iter(["/work"]).into()),
+    ]))
+    .unwrap();
+
+    let project_agents = Dictionary::from_iter([(
+        "gemini",
+        Dictionary::from_iter([("command", "./bin/gemini")]),
+    )]);
+    let project_permissions = Dictionary::from_iter([("execute", "ask")]);
+    setup
+        .merge_project(
+            options(vec![
+                ("agents", project_agents.into()),
+                ("permissions", project_permissions.into()),
+                ("fs_roots", Array::from_iter(["src"]).into()),
+            ]),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        setup.agents[&Assistant::from("gemini")],
+        AgentDefinition::new("./bin/gemini", Vec::<String>::new())
+    );
+    assert_eq!(
+        setup.agents[&Assistant::from("claude")],
+        AgentDefinition::new("claude-acp", Vec::<String>::new())
+    );
+    assert_eq!(setup.permissions.rule_for(None), PermissionRule::Deny);
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Read)),
+        PermissionRule::Allow
+    );
+    // Asking is looser than the user's default
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Execute)),
+        PermissionRule::Deny
+    );
+    assert_eq!(setup.fs_roots, vec![PathBuf::from("/work/project/src")]);
+}
+
+#[test]
+fn test_project_permissions_only_tighten() {
+    let user_permissions =
+        Dictionary::from_iter([("default", "allow"), ("read", "allow"), ("execute", "ask")]);
+    let mut setup =
+        Setup::from_dictionary(options(vec![("permissions", user_permissions.into())])).unwrap();
+
+    let project_permissions =
+        Dictionary::from_iter([("default", "ask"), ("read", "deny"), ("execute", "allow")]);
+    setup
+        .merge_project(
+            options(vec![("permissions", project_permissions.into())]),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+
+    assert_eq!(setup.permissions.rule_for(None), PermissionRule::Ask);
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Read)),
+        PermissionRule::Deny
+    );
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Execute)),
+        PermissionRule::Ask
+    );
+    assert_eq!(
+        setup.permissions.rule_for(Some(ToolKind::Edit)),
+        PermissionRule::Ask
+    );
+}
+
+#[test]
+fn test_project_fs_roots_stay_within_the_user_roots() {
+    let user_roots = Array::from_iter(["/work/project/src", "/home"]);
+    let setup = Setup::from_dictionary(options(vec![("fs_roots", user_roots.into())])).unwrap();
+
+    let mut narrowed = setup.clone();
+    narrowed
+        .merge_project(
+            options(vec![(
+                "fs_roots",
+                Array::from_iter([".", "/etc", "/home/user/docs"]).into(),
+            )]),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+    assert_eq!(
+        narrowed.fs_roots,
+        vec![
+            PathBuf::from("/work/project/src"),
+            PathBuf::from("/home/user/docs")
+        ]
+    );
+
+    let mut outside = setup.clone();
+    let result = outside.merge_project(
+        options(vec![("fs_roots", Array::from_iter(["/etc"]).into())]),
+        Path::new("/work/project"),
+    );
+    match result {
+        Err(nvim_oxi::lua::Error::RuntimeError(message)) => assert_eq!(
+            message,
+            "\"fs_roots\" must lie within the ones passed to setup"
+        ),
+        other => panic!("expected a runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_project_fs_roots_apply_when_the_user_has_none() {
+    let mut setup = Setup::default();
+    setup
+        .merge_project(
+            options(vec![("fs_roots", Array::from_iter(["src"]).into())]),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+
+    assert_eq!(setup.fs_roots, vec![PathBuf::from("/work/project/src")]);
+}
+
+#[test]
+fn test_project_cannot_change_capabilities() {
+    let mut setup = Setup::default();
+    let capabilities = Dictionary::from_iter([("terminal", true)]);
+    let result = setup.merge_project(
+        options(vec![("capabilities", capabilities.into())]),
+        Path::new("/work/project"),
+    );
+
+    match result {
+        Err(nvim_oxi::lua::Error::RuntimeError(message)) => assert_eq!(
+            message,
+            "Unknown option \"capabilities\", expected one of: agents, fs_roots, permissions, mcp_servers"
+        ),
+        other => panic!("expected a runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_mcp_servers() {
+    let tools = Dictionary::from_iter([
+        ("command", Object::from("mcp-tools")),
+        ("args", Array::from_iter(["--stdio"]).into()),
+        ("env", Dictionary::from_iter([("TOKEN", "secret")]).into()),
+    ]);
+    let docs = Dictionary::from_iter([
+        ("type", Object::from("http")),
+        ("url", Object::from("https://docs.example.com/mcp")),
+        (
+            "headers",
+            Dictionary::from_iter([("Authorization", "Bearer token")]).into(),
+        ),
+    ]);
+    let setup = Setup::from_dictionary(options(vec![(
+        "mcp_servers",
+        Dictionary::from_iter([("tools", tools), ("docs", docs)]).into(),
+    )]))
+    .unwrap();
+
+    assert_eq!(
+        setup.mcp_servers,
+        vec![
+            McpServer::Http(
+                McpServerHttp::new("docs", "https://docs.example.com/mcp")
+                    .headers(vec![HttpHeader::new("Authorization", "Bearer token")])
+            ),
+            McpServer::Stdio(
+                McpServerStdio::new("tools", "mcp-tools")
+                    .args(vec!["--stdio".to_string()])
+                    .env(vec![EnvVariable::new("TOKEN", "secret")])
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_mcp_servers() {
+    let missing_url = Dictionary::from_iter([("type", "sse")]);
+    assert_eq!(
+        error(options(vec![(
+            "mcp_servers",
+            Dictionary::from_iter([("docs", missing_url)]).into()
+        )])),
+        "Missing \"mcp_servers.docs.url\""
+    );
+
+    let unknown_type = Dictionary::from_iter([("type", "websocket")]);
+    assert_eq!(
+        error(options(vec![(
+            "mcp_servers",
+            Dictionary::from_iter([("docs", unknown_type)]).into()
+        )])),
+        "Invalid input for \"mcp_servers.docs.type\", must be one of: stdio, http, sse"
+    );
+
+    let url_for_stdio = Dictionary::from_iter([("command", "mcp-tools"), ("url", "http://x")]);
+    assert_eq!(
+        error(options(vec![(
+            "mcp_servers",
+            Dictionary::from_iter([("tools", url_for_stdio)]).into()
+        )])),
+        "Unknown option \"mcp_servers.tools.url\", expected one of: type, command, args, env"
+    );
+}
+
+#[test]
+fn test_project_mcp_servers_replace_by_name() {
+    let mut setup = Setup::from_dictionary(options(vec![(
+        "mcp_servers",
+        Dictionary::from_iter([
+            ("tools", Dictionary::from_iter([("command", "mcp-tools")])),
+            ("search", Dictionary::from_iter([("command", "mcp-search")])),
+        ])
+        .into(),
+    )]))
+    .unwrap();
+
+    setup
+        .merge_project(
+            options(vec![(
+                "mcp_servers",
+                Dictionary::from_iter([(
+                    "tools",
+                    Dictionary::from_iter([("command", "./bin/tools")]),
+                )])
+                .into(),
+            )]),
+            Path::new("/work/project"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        setup.mcp_servers,
+        vec![
+            McpServer::Stdio(McpServerStdio::new("search", "mcp-search")),
+            McpServer::Stdio(McpServerStdio::new("tools", "./bin/tools")),
+        ]
+    );
+}
@@ -0,0 +1,205 @@
+use agent_client_protocol::{AgentCapabilities, InitializeResponse, ProtocolVersion, SessionId};
+use hermes::{
+    apc::{
+        connection::{Assistant, ConnectionId, ConnectionInfo, Protocol},
+        status::{ConnectionState, StatusTracker},
+    },
+    nvim::command::status_line,
+};
+
+fn initialize() -> InitializeResponse {
+    InitializeResponse::new(ProtocolVersion::LATEST)
+        .agent_capabilities(AgentCapabilities::new().load_session(true))
+}
+
+fn connection(id: &str, agent: Assistant, label: Option<&str>) -> ConnectionInfo {
+    ConnectionInfo {
+        id: ConnectionId::from(id),
+        agent,
+        label: label.map(ToString::to_string),
+    }
+}
+
+#[test]
+fn test_unknown_connection_has_no_status() {
+    let tracker = StatusTracker::new();
+    assert!(tracker.get(&ConnectionId::from("copilot-1")).is_none());
+    assert!(tracker.connections().is_empty());
+}
+
+#[test]
+fn test_connection_lifecycle() {
+    let mut tracker = StatusTracker::new();
+    let connection = connection("opencode-1", Assistant::Opencode, None);
+    let id = &connection.id;
+
+    tracker.connecting(&connection, Protocol::Stdio);
+    let status = tracker.get(id).unwrap();
+    assert_eq!(status.state, ConnectionState::Connecting);
+    assert_eq!(status.agent, Assistant::Opencode);
+
+    tracker.initialized(id, Some(42), &initialize());
+    let status = tracker.get(id).unwrap();
+    assert_eq!(status.state, ConnectionState::Initialized);
+    assert_eq!(status.pid, Some(42));
+    assert_eq!(status.protocol_version, Some(ProtocolVersion::LATEST));
+    assert!(status.capabilities.as_ref().unwrap().load_session);
+
+    tracker.authenticated(id);
+    assert_eq!(
+        tracker.get(id).unwrap().state,
+        ConnectionState::Authenticated
+    );
+
+    tracker.session_created(id, &SessionId::new("sess-1"));
+    tracker.disconnected(id);
+    let status = tracker.get(id).unwrap();
+    assert_eq!(status.state, ConnectionState::Disconnected);
+    assert_eq!(status.pid, None);
+    assert!(status.sessions.is_empty());
+}
+
+#[test]
+fn test_failed_connection_is_disconnected() {
+    let mut tracker = StatusTracker::new();
+    let connection = connection("gemini-1", Assistant::from("gemini"), None);
+
+    tracker.connecting(&connection, Protocol::Stdio);
+    tracker.failed(&connection.id, "No such file or directory");
+
+    let status = tracker.get(&connection.id).unwrap();
+    assert_eq!(status.state, ConnectionState::Disconnected);
+    assert_eq!(
+        status.last_error.as_deref(),
+        Some("No such file or directory")
+    );
+}
+
+#[test]
+fn test_failed_request_keeps_connection_state() {
+    let mut tracker = StatusTracker::new();
+    let connection = connection("copilot-1", Assistant::Copilot, None);
+
+    tracker.connecting(&connection, Protocol::Stdio);
+    tracker.initialized(&connection.id, None, &initialize());
+    tracker.failed(&connection.id, "Authentication required");
+
+    let status = tracker.get(&connection.id).unwrap();
+    assert_eq!(status.state, ConnectionState::Initialized);
+    assert_eq!(
+        status.last_error.as_deref(),
+        Some("Authentication required")
+    );
+}
+
+#[test]
+fn test_reconnecting_replaces_the_closed_connection() {
+    let mut tracker = StatusTracker::new();
+    let first = connection("copilot-1", Assistant::Copilot, None);
+    let second = connection("copilot-2", Assistant::Copilot, None);
+
+    tracker.connecting(&first, Protocol::Stdio);
+    tracker.failed(&first.id, "boom");
+    tracker.connecting(&second, Protocol::Stdio);
+
+    assert!(tracker.get(&first.id).is_none());
+    assert_eq!(tracker.get(&second.id).unwrap().last_error, None);
+}
+
+#[test]
+fn test_connections_to_the_same_agent_are_tracked_apart() {
+    let mut tracker = StatusTracker::new();
+    let main = connection("copilot-1", Assistant::Copilot, Some("main"));
+    let review = connection("copilot-2", Assistant::Copilot, Some("review"));
+    let session = SessionId::new("sess-1");
+
+    tracker.connecting(&main, Protocol::Stdio);
+    tracker.connecting(&review, Protocol::Stdio);
+    tracker.initialized(&main.id, Some(1), &initialize());
+    tracker.initialized(&review.id, Some(2), &initialize());
+    tracker.session_created(&review.id, &session);
+    tracker.disconnected(&main.id);
+
+    let status = tracker.get(&review.id).unwrap();
+    assert_eq!(status.label.as_deref(), Some("review"));
+    assert_eq!(status.pid, Some(2));
+    assert_eq!(status.sessions, vec![session]);
+    assert_eq!(
+        tracker.get(&main.id).unwrap().state,
+        ConnectionState::Disconnected
+    );
+}
+
+#[test]
+fn test_turns_in_flight() {
+    let mut tracker = StatusTracker::new();
+    let connection = connection("copilot-1", Assistant::Copilot, None);
+    let id = &connection.id;
+    let session = SessionId::new("sess-1");
+
+    tracker.connecting(&connection, Protocol::Stdio);
+    tracker.session_created(id, &session);
+    tracker.session_created(id, &session);
+    tracker.turn_started(id, &session);
+
+    let status = tracker.get(id).unwrap();
+    assert_eq!(status.sessions, vec![session.clone()]);
+    assert_eq!(status.prompting, vec![session.clone()]);
+
+    tracker.turn_finished(id, &session);
+    assert!(tracker.get(id).unwrap().prompting.is_empty());
+}
+
+#[test]
+fn test_connections_keep_their_order() {
+    let mut tracker = StatusTracker::new();
+    tracker.connecting(
+        &connection("opencode-1", Assistant::Opencode, None),
+        Protocol::Stdio,
+    );
+    tracker.connecting(
+        &connection("copilot-2", Assistant::Copilot, None),
+        Protocol::Stdio,
+    );
+
+    let ids: Vec<String> = tracker
+        .connections()
+        .into_iter()
+        .map(|(id, _)| id.to_string())
+        .collect();
+    assert_eq!(ids, vec!["opencode-1", "copilot-2"]);
+}
+
+#[test]
+fn test_status_line() {
+    let mut tracker = StatusTracker::new();
+    let connection = connection("copilot-1", Assistant::Copilot, None);
+    let id = &connection.id;
+    tracker.connecting(&connection, Protocol::Stdio);
+    tracker.initialized(id, Some(42), &initialize());
+    tracker.session_created(id, &SessionId::new("sess-1"));
+
+    assert_eq!(
+        status_line(id, tracker.get(id).unwrap()),
+        "copilot-1: initialized, pid 42, 1 session(s)"
+    );
+
+    tracker.disconnected(id);
+    tracker.failed(id, "boom");
+    assert_eq!(
+        status_line(id, tracker.get(id).unwrap()),
+        "copilot-1: disconnected, last error: boom"
+    );
+}
+
+#[test]
+fn test_status_line_with_label() {
+    let mut tracker = StatusTracker::new();
+    let connection = connection("copilot-3", Assistant::Copilot, Some("review"));
+    tracker.connecting(&connection, Protocol::Stdio);
+
+    assert_eq!(
+        status_line(&connection.id, tracker.get(&connection.id).unwrap()),
+        "copilot-3 (review): connecting, 0 session(s)"
+    );
+}
@@ -0,0 +1,189 @@
+use agent_client_protocol::{
+    Content, ContentBlock, SessionId, TextContent, ToolCall, ToolCallContent, ToolCallId,
+    ToolCallStatus, ToolCallUpdate, ToolCallUpdateFields, ToolKind,
+};
+use hermes::apc::tool_calls::ToolCallTracker;
+
+fn text(value: &str) -> ToolCallContent {
+    ToolCallContent::Content(Content::new(ContentBlock::Text(TextContent::new(value))))
+}
+
+fn session() -> SessionId {
+    SessionId::new("session_1")
+}
+
+#[test]
+fn test_record_tool_call() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(&session(), ToolCall::new(ToolCallId::new("call_1"), "Read"));
+
+    let tool_calls = tracker.tool_calls(&session());
+    assert_eq!(tool_calls.len(), 1);
+    assert_eq!(tool_calls[0].title, "Read");
+}
+
+#[test]
+fn test_tool_calls_for_unknown_session_is_empty() {
+    let tracker = ToolCallTracker::new();
+    assert!(tracker.tool_calls(&session()).is_empty());
+}
+
+#[test]
+fn test_record_replaces_existing_tool_call() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(
+        &session(),
+        ToolCall::new(ToolCallId::new("call_1"), "First"),
+    );
+    tracker.record(
+        &session(),
+        ToolCall::new(ToolCallId::new("call_1"), "Second"),
+    );
+
+    let tool_calls = tracker.tool_calls(&session());
+    assert_eq!(tool_calls.len(), 1);
+    assert_eq!(tool_calls[0].title, "Second");
+}
+
+#[test]
+fn test_tool_calls_keep_reported_order() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(&session(), ToolCall::new(ToolCallId::new("call_2"), "B"));
+    tracker.record(&session(), ToolCall::new(ToolCallId::new("call_1"), "A"));
+
+    let titles: Vec<String> = tracker
+        .tool_calls(&session())
+        .into_iter()
+        .map(|tool_call| tool_call.title)
+        .collect();
+    assert_eq!(titles, vec!["B", "A"]);
+}
+
+#[test]
+fn test_apply_overwrites_fields() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(
+        &session(),
+        ToolCall::new(ToolCallId::new("call_1"), "Edit")
+            .kind(ToolKind::Edit)
+            .status(ToolCallStatus::Pending),
+    );
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().status(ToolCallStatus::InProgress),
+    );
+    let merged = tracker.apply(&session(), update).unwrap();
+
+    assert_eq!(merged.status, ToolCallStatus::InProgress);
+    assert_eq!(merged.kind, ToolKind::Edit);
+    assert_eq!(merged.title, "Edit");
+}
+
+#[test]
+fn test_apply_replaces_content() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(
+        &session(),
+        ToolCall::new(ToolCallId::new("call_1"), "Run").content(vec![text("first")]),
+    );
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().content(vec![text("second")]),
+    );
+    let merged = tracker.apply(&session(), update).unwrap();
+
+    assert_eq!(merged.content, vec![text("second")]);
+}
+
+#[test]
+fn test_apply_keeps_content_when_absent() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(
+        &session(),
+        ToolCall::new(ToolCallId::new("call_1"), "Run").content(vec![text("first")]),
+    );
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().status(ToolCallStatus::Completed),
+    );
+    let merged = tracker.apply(&session(), update).unwrap();
+
+    assert_eq!(merged.content, vec![text("first")]);
+}
+
+#[test]
+fn test_apply_updates_stored_state() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(&session(), ToolCall::new(ToolCallId::new("call_1"), "Run"));
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().status(ToolCallStatus::Failed),
+    );
+    tracker.apply(&session(), update);
+
+    let stored = tracker.get(&session(), &ToolCallId::new("call_1")).unwrap();
+    assert_eq!(stored.status, ToolCallStatus::Failed);
+}
+
+#[test]
+fn test_apply_unknown_tool_call_with_title_is_recorded() {
+    let mut tracker = ToolCallTracker::new();
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().title("Late".to_string()),
+    );
+    let merged = tracker.apply(&session(), update).unwrap();
+
+    assert_eq!(merged.title, "Late");
+    assert_eq!(tracker.tool_calls(&session()).len(), 1);
+}
+
+#[test]
+fn test_apply_unknown_tool_call_without_title_is_ignored() {
+    let mut tracker = ToolCallTracker::new();
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().status(ToolCallStatus::Completed),
+    );
+
+    assert!(tracker.apply(&session(), update).is_none());
+    assert!(tracker.tool_calls(&session()).is_empty());
+}
+
+#[test]
+fn test_sessions_are_tracked_separately() {
+    let mut tracker = ToolCallTracker::new();
+    let other = SessionId::new("session_2");
+    tracker.record(&session(), ToolCall::new(ToolCallId::new("call_1"), "A"));
+    tracker.record(&other, ToolCall::new(ToolCallId::new("call_1"), "B"));
+
+    let update = ToolCallUpdate::new(
+        ToolCallId::new("call_1"),
+        ToolCallUpdateFields::new().status(ToolCallStatus::Completed),
+    );
+    tracker.apply(&other, update);
+
+    assert_eq!(
+        tracker.tool_calls(&session())[0].status,
+        ToolCallStatus::Pending
+    );
+    assert_eq!(
+        tracker.tool_calls(&other)[0].status,
+        ToolCallStatus::Completed
+    );
+}
+
+#[test]
+fn test_clear_session() {
+    let mut tracker = ToolCallTracker::new();
+    tracker.record(&session(), ToolCall::new(ToolCallId::new("call_1"), "A"));
+    tracker.clear(&session());
+
+    assert!(tracker.tool_calls(&session()).is_empty());
+}
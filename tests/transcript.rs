@@ -0,0 +1,139 @@
+use agent_client_protocol::{ContentBlock, ImageContent, SessionId, TextContent};
+use hermes::apc::transcript::{Message, MessageRole, Transcript};
+
+fn text(value: &str) -> ContentBlock {
+    ContentBlock::Text(TextContent::new(value))
+}
+
+fn image() -> ContentBlock {
+    ContentBlock::Image(ImageContent::new("data", "image/png"))
+}
+
+fn session() -> SessionId {
+    SessionId::new("session_1")
+}
+
+#[test]
+fn test_push_concatenates_text_chunks() {
+    let mut transcript = Transcript::new();
+    transcript.push(&session(), MessageRole::Agent, text("Hello, "));
+    transcript.push(&session(), MessageRole::Agent, text("world"));
+
+    let pending = transcript.pending(&session()).unwrap();
+    assert_eq!(pending.text(), "Hello, world");
+    assert_eq!(pending.content.len(), 1);
+}
+
+#[test]
+fn test_push_returns_nothing_while_streaming() {
+    let mut transcript = Transcript::new();
+
+    assert!(
+        transcript
+            .push(&session(), MessageRole::Agent, text("a"))
+            .is_none()
+    );
+    assert!(
+        transcript
+            .push(&session(), MessageRole::Agent, text("b"))
+            .is_none()
+    );
+    assert!(transcript.messages(&session()).is_empty());
+}
+
+#[test]
+fn test_role_change_completes_message() {
+    let mut transcript = Transcript::new();
+    transcript.push(&session(), MessageRole::Thought, text("thinking"));
+    let completed = transcript
+        .push(&session(), MessageRole::Agent, text("answer"))
+        .unwrap();
+
+    assert_eq!(completed.role, MessageRole::Thought);
+    assert_eq!(completed.text(), "thinking");
+    assert_eq!(transcript.messages(&session()).len(), 1);
+    assert_eq!(
+        transcript.pending(&session()).unwrap().role,
+        MessageRole::Agent
+    );
+}
+
+#[test]
+fn test_content_type_change_completes_message() {
+    let mut transcript = Transcript::new();
+    transcript.push(&session(), MessageRole::Agent, text("Look at this"));
+    let completed = transcript.push(&session(), MessageRole::Agent, image());
+
+    assert_eq!(completed.unwrap().text(), "Look at this");
+    assert_eq!(
+        transcript.pending(&session()).unwrap().content,
+        vec![image()]
+    );
+}
+
+#[test]
+fn test_complete_closes_pending_message() {
+    let mut transcript = Transcript::new();
+    transcript.push(&session(), MessageRole::Agent, text("done"));
+
+    let completed = transcript.complete(&session()).unwrap();
+    assert_eq!(completed.text(), "done");
+    assert!(transcript.pending(&session()).is_none());
+    assert_eq!(transcript.messages(&session()), vec![completed]);
+}
+
+#[test]
+fn test_complete_without_pending_message() {
+    let mut transcript = Transcript::new();
+    assert!(transcript.complete(&session()).is_none());
+}
+
+#[test]
+fn test_record_completes_pending_message() {
+    let mut transcript = Transcript::new();
+    transcript.push(&session(), MessageRole::Agent, text("previous"));
+
+    let completed = transcript.record(
+        &session(),
+        Message::new(MessageRole::User, vec![text("next question")]),
+    );
+
+    assert_eq!(completed.unwrap().text(), "previous");
+    let messages = transcript.messages(&session());
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].role, MessageRole::User);
+}
+
+#[test]
+fn test_sessions_are_separate() {
+    let mut transcript = Transcript::new();
+    let other = SessionId::new("session_2");
+    transcript.push(&session(), MessageRole::Agent, text("one"));
+    transcript.push(&other, MessageRole::Agent, text("two"));
+
+    assert_eq!(transcript.pending(&session()).unwrap().text(), "one");
+    assert_eq!(transcript.pending(&other).unwrap().text(), "two");
+}
+
+#[test]
+fn test_clear_session() {
+    let mut transcript = Transcript::new();
+    transcript.push(&session(), MessageRole::Agent, text("one"));
+    transcript.complete(&session());
+    transcript.clear(&session());
+
+    assert!(transcript.messages(&session()).is_empty());
+}
+
+#[test]
+fn test_message_text_skips_non_text_blocks() {
+    let message = Message::new(MessageRole::Agent, vec![text("a"), image(), text("b")]);
+    assert_eq!(message.text(), "ab");
+}
+
+#[test]
+fn test_message_role_display() {
+    assert_eq!(MessageRole::User.to_string(), "user");
+    assert_eq!(MessageRole::Agent.to_string(), "agent");
+    assert_eq!(MessageRole::Thought.to_string(), "thought");
+}
@@ -0,0 +1,384 @@
+//! Requests made while a prompt turn is waited on, against an agent running in-process
+
+use agent_client_protocol::{
+    Agent, AgentSideConnection, AudioContent, AuthenticateRequest, AuthenticateResponse,
+    CancelNotification, Client, ContentBlock, InitializeRequest, InitializeResponse,
+    NewSessionRequest, NewSessionResponse, PermissionOption, PermissionOptionKind, PromptRequest,
+    PromptResponse, ProtocolVersion, RequestPermissionOutcome, RequestPermissionRequest,
+    RequestPermissionResponse, SessionId, SessionModeId, SessionNotification, StopReason,
+    ToolCallUpdate, ToolCallUpdateFields,
+};
+use async_trait::async_trait;
+use hermes::apc::connection::{Assistant, ConnectionDetails, ConnectionId, ConnectionManager};
+use hermes::nvim::context::text_resource;
+use hermes::nvim::mcp::{Editor, Position, Server};
+use hermes::{ApcClient, ClientConfig};
+use serde_json::{Value, json};
+use std::{
+    cell::{OnceCell, RefCell},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::Notify;
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+/// Asks for permission during every prompt, the turn ends shortly after the client answered or
+/// as soon as it is cancelled
+///
+/// Given the editor's MCP socket, it first asks for the cursor through it like a tool call would.
+#[derive(Clone, Default)]
+struct FakeAgent {
+    client: Rc<OnceCell<AgentSideConnection>>,
+    cancelled: Rc<Notify>,
+    mcp_socket: Option<PathBuf>,
+    tool_results: Rc<RefCell<Vec<Value>>>,
+}
+
+impl FakeAgent {
+    async fn call_cursor_tool(&self, socket: &Path) -> std::io::Result<Value> {
+        let stream = tokio::net::UnixStream::connect(socket).await?;
+        let (read, mut write) = stream.into_split();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": "cursor", "arguments": {} }
+        });
+        write.write_all(format!("{}\n", request).as_bytes()).await?;
+        let mut line = String::new();
+        BufReader::new(read).read_line(&mut line).await?;
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+#[async_trait(?Send)]
+impl Agent for FakeAgent {
+    async fn initialize(
+        &self,
+        _args: InitializeRequest,
+    ) -> agent_client_protocol::Result<InitializeResponse> {
+        Ok(InitializeResponse::new(ProtocolVersion::LATEST))
+    }
+
+    async fn authenticate(
+        &self,
+        _args: AuthenticateRequest,
+    ) -> agent_client_protocol::Result<AuthenticateResponse> {
+        Ok(AuthenticateResponse::new())
+    }
+
+    async fn new_session(
+        &self,
+        _args: NewSessionRequest,
+    ) -> agent_client_protocol::Result<NewSessionResponse> {
+        Ok(NewSessionResponse::new("session-1"))
+    }
+
+    async fn prompt(&self, args: PromptRequest) -> agent_client_protocol::Result<PromptResponse> {
+        if let Some(socket) = &self.mcp_socket {
+            let result = self
+                .call_cursor_tool(socket)
+                .await
+                .map_err(|_| agent_client_protocol::Error::internal_error())?;
+            self.tool_results.borrow_mut().push(result);
+        }
+        let client = self.client.get().expect("the agent is connected");
+        client
+            .request_permission(RequestPermissionRequest::new(
+                args.session_id,
+                ToolCallUpdate::new("call-1", ToolCallUpdateFields::new()),
+                vec![PermissionOption::new(
+                    "allow",
+                    "Allow",
+                    PermissionOptionKind::AllowOnce,
+                )],
+            ))
+            .await?;
+        match tokio::time::timeout(Duration::from_millis(100), self.cancelled.notified()).await {
+            Ok(_) => Ok(PromptResponse::new(StopReason::Cancelled)),
+            Err(_) => Ok(PromptResponse::new(StopReason::EndTurn)),
+        }
+    }
+
+    async fn cancel(&self, _args: CancelNotification) -> agent_client_protocol::Result<()> {
+        self.cancelled.notify_one();
+        Ok(())
+    }
+}
+
+type Manager = ConnectionManager<Handler>;
+
+/// Calls back into the manager when asked for permission, like an autocommand callback would
+#[derive(Clone, Default)]
+struct Handler {
+    manager: Rc<OnceCell<Rc<Mutex<Manager>>>>,
+    /// Whether to cancel the turn instead of changing mode
+    cancel: bool,
+    observed: Rc<RefCell<Vec<String>>>,
+}
+
+#[async_trait(?Send)]
+impl Client for Handler {
+    async fn request_permission(
+        &self,
+        args: RequestPermissionRequest,
+    ) -> agent_client_protocol::Result<RequestPermissionResponse> {
+        let manager = self.manager.get().expect("the handler knows the manager");
+        let handle = {
+            let manager = manager
+                .try_lock()
+                .expect("the manager isn't locked during the turn");
+            let id = manager
+                .session_connection(&args.session_id)
+                .expect("the session is known");
+            manager.handle(&id).expect("the connection is open")
+        };
+        let mut observed = self.observed.borrow_mut();
+        observed.push("unlocked".to_string());
+        let result = if self.cancel {
+            handle.cancel(args.session_id)
+        } else {
+            handle
+                .set_mode(args.session_id, SessionModeId::new("plan"))
+                .map(|_| ())
+        };
+        if let Err(error) = result {
+            observed.push(error.to_string());
+        }
+        Ok(RequestPermissionResponse::new(
+            RequestPermissionOutcome::Cancelled,
+        ))
+    }
+
+    async fn session_notification(
+        &self,
+        _args: SessionNotification,
+    ) -> agent_client_protocol::Result<()> {
+        Ok(())
+    }
+}
+
+/// A manager connected to `agent`, with a session created on it
+#[allow(clippy::arc_with_non_send_sync)]
+fn connect(handler: &Handler, agent: FakeAgent) -> (Rc<Mutex<Manager>>, ConnectionId, SessionId) {
+    let mut manager = ConnectionManager::new(Arc::new(ApcClient::new(
+        ClientConfig::default(),
+        handler.clone(),
+    )))
+    .unwrap();
+    let (client_io, agent_io) = tokio::io::duplex(4096);
+    let (client_read, client_write) = tokio::io::split(client_io);
+    let (agent_read, agent_write) = tokio::io::split(agent_io);
+    manager.spawn_local(async move {
+        let (client, handle_io) = AgentSideConnection::new(
+            agent.clone(),
+            agent_write.compat_write(),
+            agent_read.compat(),
+            |task| {
+                tokio::task::spawn_local(task);
+            },
+        );
+        let _ = agent.client.set(client);
+        let _ = handle_io.await;
+    });
+    let id = ConnectionId::from("fake-1");
+    manager
+        .connect_streams(
+            id.clone(),
+            ConnectionDetails {
+                agent: Assistant::Custom("fake".to_string()),
+                ..ConnectionDetails::default()
+            },
+            client_write,
+            client_read,
+        )
+        .unwrap();
+    let session_id = manager
+        .new_session(&id, std::env::temp_dir(), Vec::new())
+        .unwrap()
+        .session_id;
+    let manager = Rc::new(Mutex::new(manager));
+    let _ = handler.manager.set(manager.clone());
+    (manager, id, session_id)
+}
+
+#[test]
+fn test_handler_calls_back_into_the_manager_during_a_turn() {
+    let handler = Handler::default();
+    let (manager, id, session_id) = connect(&handler, FakeAgent::default());
+
+    let handle = manager.lock().unwrap().handle(&id).unwrap();
+    let response = handle.prompt(session_id, vec!["Hello".into()]).unwrap();
+
+    assert_eq!(response.stop_reason, StopReason::EndTurn);
+    let observed = handler.observed.borrow();
+    assert_eq!(observed[0], "unlocked");
+    assert!(
+        observed[1].contains("already waiting for one"),
+        "{:?}",
+        observed
+    );
+}
+
+#[test]
+fn test_cancel_during_a_turn() {
+    let handler = Handler {
+        cancel: true,
+        ..Handler::default()
+    };
+    let (manager, id, session_id) = connect(&handler, FakeAgent::default());
+
+    let handle = manager.lock().unwrap().handle(&id).unwrap();
+    let response = handle.prompt(session_id, vec!["Hello".into()]).unwrap();
+
+    assert_eq!(response.stop_reason, StopReason::Cancelled);
+    assert_eq!(*handler.observed.borrow(), vec!["unlocked".to_string()]);
+}
+
+/// Answers the cursor tool, the other tools aren't used by the agent
+struct CursorEditor;
+
+impl Editor for CursorEditor {
+    fn diagnostics(&self, _path: Option<&str>) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn definitions(&self, _position: &Position) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn references(&self, _position: &Position) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn buffers(&self) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn quickfix(&self) -> Result<Value, String> {
+        Ok(json!([]))
+    }
+
+    fn selection(&self) -> Result<Value, String> {
+        Ok(Value::Null)
+    }
+
+    fn cursor(&self) -> Result<Value, String> {
+        Ok(json!({ "path": "src/main.rs", "line": 7, "column": 3 }))
+    }
+}
+
+#[test]
+fn test_editor_mcp_server_answers_during_a_turn() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("hermes.sock");
+    let (server, _woken) = Server::bind(&socket).unwrap();
+    let agent = FakeAgent {
+        mcp_socket: Some(socket),
+        ..FakeAgent::default()
+    };
+    let handler = Handler::default();
+    let (manager, id, session_id) = connect(&handler, agent.clone());
+    manager
+        .lock()
+        .unwrap()
+        .spawn_local(server.serve(CursorEditor));
+
+    let handle = manager.lock().unwrap().handle(&id).unwrap();
+    let response = handle.prompt(session_id, vec!["Hello".into()]).unwrap();
+
+    assert_eq!(response.stop_reason, StopReason::EndTurn);
+    let results = agent.tool_results.borrow();
+    assert_eq!(results[0]["id"], json!(1));
+    assert_eq!(results[0]["result"]["isError"], json!(false));
+    let text = results[0]["result"]["content"][0]["text"].as_str().unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(text).unwrap(),
+        json!({ "path": "src/main.rs", "line": 7, "column": 3 })
+    );
+}
+
+#[test]
+fn test_spawned_turn_is_driven_and_cancelled_between_ticks() {
+    let handler = Handler::default();
+    let (manager, id, session_id) = connect(&handler, FakeAgent::default());
+    let (handle, driver, status) = {
+        let manager = manager.lock().unwrap();
+        (
+            manager.handle(&id).unwrap(),
+            manager.driver(),
+            manager.status(),
+        )
+    };
+    let finished = Rc::new(RefCell::new(None));
+
+    let done = finished.clone();
+    handle.spawn_prompt(session_id.clone(), vec!["Hello".into()], move |response| {
+        *done.borrow_mut() = Some(response.map(|response| response.stop_reason));
+    });
+    assert!(status.lock().unwrap().is_prompting());
+    // The agent asked for permission once the handler ran, it waits for a cancellation now
+    while handler.observed.borrow().is_empty() {
+        driver.run_for(Duration::from_millis(5)).unwrap();
+    }
+    handle.cancel(session_id).unwrap();
+    while finished.borrow().is_none() {
+        driver.run_for(Duration::from_millis(5)).unwrap();
+    }
+
+    let stop_reason = finished.borrow_mut().take().unwrap().unwrap();
+    assert_eq!(stop_reason, StopReason::Cancelled);
+    assert!(!status.lock().unwrap().is_prompting());
+}
+
+#[test]
+fn test_audio_is_refused_unless_the_agent_accepts_it() {
+    let handler = Handler::default();
+    let (manager, id, _session_id) = connect(&handler, FakeAgent::default());
+    let manager = manager.lock().unwrap();
+
+    let error = manager
+        .prompt_content(
+            &id,
+            vec![
+                "Transcribe this".into(),
+                ContentBlock::Audio(AudioContent::new("data", "audio/wav")),
+            ],
+        )
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("does not accept audio"),
+        "{}",
+        error
+    );
+    let content = manager.prompt_content(&id, vec!["Hello".into()]).unwrap();
+    assert_eq!(content.len(), 1);
+}
+
+#[test]
+fn test_embedded_resources_are_linked_unless_the_agent_accepts_them() {
+    let handler = Handler::default();
+    let (manager, id, _session_id) = connect(&handler, FakeAgent::default());
+    let manager = manager.lock().unwrap();
+
+    let content = manager
+        .prompt_content(
+            &id,
+            vec![
+                "Look at @diff".into(),
+                text_resource("git://diff", "+ added", "text/x-diff"),
+            ],
+        )
+        .unwrap();
+
+    match &content[1] {
+        ContentBlock::ResourceLink(link) => {
+            assert_eq!(link.uri, "git://diff");
+            assert_eq!(link.mime_type.as_deref(), Some("text/x-diff"));
+        }
+        block => panic!("expected a resource link, got {:?}", block),
+    }
+}